@@ -5,21 +5,32 @@ mod storage_commands;
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{Emitter, Manager, State, AppHandle};
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::TrayIconBuilder;
 use tokio::sync::Mutex;
 use ghost_hand_client::adaptive_bitrate::AdaptiveBitrateController;
 use ghost_hand_client::audit::{audit_log, init_global_logger, AuditEvent, AuditLevel};
-use ghost_hand_client::clipboard::ClipboardManager;
+use ghost_hand_client::clipboard::{ClipboardContent, ClipboardManager};
+use ghost_hand_client::clipboard_filter::{ClipboardDirection, ClipboardFilter};
+use ghost_hand_client::clipboard_watcher::{ClipboardPolicyHandle, ClipboardRole, ClipboardSyncPolicy, ClipboardWatcher};
 use ghost_hand_client::config::{Config, VideoCodec};
-use ghost_hand_client::crypto::{KeyExchange, CryptoManager, derive_session_key, seal_frame, open_frame, session_fingerprint, ENCRYPTED_MAGIC};
-use ghost_hand_client::file_transfer::FileTransferManager;
+use ghost_hand_client::crypto::{KeyExchange, CryptoManager, derive_session_key, FrameCipher, FrameRole, SessionKeyState};
+use ghost_hand_client::error::GhostHandError;
+use ghost_hand_client::file_browser::{BrowseOutcome, BrowseRequests, FileBrowser};
+use ghost_hand_client::file_sender::{AckRegistry, FileSender};
+use ghost_hand_client::file_transfer::{FileOffer, FileTransferManager, OutgoingTransfer};
+use ghost_hand_client::folder_transfer::FolderManifest;
+use ghost_hand_client::transfer_queue::{TransferDirection, TransferInfo, TransferQueue};
+use ghost_hand_client::input_control::{ChannelInputSource, InputController, InputListener, MouseMoveCoalescer, RawInputEvent};
+use ghost_hand_client::input_macro::{InputMacro, MacroAbortHandle, MacroPlayer, MacroRecorder, PlaybackOutcome};
 use ghost_hand_client::network::{generate_device_id, SessionManager};
 use tokio::sync::mpsc as relay_mpsc;
-use ghost_hand_client::protocol::{ControlMessage, DisplayInfoProto};
+use ghost_hand_client::keymap::hid_usage_from_dom_code;
+use ghost_hand_client::protocol::{ControlMessage, DisplayInfoProto, FileBrowserResult, KeyInputMode, ScrollUnit, TouchPhase};
 use ghost_hand_client::storage::{global_storage, init_global_storage, ConnectionHistory};
-use ghost_hand_client::streaming::{Streamer, Receiver, InputHandler};
+use ghost_hand_client::streaming::{Streamer, Receiver, InputHandler, SessionKeyHandle, HostFrameDecoder};
 use ghost_hand_client::screen_capture::{self, ScreenCapturer};
 use ghost_hand_client::video_encoder::{self, VideoEncoder};
 use base64::Engine;
@@ -221,13 +232,25 @@ struct AppState {
     pending_requests: Arc<Mutex<Vec<ConnectionRequest>>>,
     streamer_handle: Arc<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>>,
     discovered_peers: Arc<std::sync::Mutex<Vec<DiscoveredPeer>>>,
-    clipboard_manager: Arc<std::sync::Mutex<ClipboardManager>>,
+    clipboard_manager: ClipboardManager,
+    /// Sens de synchronisation du presse-papiers pour la session courante
+    clipboard_policy: ClipboardPolicyHandle,
+    /// Surveillance automatique du presse-papiers (hôte ou viewer), None si inactive
+    clipboard_watcher: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// Envoi des mouvements souris regroupés de la session sortante, None hors session
+    mouse_flush_task: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     file_transfer_manager: Arc<Mutex<FileTransferManager>>,
+    /// Acquittements des envois de fichiers en cours
+    file_acks: AckRegistry,
+    /// File des transferts (celle du gestionnaire), consultable sans le verrouiller
+    transfer_queue: TransferQueue,
+    /// Requêtes de navigation distante en attente de réponse (côté viewer)
+    browse_requests: BrowseRequests,
     active_capturer: Arc<Mutex<Option<Arc<Mutex<Box<dyn ScreenCapturer>>>>>>,
     active_encoder: Arc<Mutex<Option<Arc<Mutex<Box<dyn VideoEncoder>>>>>>,
     /// Clé de session E2E partagée (dérivée via X25519 ECDH lors du handshake).
-    /// Contient temporairement `b"PENDING:"+priv` pendant le handshake.
-    e2e_session_key: Arc<Mutex<Option<Vec<u8>>>>,
+    /// Contient temporairement la clé privée de l'hôte (`Pending`) pendant le handshake.
+    e2e_session_key: SessionKeyHandle,
     /// Secret d'authentification partagé (raw hash du mot de passe) issu du
     /// challenge-response — lie l'ECDH au mot de passe (anti-MITM). None si pas de mdp.
     e2e_auth_secret: Arc<Mutex<Option<Vec<u8>>>>,
@@ -235,26 +258,384 @@ struct AppState {
     sys_handle: Arc<std::sync::Mutex<System>>,
     /// Canal entrant du transport relay (None si WebRTC ou déconnecté)
     relay_data_tx: Arc<Mutex<Option<relay_mpsc::UnboundedSender<Vec<u8>>>>>,
+    /// ID du pair de la session courante (règles d'input par pair)
+    active_peer_id: Arc<Mutex<Option<String>>>,
+    /// Session en lecture seule (hôte : input et écriture clipboard distants écartés ;
+    /// viewer : annoncée par `PermissionChanged`, suspend la surveillance du presse-papiers)
+    view_only: Arc<AtomicBool>,
+    /// Handler d'input actif (côté hôte) — pour relâcher l'input maintenu
+    input_handler: Arc<Mutex<Option<Arc<InputHandler>>>>,
+    /// Entrée de la capture d'input locale (côté viewer) — None si inactive
+    local_input_tx: Arc<std::sync::Mutex<Option<relay_mpsc::UnboundedSender<RawInputEvent>>>>,
+    /// Mode clavier de la session (côté viewer) + layout annoncé en mode physique
+    keyboard_mode: Arc<Mutex<(KeyInputMode, Option<String>)>>,
+    /// Regroupement des mouvements souris sortants (côté viewer), vidé à chaque tick
+    move_coalescer: Arc<MouseMoveCoalescer>,
+    /// Enregistrement de macro des messages d'input sortants (côté viewer)
+    macro_recorder: Arc<MacroRecorder>,
+    /// Rejeu de macro en cours (poignée d'interruption), None si aucun
+    macro_playback: Arc<std::sync::Mutex<Option<MacroAbortHandle>>>,
 }
 
-/// Extraire la vraie clé de session depuis le state (ignore le sentinel PENDING).
-async fn real_e2e_key(e2e_key: &Arc<Mutex<Option<Vec<u8>>>>) -> Option<Vec<u8>> {
-    let g = e2e_key.lock().await;
-    g.as_ref()
-        .filter(|k| !k.starts_with(b"PENDING:"))
-        .cloned()
+/// Intervalle minimal entre deux envois de mouvements souris regroupés (~60 Hz)
+const MOUSE_MOVE_TICK: std::time::Duration = std::time::Duration::from_millis(16);
+
+/// Démarrer l'envoi des mouvements regroupés pour la session sortante. La tâche
+/// dort tant qu'aucun mouvement n'est en attente ; elle est arrêtée à la déconnexion.
+async fn start_mouse_flush(state: &AppState, transport: ghost_hand_client::network::Transport) {
+    let coalescer = state.move_coalescer.clone();
+    let recorder = state.macro_recorder.clone();
+    let e2e_key = state.e2e_session_key.clone();
+    coalescer.take();
+    let task = tokio::spawn(async move {
+        loop {
+            coalescer.wait().await;
+            if let Err(e) = flush_mouse_move(&coalescer, &recorder, &transport, &e2e_key).await {
+                eprintln!("[INPUT] {}", e);
+            }
+            // Les mouvements reçus pendant ce délai partent ensemble au réveil suivant
+            tokio::time::sleep(MOUSE_MOVE_TICK).await;
+        }
+    });
+    if let Some(previous) = state.mouse_flush_task.lock().await.replace(task) {
+        previous.abort();
+    }
+}
+
+/// Envoyer la dernière position souris en attente, s'il y en a une
+async fn flush_mouse_move(
+    coalescer: &MouseMoveCoalescer,
+    recorder: &MacroRecorder,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+) -> Result<(), String> {
+    if let Some(msg) = coalescer.take() {
+        recorder.record(&msg);
+        let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+        let payload = seal_control(e2e_key, bytes).await;
+        transport.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Extraire le chiffrement de session actif depuis le state (ignore l'état Pending).
+async fn real_e2e_key(e2e_key: &SessionKeyHandle) -> Option<Arc<FrameCipher>> {
+    e2e_key.lock().await.as_ref().and_then(SessionKeyState::cipher)
 }
 
 /// Sceller un message de contrôle avec la clé de session E2E si disponible.
 /// Tant que le handshake n'est pas terminé, renvoie le message tel quel (fenêtre
 /// limitée aux tout premiers échanges avant dérivation de la clé).
-async fn seal_control(e2e_key: &Arc<Mutex<Option<Vec<u8>>>>, bytes: Vec<u8>) -> Vec<u8> {
+async fn seal_control(e2e_key: &SessionKeyHandle, bytes: Vec<u8>) -> Vec<u8> {
     match real_e2e_key(e2e_key).await {
-        Some(k) => seal_frame(&k, &bytes).unwrap_or(bytes),
+        Some(cipher) => cipher.seal(&bytes).unwrap_or(bytes),
         None => bytes,
     }
 }
 
+/// Sceller et envoyer un message de contrôle
+async fn send_control(
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    msg: ControlMessage,
+) -> Result<(), String> {
+    let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+    let payload = seal_control(e2e_key, bytes).await;
+    transport.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))
+}
+
+/// Démarrer la réception d'une annonce acceptée et l'annoncer à l'UI
+fn start_offer(manager: &mut FileTransferManager, app: &AppHandle, id: &str) -> Result<(), GhostHandError> {
+    let (offer, root) = manager.accept(id)?;
+    match (offer, root) {
+        (FileOffer::Folder { id, name, entries }, Some(root)) => {
+            let files = entries.iter().filter(|e| !e.dir).count();
+            let size: u64 = entries.iter().map(|e| e.size).sum();
+            let _ = app.emit("ghosthand-folder-incoming", serde_json::json!({
+                "id": id, "name": name, "path": root.to_string_lossy(), "files": files, "size": size,
+            }));
+        }
+        (offer, _) => {
+            let _ = app.emit("ghosthand-file-incoming", serde_json::json!({
+                "id": offer.id(), "name": offer.name(), "size": offer.size(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Annonce d'un fichier ou d'un dossier : acceptée d'office (pair de confiance,
+/// confirmation désactivée) ou soumise à l'utilisateur (`ghosthand-file-offer`).
+/// Retourne la réponse à envoyer tout de suite, None si la décision est en attente.
+fn offer_incoming(
+    manager: &mut FileTransferManager,
+    app: &AppHandle,
+    offer: FileOffer,
+) -> Result<Option<ControlMessage>, GhostHandError> {
+    let id = offer.id().to_string();
+    if manager.offer(offer.clone())? {
+        start_offer(manager, app, &id)?;
+        return Ok(Some(ControlMessage::FileTransferResponse { id, accepted: true, reason: None }));
+    }
+    println!("[FILE] {} ({} fichier(s)) en attente de confirmation", offer.name(), offer.files());
+    let _ = app.emit("ghosthand-file-offer", serde_json::json!({
+        "offer": offer, "size": offer.size(), "files": offer.files(),
+    }));
+    Ok(None)
+}
+
+/// Traiter un message de transfert de fichier entrant (hôte comme viewer).
+/// Les messages d'un même transfert doivent arriver dans l'ordre d'envoi du
+/// Complete ; les chunks, eux, peuvent arriver dans le désordre. Chaque annonce
+/// reçoit une réponse (acceptée, refusée ou soumise à l'utilisateur) et chaque
+/// chunk écrit est acquitté auprès de l'émetteur (contrôle de flux).
+async fn handle_incoming_file_message(
+    manager: &Mutex<FileTransferManager>,
+    acks: &AckRegistry,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    app: &AppHandle,
+    msg: ControlMessage,
+) {
+    let announce = matches!(msg, ControlMessage::FileTransferStart { .. } | ControlMessage::FolderTransferStart { .. });
+    let mut manager = manager.lock().await;
+    // Seule une réception réellement en cours peut être annulée sur erreur :
+    // un id inventé par le pair ne doit pas toucher à l'état local
+    let receiving = match &msg {
+        ControlMessage::FileTransferChunk { id, .. } | ControlMessage::FileTransferComplete { id, .. } => {
+            manager.is_receiving(id)
+        }
+        _ => false,
+    };
+    let mut reply = None;
+    let (id, result) = match msg {
+        ControlMessage::FileTransferStart { id, name, size, sha256, folder: Some(folder) } => {
+            // Fichier d'un dossier déjà accepté : pas d'annonce séparée
+            let result = manager.start_receive_in_folder(&folder, id.clone(), name, size, sha256.as_deref());
+            reply = Some(ControlMessage::FileTransferResponse {
+                id: id.clone(),
+                accepted: result.is_ok(),
+                reason: result.as_ref().err().map(|e| e.to_string()),
+            });
+            (id, result)
+        }
+        ControlMessage::FileTransferStart { id, name, size, sha256, folder: None } => {
+            let result = offer_incoming(&mut manager, app, FileOffer::File { id: id.clone(), name, size, sha256 });
+            (id, result.map(|response| reply = response))
+        }
+        ControlMessage::FolderTransferStart { id, name, entries } => {
+            let result = offer_incoming(&mut manager, app, FileOffer::Folder { id: id.clone(), name, entries });
+            (id, result.map(|response| reply = response))
+        }
+        ControlMessage::FileTransferCancel { id, reason } => {
+            // Le pair annule un envoi vers nous ou une réception de notre envoi
+            let outgoing = acks.cancel(&id);
+            if manager.cancel(&id) || outgoing {
+                println!("[FILE] Transfert {} annulé par le pair", id);
+                let _ = app.emit("ghosthand-file-cancelled", serde_json::json!({
+                    "id": id, "reason": reason, "by_peer": true,
+                }));
+            }
+            return;
+        }
+        ControlMessage::FileTransferChunk { id, data, offset, sha256 } => {
+            let result = manager.receive_chunk(&id, &data, offset, sha256.as_deref()).map(|progress| {
+                let _ = app.emit("ghosthand-file-progress", serde_json::json!({
+                    "id": id, "progress": progress,
+                }));
+            });
+            if result.is_ok() {
+                let received = manager.received(&id).unwrap_or_default();
+                reply = Some(ControlMessage::FileTransferAck { id: id.clone(), offset, received });
+            }
+            (id, result)
+        }
+        ControlMessage::FileTransferComplete { id, sha256 } => {
+            let result = manager.complete(&id, sha256.as_deref()).map(|path| {
+                println!("[FILE] Fichier reçu: {}", path.display());
+                let _ = app.emit("ghosthand-file-received", serde_json::json!({
+                    "id": id, "path": path.to_string_lossy(),
+                }));
+            });
+            for (folder, root) in manager.take_finished_folders() {
+                println!("[FILE] Dossier reçu: {}", root.display());
+                let _ = app.emit("ghosthand-folder-received", serde_json::json!({
+                    "id": folder, "path": root.to_string_lossy(),
+                }));
+            }
+            (id, result)
+        }
+        _ => return,
+    };
+    match result {
+        Ok(()) => {}
+        // Messages en vol d'un transfert annulé : ignorés
+        Err(GhostHandError::TransferCancelled { .. }) => {}
+        // Message d'un transfert inconnu : ignoré
+        Err(e) if !announce && !receiving => {
+            eprintln!("[FILE] Message ignoré pour {}: {}", id, e);
+        }
+        Err(e) => {
+            eprintln!("[FILE] Transfert {}: {}", id, e);
+            // Un chunk invalide compromet tout le fichier : ne pas garder le partiel
+            // (une annonce refusée, elle, n'a rien créé)
+            if !announce {
+                manager.cancel(&id);
+            }
+            let integrity = matches!(e, GhostHandError::IntegrityMismatch { .. });
+            let _ = app.emit("ghosthand-file-error", serde_json::json!({
+                "id": id, "error": e.to_string(), "integrity": integrity,
+            }));
+            // Prévenir l'émetteur, qui sinon attendrait en vain : annonce refusée
+            // ou transfert annulé (son état reprenable est alors oublié)
+            let reason = Some(e.to_string());
+            reply = match reply {
+                Some(reply) => Some(reply),
+                None if announce => Some(ControlMessage::FileTransferResponse { id: id.clone(), accepted: false, reason }),
+                None => Some(ControlMessage::FileTransferCancel { id: id.clone(), reason }),
+            };
+        }
+    }
+    drop(manager);
+
+    if let Some(msg) = reply {
+        if let Err(e) = send_control(transport, e2e_key, msg).await {
+            eprintln!("[FILE] {}", e);
+        }
+    }
+}
+
+/// Tracer la réponse du pair à l'annonce d'un envoi
+fn log_file_response(id: &str, accepted: bool, reason: Option<&str>) {
+    if accepted {
+        println!("[FILE] Envoi {} accepté par le pair", id);
+    } else {
+        println!("[FILE] Envoi {} refusé par le pair: {}", id, reason.unwrap_or("sans motif"));
+    }
+}
+
+/// Une fois la clé E2E établie : demander au pair la suite des transferts
+/// entrants interrompus par une déconnexion
+async fn request_file_resumes(
+    manager: &Mutex<FileTransferManager>,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    app: &AppHandle,
+) {
+    let resumes = manager.lock().await.pending_resumes();
+    for (id, missing) in resumes {
+        let remaining: u64 = missing.iter().map(|(s, e)| e - s).sum();
+        println!("[FILE] Reprise demandée pour {} ({} bytes manquants)", id, remaining);
+        let _ = app.emit("ghosthand-file-resuming", serde_json::json!({
+            "id": id, "remaining": remaining,
+        }));
+        let msg = ControlMessage::FileTransferResume { id, missing };
+        let Ok(bytes) = msg.to_bytes() else { continue };
+        let payload = seal_control(e2e_key, bytes).await;
+        if let Err(e) = transport.send_data(&payload).await {
+            eprintln!("[FILE] Erreur envoi demande de reprise: {}", e);
+            return;
+        }
+    }
+}
+
+/// Reprendre un envoi à la demande du destinataire : seules les plages
+/// manquantes sont relues sur disque et renvoyées, puis le Complete
+async fn resume_outgoing_file(
+    manager: &Mutex<FileTransferManager>,
+    acks: &AckRegistry,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    app: &AppHandle,
+    id: String,
+    missing: Vec<(u64, u64)>,
+) {
+    let outgoing = match manager.lock().await.prepare_resume(&id, &missing) {
+        Ok(outgoing) => outgoing,
+        Err(e) => {
+            eprintln!("[FILE] Reprise de {} refusée: {}", id, e);
+            return;
+        }
+    };
+    println!("[FILE] Reprise de l'envoi {} ({})", id, outgoing.path.display());
+
+    // La reprise repasse par la file, déjà avancée des plages reçues
+    let queue = manager.lock().await.queue().clone();
+    let remaining: u64 = missing.iter().map(|(s, e)| e - s).sum();
+    // Un second `FileTransferResume` ne doit pas lancer un envoi concurrent du premier
+    if !queue.enqueue(&id, &outgoing.name, TransferDirection::Send, outgoing.size) {
+        eprintln!("[FILE] Reprise de {} ignorée: envoi déjà en cours", id);
+        return;
+    }
+    queue.update(&id, outgoing.size.saturating_sub(remaining));
+
+    let ack_rx = acks.register(&id);
+    let result = async {
+        queue.acquire(&id).await?;
+        FileSender::new()
+            .with_queue(queue.clone(), id.as_str())
+            .send_over(transport, e2e_key, &outgoing, Some(&missing), ack_rx, |progress| {
+                queue.update(&id, progress.acked);
+                let _ = app.emit("ghosthand-file-send-progress", &progress);
+            })
+            .await
+    }
+    .await;
+    acks.unregister(&id);
+    queue.finish(&id, result.as_ref().err().map(|e| e.to_string()));
+
+    match result {
+        Ok(()) => {
+            manager.lock().await.finish_outgoing(&id);
+            let _ = app.emit("ghosthand-file-resumed", serde_json::json!({ "id": id }));
+        }
+        // L'envoi reste reprenable à la prochaine reconnexion
+        Err(e) => eprintln!("[FILE] Reprise de {} interrompue: {}", id, e),
+    }
+}
+
+/// Traiter une requête de navigation distante du viewer (côté hôte) : réponse,
+/// puis envoi du fichier demandé par `ReadFile` comme un transfert ordinaire
+async fn handle_file_browser_request(
+    browser: Arc<FileBrowser>,
+    manager: &Mutex<FileTransferManager>,
+    acks: &AckRegistry,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    app: &AppHandle,
+    msg: ControlMessage,
+) {
+    // Accès disque (et SHA-256 du fichier demandé) hors du runtime async
+    let handled = tokio::task::spawn_blocking(move || {
+        let (request_id, outcome) = browser.handle(&msg)?;
+        let reply = outcome.and_then(|outcome| match outcome {
+            BrowseOutcome::Listing { entries, truncated } => Ok((FileBrowserResult::Listing { entries, truncated }, None)),
+            BrowseOutcome::Done => Ok((FileBrowserResult::Done, None)),
+            BrowseOutcome::Read(path) => FileTransferManager::prepare_send(&path, u64::MAX)
+                .map(|t| (FileBrowserResult::Transfer { transfer_id: t.id.clone() }, Some(t))),
+        });
+        let (result, transfer) = reply.unwrap_or_else(|e| (FileBrowserResult::Error { message: e.to_string() }, None));
+        Some((request_id, result, transfer))
+    })
+    .await;
+    let Ok(Some((request_id, result, transfer))) = handled else { return };
+
+    let response = ControlMessage::FileBrowserResponse { request_id, result };
+    let Ok(bytes) = response.to_bytes() else { return };
+    let payload = seal_control(e2e_key, bytes).await;
+    if let Err(e) = transport.send_data(&payload).await {
+        eprintln!("[FILE] Erreur envoi réponse de navigation: {}", e);
+        return;
+    }
+
+    if let Some(mut transfer) = transfer {
+        println!("[FILE] Téléchargement demandé par le viewer: {}", transfer.path.display());
+        if let Err(e) = send_outgoing_file(manager, acks, transport, e2e_key, app, &mut transfer).await {
+            eprintln!("[FILE] {}", e);
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct MouseEvent {
     x: i32,
@@ -263,12 +644,45 @@ struct MouseEvent {
     r#type: String,
     #[serde(default)]
     delta: i32,
+    /// Défilement horizontal (`WheelEvent.deltaX`)
+    #[serde(default)]
+    delta_x: i32,
+    /// `WheelEvent.deltaMode` : 0 = pixels, 1 = lignes, 2 = pages
+    #[serde(default)]
+    delta_mode: u32,
+    /// Position normalisée (0.0..=1.0) dans l'image distante, prioritaire sur x/y
+    #[serde(default)]
+    norm_x: Option<f64>,
+    #[serde(default)]
+    norm_y: Option<f64>,
+}
+
+impl MouseEvent {
+    /// Message de positionnement : normalisé si l'UI le fournit, sinon en pixels
+    fn move_message(&self) -> ControlMessage {
+        match (self.norm_x, self.norm_y) {
+            (Some(x), Some(y)) => ControlMessage::MouseMoveNormalized { x, y },
+            _ => ControlMessage::MouseMove { x: self.x, y: self.y },
+        }
+    }
+}
+
+/// Contact tactile ou stylet relayé par l'UI (Pointer Events)
+#[derive(Debug, Deserialize)]
+struct PointerEventData {
+    /// "touch" | "pen"
+    pointer_type: String,
+    id: u32,
+    phase: TouchPhase,
+    norm_x: f64,
+    norm_y: f64,
+    #[serde(default)]
+    pressure: f32,
 }
 
 #[derive(Debug, Deserialize)]
 struct KeyboardEvent {
     key: String,
-    #[allow(dead_code)]
     code: String,
     r#type: String,
     #[allow(dead_code)]
@@ -291,6 +705,7 @@ use settings_commands::{load_settings, save_settings};
 use storage_commands::{
     get_connection_history, get_known_peers, get_favorite_peers,
     set_peer_favorite, get_storage_stats,
+    list_macros, delete_macro, export_macro, import_macro,
 };
 
 /// Démarrer la découverte LAN via UDP broadcast
@@ -512,11 +927,20 @@ async fn connect_to_device(
             // Handshake E2E neuf : réinitialiser la clé, mémoriser le secret d'auth (anti-MITM)
             *state.e2e_session_key.lock().await = None;
             *state.e2e_auth_secret.lock().await = session.auth_secret();
+            *state.active_peer_id.lock().await = Some(target_id.clone());
+            {
+                let mut manager = state.file_transfer_manager.lock().await;
+                manager.set_peer(Some(target_id.clone()));
+                manager.set_auto_accept(peer_auto_accepts_files(&target_id));
+            }
+            let default_policy = state.config.lock().await.clipboard_policy;
+            set_session_clipboard_policy(&state.clipboard_policy, default_policy);
             // Enregistrer le canal relay pour le listener de messages entrants
             if let Some(transport) = &session.webrtc {
                 if let Some(tx) = transport.relay_incoming_tx() {
                     *state.relay_data_tx.lock().await = Some(tx);
                 }
+                start_mouse_flush(&state, transport.clone()).await;
             }
         }
         Err(e) => {
@@ -559,6 +983,8 @@ async fn connect_to_device(
                     favorite: false,
                     connection_count: 1,
                     notes: None,
+                    input_rules: Vec::new(),
+                    auto_accept_files: false,
                 });
             }
 
@@ -583,6 +1009,44 @@ async fn disconnect(state: State<'_, AppState>) -> Result<(), String> {
     // Purger l'état E2E (clé de session + secret d'auth) pour le prochain handshake
     *state.e2e_session_key.lock().await = None;
     *state.e2e_auth_secret.lock().await = None;
+    *state.active_peer_id.lock().await = None;
+    state.view_only.store(false, Ordering::SeqCst);
+    *state.keyboard_mode.lock().await = (KeyInputMode::Logical, None);
+
+    // Arrêter la surveillance du presse-papiers et l'envoi des mouvements souris
+    if let Some(watcher) = state.clipboard_watcher.lock().await.take() {
+        watcher.abort();
+    }
+    if let Some(flush) = state.mouse_flush_task.lock().await.take() {
+        flush.abort();
+    }
+    state.move_coalescer.take();
+
+    // Transferts entrants interrompus : conservés sur disque pour reprise
+    {
+        let mut manager = state.file_transfer_manager.lock().await;
+        manager.suspend_all();
+        manager.set_peer(None);
+    }
+
+    // Interrompre un rejeu de macro (le transport n'existe plus)
+    if let Ok(playback) = state.macro_playback.lock() {
+        if let Some(handle) = playback.as_ref() {
+            handle.abort();
+        }
+    }
+
+    // Arrêter la capture d'input locale (la fermeture du canal termine le listener)
+    if let Ok(mut tx) = state.local_input_tx.lock() {
+        *tx = None;
+    }
+
+    // Ne laisser aucune touche ni bouton enfoncé sur l'hôte
+    if let Some(handler) = state.input_handler.lock().await.take() {
+        if let Err(e) = handler.release_all().await {
+            eprintln!("[INPUT] Erreur relâchement input: {}", e);
+        }
+    }
 
     // Supprimer la session
     *state.session_manager.lock().await = None;
@@ -604,28 +1068,50 @@ async fn send_mouse_event(
         if let Some(webrtc) = &session.webrtc {
             match event.r#type.as_str() {
                 "move" => {
-                    let msg = ControlMessage::MouseMove { x: event.x, y: event.y };
-                    let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
-                    let payload = seal_control(&state.e2e_session_key, bytes).await;
-                    webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+                    // Regroupé : seule la dernière position part au prochain tick
+                    match (event.norm_x, event.norm_y) {
+                        (Some(x), Some(y)) => state.move_coalescer.push_normalized(x, y),
+                        _ => state.move_coalescer.push(event.x, event.y),
+                    }
                 },
-                "down" | "up" => {
+                "down" | "up" | "dblclick" => {
+                    // La position du clic remplace tout mouvement encore en attente
+                    state.move_coalescer.take();
                     // FIX: Envoyer MouseMove AVANT MouseClick pour positionner le curseur
-                    let move_msg = ControlMessage::MouseMove { x: event.x, y: event.y };
+                    let move_msg = event.move_message();
+                    state.macro_recorder.record(&move_msg);
                     let move_bytes = move_msg.to_bytes().map_err(|e| format!("Erreur sérialisation move: {}", e))?;
                     let move_payload = seal_control(&state.e2e_session_key, move_bytes).await;
                     webrtc.send_data(&move_payload).await.map_err(|e| format!("Erreur envoi move: {}", e))?;
 
-                    let click_msg = ControlMessage::MouseClick {
-                        button: event.button.clone(),
-                        pressed: event.r#type == "down",
+                    let click_msg = if event.r#type == "dblclick" {
+                        ControlMessage::MouseDoubleClick { button: event.button.clone() }
+                    } else {
+                        ControlMessage::MouseClick {
+                            button: event.button.clone(),
+                            pressed: event.r#type == "down",
+                        }
                     };
+                    state.macro_recorder.record(&click_msg);
                     let click_bytes = click_msg.to_bytes().map_err(|e| format!("Erreur sérialisation click: {}", e))?;
                     let click_payload = seal_control(&state.e2e_session_key, click_bytes).await;
                     webrtc.send_data(&click_payload).await.map_err(|e| format!("Erreur envoi click: {}", e))?;
                 },
                 "scroll" | "wheel" => {
-                    let msg = ControlMessage::MouseScroll { delta: event.delta.clamp(-2000, 2000) };
+                    // Le défilement s'applique à la position courante : envoyer d'abord le mouvement en attente
+                    flush_mouse_move(&state.move_coalescer, &state.macro_recorder, webrtc, &state.e2e_session_key).await?;
+                    let (unit, delta, delta_x) = match event.delta_mode {
+                        0 => (ScrollUnit::Pixel, event.delta, event.delta_x),
+                        // Page : approximée à 3 crans
+                        2 => (ScrollUnit::Line, event.delta.saturating_mul(3), event.delta_x.saturating_mul(3)),
+                        _ => (ScrollUnit::Line, event.delta, event.delta_x),
+                    };
+                    let msg = ControlMessage::MouseScroll {
+                        delta: delta.clamp(-2000, 2000),
+                        delta_x: delta_x.clamp(-2000, 2000),
+                        unit,
+                    };
+                    state.macro_recorder.record(&msg);
                     let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
                     let payload = seal_control(&state.e2e_session_key, bytes).await;
                     webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
@@ -642,12 +1128,62 @@ async fn send_mouse_event(
     }
 }
 
+/// Demander à l'hôte une séquence de touches qu'il a approuvée (Ctrl+Alt+Suppr...)
+#[tauri::command]
+async fn send_key_sequence(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    let session_guard = state.session_manager.lock().await;
+    let webrtc = session_guard.as_ref()
+        .ok_or_else(|| "Non connecté".to_string())?
+        .webrtc.as_ref()
+        .ok_or_else(|| "Pas de connexion WebRTC".to_string())?;
+
+    let msg = ControlMessage::SendKeySequence { name };
+    state.macro_recorder.record(&msg);
+    let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+    let payload = seal_control(&state.e2e_session_key, bytes).await;
+    webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))
+}
+
+/// Envoyer un contact tactile ou stylet
+#[tauri::command]
+async fn send_pointer_event(
+    state: State<'_, AppState>,
+    event: PointerEventData,
+) -> Result<(), String> {
+    let session_guard = state.session_manager.lock().await;
+    let webrtc = session_guard.as_ref()
+        .ok_or_else(|| "Non connecté".to_string())?
+        .webrtc.as_ref()
+        .ok_or_else(|| "Pas de connexion WebRTC".to_string())?;
+
+    let (id, phase, x, y, pressure) = (event.id, event.phase, event.norm_x, event.norm_y, event.pressure);
+    let msg = match event.pointer_type.as_str() {
+        "touch" => ControlMessage::Touch { id, phase, x, y, pressure },
+        "pen" => ControlMessage::Pen { id, phase, x, y, pressure },
+        other => return Err(format!("Type de pointeur non supporté: {}", other)),
+    };
+    // Un mouvement souris encore en attente précède le contact
+    flush_mouse_move(&state.move_coalescer, &state.macro_recorder, webrtc, &state.e2e_session_key).await?;
+    state.macro_recorder.record(&msg);
+    let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+    let payload = seal_control(&state.e2e_session_key, bytes).await;
+    webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))
+}
+
 /// Envoyer un événement clavier
 #[tauri::command]
 async fn send_keyboard_event(
     state: State<'_, AppState>,
     event: KeyboardEvent,
 ) -> Result<(), String> {
+    // Touche d'arrêt d'un rejeu de macro : consommée localement
+    if event.r#type == "keydown" && abort_macro_on_hotkey(&state, &event.key) {
+        return Ok(());
+    }
+
     // Récupérer la session
     let session_guard = state.session_manager.lock().await;
 
@@ -655,6 +1191,13 @@ async fn send_keyboard_event(
         if let Some(webrtc) = &session.webrtc {
             // Convertir en ControlMessage avec modifiers
             use ghost_hand_client::protocol::KeyModifiersProto;
+            let (mode, layout) = state.keyboard_mode.lock().await.clone();
+            // Mode physique : position de la touche (code DOM → usage HID)
+            let hid_usage = match mode {
+                KeyInputMode::Physical => Some(hid_usage_from_dom_code(&event.code)
+                    .ok_or_else(|| format!("Touche physique non supportée: {}", event.code))?),
+                KeyInputMode::Logical => None,
+            };
             let msg = ControlMessage::KeyPress {
                 key: event.key.clone(),
                 pressed: event.r#type == "keydown",
@@ -664,8 +1207,12 @@ async fn send_keyboard_event(
                     alt: event.modifiers.alt,
                     meta: event.modifiers.meta,
                 }),
+                mode,
+                hid_usage,
+                layout: if hid_usage.is_some() { layout } else { None },
             };
 
+            state.macro_recorder.record(&msg);
             // Chiffrer puis envoyer via le transport (relais VPS)
             let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
             let payload = seal_control(&state.e2e_session_key, bytes).await;
@@ -683,6 +1230,209 @@ async fn send_keyboard_event(
     }
 }
 
+/// Choisir le mode clavier de la session : logique (caractères, layout de l'hôte)
+/// ou physique (positions de touches, indépendant des layouts)
+#[tauri::command]
+async fn set_keyboard_mode(
+    state: State<'_, AppState>,
+    mode: KeyInputMode,
+    layout: Option<String>,
+) -> Result<(), String> {
+    println!("[TAURI] Mode clavier → {:?} (layout {:?})", mode, layout);
+    *state.keyboard_mode.lock().await = (mode, layout);
+    Ok(())
+}
+
+/// Saisir un texte complet sur l'hôte (collage, Unicode, IME) en un seul message
+#[tauri::command]
+async fn send_text(
+    state: State<'_, AppState>,
+    text: String,
+    pacing_ms: Option<u32>,
+) -> Result<(), String> {
+    if text.len() > InputController::MAX_TYPE_LEN {
+        return Err(format!("Texte trop long (max {} octets)", InputController::MAX_TYPE_LEN));
+    }
+
+    let session_guard = state.session_manager.lock().await;
+    if let Some(webrtc) = session_guard.as_ref().and_then(|s| s.webrtc.as_ref()) {
+        let msg = ControlMessage::TypeText { text, pacing_ms: pacing_ms.unwrap_or(0) };
+        state.macro_recorder.record(&msg);
+        let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+        let payload = seal_control(&state.e2e_session_key, bytes).await;
+        webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+        Ok(())
+    } else {
+        Err("Pas de connexion WebRTC".to_string())
+    }
+}
+
+/// Démarrer la capture d'input locale (côté viewer) : les événements de la fenêtre
+/// sont traduits en messages de contrôle, chiffrés puis envoyés à l'hôte
+#[tauri::command]
+async fn start_local_input_capture(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let transport = {
+        let session_guard = state.session_manager.lock().await;
+        session_guard.as_ref()
+            .and_then(|s| s.webrtc.clone())
+            .ok_or_else(|| "Pas de connexion WebRTC".to_string())?
+    };
+
+    let (event_tx, source) = ChannelInputSource::new();
+    let (msg_tx, mut msg_rx) = relay_mpsc::unbounded_channel::<ControlMessage>();
+    InputListener::new(Box::new(source)).start(move |msg| {
+        let _ = msg_tx.send(msg);
+    });
+
+    let e2e_key = state.e2e_session_key.clone();
+    let coalescer = state.move_coalescer.clone();
+    let recorder = state.macro_recorder.clone();
+    tokio::spawn(async move {
+        while let Some(msg) = msg_rx.recv().await {
+            // Mouvements regroupés par le tick ; tout autre message part après
+            // le mouvement en attente pour préserver l'ordre
+            match msg {
+                ControlMessage::MouseMove { x, y } => {
+                    coalescer.push(x, y);
+                    continue;
+                }
+                ControlMessage::MouseMoveNormalized { x, y } => {
+                    coalescer.push_normalized(x, y);
+                    continue;
+                }
+                _ => {}
+            }
+            if let Err(e) = flush_mouse_move(&coalescer, &recorder, &transport, &e2e_key).await {
+                eprintln!("[INPUT] {}", e);
+            }
+            recorder.record(&msg);
+            let Ok(bytes) = msg.to_bytes() else { continue };
+            let payload = seal_control(&e2e_key, bytes).await;
+            if let Err(e) = transport.send_data(&payload).await {
+                eprintln!("[INPUT] Erreur envoi input local: {}", e);
+            }
+        }
+    });
+
+    // État de focus initial de la fenêtre viewer
+    let focused = app_handle.get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    let _ = event_tx.send(RawInputEvent::FocusChanged { focused });
+
+    *state.local_input_tx.lock().map_err(|e| format!("Lock erreur: {}", e))? = Some(event_tx);
+    println!("[TAURI] Capture d'input locale démarrée");
+    Ok(())
+}
+
+/// Transmettre un événement d'input local de la fenêtre viewer à la capture
+#[tauri::command]
+fn push_local_input(state: State<AppState>, event: RawInputEvent) -> Result<(), String> {
+    if let RawInputEvent::KeyDown { key } = &event {
+        if abort_macro_on_hotkey(&state, key) {
+            return Ok(());
+        }
+    }
+    let guard = state.local_input_tx.lock().map_err(|e| format!("Lock erreur: {}", e))?;
+    let tx = guard.as_ref().ok_or_else(|| "Capture d'input locale inactive".to_string())?;
+    tx.send(event).map_err(|e| format!("Erreur capture input: {}", e))
+}
+
+/// Interrompre le rejeu de macro en cours si `key` est la touche d'arrêt.
+/// Renvoie true si la touche a été consommée.
+fn abort_macro_on_hotkey(state: &AppState, key: &str) -> bool {
+    if !MacroPlayer::is_abort_hotkey(key) {
+        return false;
+    }
+    let Ok(playback) = state.macro_playback.lock() else { return false };
+    match playback.as_ref() {
+        Some(handle) => {
+            handle.abort();
+            println!("[MACRO] Rejeu interrompu (touche d'arrêt)");
+            true
+        }
+        None => false,
+    }
+}
+
+/// Démarrer l'enregistrement d'une macro d'input (messages sortants)
+#[tauri::command]
+fn start_macro_recording(state: State<AppState>, name: String) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Nom de macro vide".to_string());
+    }
+    state.macro_recorder.start(name.trim());
+    Ok(())
+}
+
+/// Arrêter l'enregistrement et sauvegarder la macro dans le storage
+#[tauri::command]
+fn stop_macro_recording(state: State<AppState>) -> Result<InputMacro, String> {
+    let input_macro = state.macro_recorder.stop()
+        .ok_or_else(|| "Aucun enregistrement de macro en cours".to_string())?;
+    storage_commands::save_macro(&input_macro)?;
+    Ok(input_macro)
+}
+
+/// Rejouer une macro stockée sur la session courante (`speed` : 1.0 = temps réel).
+/// L'issue est émise via l'événement "ghosthand-macro-finished".
+#[tauri::command]
+async fn play_macro(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    name: String,
+    speed: Option<f64>,
+) -> Result<(), String> {
+    let input_macro = storage_commands::load_macro(&name)?;
+    let transport = {
+        let session_guard = state.session_manager.lock().await;
+        session_guard.as_ref()
+            .and_then(|s| s.webrtc.clone())
+            .ok_or_else(|| "Pas de connexion WebRTC".to_string())?
+    };
+
+    let player = MacroPlayer::new().with_speed(speed.unwrap_or(1.0));
+    {
+        let mut playback = state.macro_playback.lock().map_err(|e| format!("Lock erreur: {}", e))?;
+        if playback.is_some() {
+            return Err("Une macro est déjà en cours de rejeu".to_string());
+        }
+        *playback = Some(player.abort_handle());
+    }
+
+    let e2e_key = state.e2e_session_key.clone();
+    let playback = state.macro_playback.clone();
+    tokio::spawn(async move {
+        let result = player.play_over(&input_macro, &transport, &e2e_key).await;
+        if let Ok(mut p) = playback.lock() {
+            *p = None;
+        }
+        let payload = match result {
+            Ok(PlaybackOutcome::Completed { steps }) => {
+                serde_json::json!({ "name": input_macro.name, "status": "completed", "steps": steps })
+            }
+            Ok(PlaybackOutcome::Aborted { steps }) => {
+                serde_json::json!({ "name": input_macro.name, "status": "aborted", "steps": steps })
+            }
+            Err(e) => serde_json::json!({ "name": input_macro.name, "status": "error", "error": e.to_string() }),
+        };
+        println!("[MACRO] Rejeu terminé: {}", payload);
+        let _ = app_handle.emit("ghosthand-macro-finished", payload);
+    });
+    Ok(())
+}
+
+/// Interrompre le rejeu de macro en cours
+#[tauri::command]
+fn abort_macro(state: State<AppState>) -> Result<(), String> {
+    let playback = state.macro_playback.lock().map_err(|e| format!("Lock erreur: {}", e))?;
+    playback.as_ref().ok_or_else(|| "Aucune macro en cours de rejeu".to_string())?.abort();
+    Ok(())
+}
+
 /// Récupérer la configuration actuelle
 #[tauri::command]
 async fn get_config(state: State<'_, AppState>) -> Result<Config, String> {
@@ -852,10 +1602,8 @@ async fn start_streaming(
             // Stocker la clé privée temporaire pour que start_input_handler puisse finaliser l'échange
             if let Some(ref priv_key) = kex_private_key {
                 let mut key_guard = state.e2e_session_key.lock().await;
-                // Tag spécial: on stocke la clé privée avec préfixe "PENDING:" jusqu'à réception de KeyExchangeAccept
-                let mut tagged = b"PENDING:".to_vec();
-                tagged.extend_from_slice(priv_key);
-                *key_guard = Some(tagged);
+                // Clé privée conservée jusqu'à réception de KeyExchangeAccept
+                *key_guard = Some(SessionKeyState::Pending(priv_key.clone()));
             }
             diag_log("start_streaming: KEx initié (non bloquant)");
 
@@ -995,10 +1743,41 @@ async fn start_receiving(
             let webrtc_for_kex = webrtc.clone();
             let e2e_key_for_rx = state.e2e_session_key.clone();
             let e2e_auth_for_rx = state.e2e_auth_secret.clone();
+            let rekey_for_rx = state.config.lock().await.security_config.rekey;
             let app_for_kex = app_handle.clone();
+            let file_manager_kex = state.file_transfer_manager.clone();
 
             // Fenêtre pour les messages non-vidéo (display list, chat, clipboard)
             let msg_window = app_handle.get_webview_window("main");
+            // Transferts de fichiers entrants : traités dans l'ordre par une seule tâche
+            let (file_tx, mut file_rx) = tokio::sync::mpsc::unbounded_channel::<ControlMessage>();
+            let file_manager = state.file_transfer_manager.clone();
+            let app_for_files = app_handle.clone();
+            let transport_for_files = webrtc.clone();
+            let e2e_key_for_files = state.e2e_session_key.clone();
+            let acks_for_files = state.file_acks.clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(msg) = file_rx.recv().await {
+                    handle_incoming_file_message(
+                        &file_manager, &acks_for_files, &transport_for_files, &e2e_key_for_files, &app_for_files, msg,
+                    ).await;
+                }
+            });
+            let file_acks_rx = state.file_acks.clone();
+            let browse_requests_rx = state.browse_requests.clone();
+
+            let clipboard_for_rx = state.clipboard_manager.clone();
+            let clipboard_policy_rx = state.clipboard_policy.clone();
+            let view_only_rx = state.view_only.clone();
+
+            // Surveillance du presse-papiers local → hôte (selon la politique de session)
+            restart_clipboard_watcher(
+                &state,
+                ClipboardWatcher::new(state.clipboard_manager.clone(), ClipboardRole::Viewer, state.clipboard_policy.clone())
+                    .with_view_only(state.view_only.clone())
+                    .spawn_over(webrtc.clone(), state.e2e_session_key.clone()),
+            )
+            .await;
 
             // Démarrer avec callbacks séparés pour vidéo et messages de contrôle
             let frame_counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
@@ -1035,6 +1814,7 @@ async fn start_receiving(
                             let key_store = e2e_key_for_rx.clone();
                             let auth_store = e2e_auth_for_rx.clone();
                             let app_kex = app_for_kex.clone();
+                            let file_manager = file_manager_kex.clone();
                             tauri::async_runtime::spawn(async move {
                                 match kex.generate_keypair() {
                                     Ok((priv_key, pub_key)) => {
@@ -1047,17 +1827,21 @@ async fn start_receiving(
                                                 }
                                                 // Lier la clé au mot de passe (anti-MITM du relais)
                                                 let auth = auth_store.lock().await.clone();
-                                                match derive_session_key(&shared, auth.as_deref()) {
-                                                    Ok(session_key) => {
+                                                let cipher = derive_session_key(&shared, auth.as_deref())
+                                                    .and_then(|key| FrameCipher::new(key, FrameRole::Viewer))
+                                                    .map(|cipher| cipher.with_rekey(rekey_for_rx));
+                                                match cipher {
+                                                    Ok(cipher) => {
                                                         let authenticated = auth.is_some();
-                                                        let fingerprint = session_fingerprint(&session_key);
-                                                        *key_store.lock().await = Some(session_key);
+                                                        let fingerprint = cipher.fingerprint().to_string();
+                                                        *key_store.lock().await = Some(SessionKeyState::Active(Arc::new(cipher)));
                                                         let mode = if authenticated { "authentifié (mot de passe)" } else { "non authentifié (sans mot de passe)" };
                                                         println!("[CRYPTO] Viewer: clé E2E dérivée — AES-256-GCM actif — {} — empreinte {}", mode, fingerprint);
                                                         let _ = app_kex.emit("ghosthand-session-secure", serde_json::json!({
                                                             "fingerprint": fingerprint,
                                                             "authenticated": authenticated,
                                                         }));
+                                                        request_file_resumes(&file_manager, &webrtc_kex, &key_store, &app_kex).await;
                                                     }
                                                     Err(e) => eprintln!("[CRYPTO] Viewer: erreur dérivation HKDF: {}", e),
                                                 }
@@ -1069,6 +1853,35 @@ async fn start_receiving(
                                 }
                             });
                         }
+                        msg @ (ControlMessage::FileTransferStart { .. }
+                        | ControlMessage::FileTransferChunk { .. }
+                        | ControlMessage::FileTransferComplete { .. }
+                        | ControlMessage::FolderTransferStart { .. }
+                        | ControlMessage::FileTransferCancel { .. }) => {
+                            let _ = file_tx.send(msg);
+                        }
+                        ControlMessage::FileTransferResume { id, missing } => {
+                            let file_manager = file_manager_kex.clone();
+                            let acks = file_acks_rx.clone();
+                            let transport = webrtc_for_kex.clone();
+                            let key = e2e_key_for_rx.clone();
+                            let app = app_for_kex.clone();
+                            tauri::async_runtime::spawn(async move {
+                                resume_outgoing_file(&file_manager, &acks, &transport, &key, &app, id, missing).await;
+                            });
+                        }
+                        ControlMessage::FileTransferAck { id, received, .. } => {
+                            file_acks_rx.ack(&id, received);
+                        }
+                        ControlMessage::FileTransferResponse { id, accepted, reason } => {
+                            log_file_response(&id, accepted, reason.as_deref());
+                            file_acks_rx.respond(&id, accepted);
+                        }
+                        ControlMessage::FileBrowserResponse { request_id, result } => {
+                            if !browse_requests_rx.resolve(request_id, result) {
+                                println!("[FILE] Réponse de navigation sans requête: {}", request_id);
+                            }
+                        }
                         other => {
                             if let Some(ref w) = msg_window {
                                 match &other {
@@ -1083,10 +1896,40 @@ async fn start_receiving(
                                             }),
                                         );
                                     }
-                                    ControlMessage::ClipboardSync { content } => {
+                                    ControlMessage::ClipboardSync { content, mime, alt_text } => {
+                                        let policy = clipboard_policy_rx.lock().map(|p| *p).ok();
+                                        if !policy.is_some_and(|p| p.allows_incoming(ClipboardRole::Viewer)) {
+                                            println!("[CLIPBOARD] Contenu de l'hôte écarté (politique {:?})", policy);
+                                            return;
+                                        }
+                                        // Appliqué ici (images comprises) ; l'UI n'est que notifiée
+                                        let content = match ClipboardContent::from_sync(content.clone(), *mime, alt_text.clone()) {
+                                            Ok(c) => c,
+                                            Err(e) => {
+                                                eprintln!("[CLIPBOARD] Contenu reçu rejeté: {}", e);
+                                                return;
+                                            }
+                                        };
+                                        let info = serde_json::json!({
+                                            "mime": mime.as_str(),
+                                            "size": content.size(),
+                                        });
+                                        let clipboard = clipboard_for_rx.clone();
+                                        let w = w.clone();
+                                        tauri::async_runtime::spawn_blocking(move || {
+                                            match clipboard.set_content(&content) {
+                                                Ok(()) => {
+                                                    let _ = w.emit("ghosthand-clipboard-sync", info);
+                                                }
+                                                Err(e) => eprintln!("[CLIPBOARD] Erreur écriture: {}", e),
+                                            }
+                                        });
+                                    }
+                                    ControlMessage::PermissionChanged { view_only } => {
+                                        view_only_rx.store(*view_only, Ordering::SeqCst);
                                         let _ = w.emit(
-                                            "ghosthand-clipboard-sync",
-                                            serde_json::json!({ "content": content }),
+                                            "ghosthand-permission-changed",
+                                            serde_json::json!({ "view_only": view_only }),
                                         );
                                     }
                                     _ => println!("[RECEIVER] Message non géré: {:?}", other),
@@ -1136,9 +1979,47 @@ async fn start_input_handler(
                     (1920, 1080)
                 }
             };
-            let handler = Arc::new(InputHandler::new_with_resolution(res_w as i32, res_h as i32)
-                .map_err(|e| format!("Erreur création handler: {}", e))?);
+            // Politique d'input : règles globales + règles propres au pair connecté
+            let peer_id = state.active_peer_id.lock().await.clone();
+            let known_peer = peer_id.as_ref().and_then(|id| {
+                global_storage()
+                    .and_then(|s| s.lock().ok().and_then(|s| s.get_known_peer(id).cloned()))
+            });
+            let (policy, touch_mapping, key_sequences, clipboard_filter, file_browser) = {
+                let config = state.config.lock().await;
+                (
+                    config.input_policy.scoped_for_peer(known_peer.as_ref()),
+                    config.touch_mapping.clone(),
+                    config.key_sequences.clone(),
+                    ClipboardFilter::new(&config.clipboard_filter),
+                    FileBrowser::new(&config.file_browser)
+                        .map(|b| b.with_max_file_size(config.file_transfer.max_file_size)),
+                )
+            };
+            let clipboard_filter = Arc::new(clipboard_filter.map_err(|e| format!("Filtre presse-papiers: {}", e))?);
+            // Une racine invalide désactive la navigation distante, pas la session
+            let file_browser = Arc::new(
+                file_browser
+                    .unwrap_or_else(|e| {
+                        eprintln!("[FILE] Navigation distante désactivée: {}", e);
+                        FileBrowser::disabled()
+                    })
+                    .with_peer(peer_id.clone())
+                    .with_view_only(state.view_only.clone()),
+            );
+            let controller = InputController::new_with_resolution(res_w as i32, res_h as i32)
+                .map_err(|e| format!("Erreur création handler: {}", e))?
+                .with_policy(policy, peer_id.clone())
+                .with_touch_mapping(touch_mapping)
+                .with_key_sequences(key_sequences);
+            let handler = Arc::new(
+                InputHandler::from_controller(controller)
+                    .with_view_only(state.view_only.clone())
+                    .with_clipboard(state.clipboard_manager.clone(), state.clipboard_policy.clone())
+                    .with_clipboard_filter(clipboard_filter.clone()),
+            );
             println!("[TAURI] InputHandler créé avec résolution {}x{}", res_w, res_h);
+            *state.input_handler.lock().await = Some(handler.clone());
 
             // Attendre que le data channel soit établi (race condition côté answerer)
             webrtc.wait_for_data_channel(3000).await
@@ -1152,37 +2033,41 @@ async fn start_input_handler(
                 let _ = tx.send(data.to_vec());
             }).await.map_err(|e| format!("Erreur callback: {}", e))?;
 
+            // Surveillance du presse-papiers local → viewer (selon la politique de session)
+            restart_clipboard_watcher(
+                &state,
+                ClipboardWatcher::new(state.clipboard_manager.clone(), ClipboardRole::Host, state.clipboard_policy.clone())
+                    .with_view_only(state.view_only.clone())
+                    .with_filter(clipboard_filter, peer_id.clone())
+                    .spawn_over(webrtc.clone(), state.e2e_session_key.clone()),
+            )
+            .await;
+
             let handler_clone = handler.clone();
             let capturer_ref = state.active_capturer.clone();
             let encoder_ref = state.active_encoder.clone();
             let e2e_key_ref = state.e2e_session_key.clone();
             let e2e_auth_ref = state.e2e_auth_secret.clone();
+            let rekey_ref = state.config.lock().await.security_config.rekey;
             let app_for_secure = app_handle.clone();
+            let view_only_ref = state.view_only.clone();
+            let webrtc_perm = webrtc.clone();
+            let file_manager_ref = state.file_transfer_manager.clone();
+            let file_acks_ref = state.file_acks.clone();
+            let app_for_files = app_handle.clone();
 
             tokio::spawn(async move {
+                // Les messages au-delà de MAX_CHUNK_SIZE (chunks de fichier, presse-papiers,
+                // manifestes) arrivent fragmentés : réassemblés avant déchiffrement
+                let mut decoder = HostFrameDecoder::new();
                 while let Some(raw) = rx.recv().await {
-                    // Clé de session active (hors sentinel PENDING de handshake)
-                    let real_key = {
-                        let g = e2e_key_ref.lock().await;
-                        g.as_ref().filter(|k| !k.starts_with(b"PENDING:")).cloned()
-                    };
+                    // Clé de session active (hors état Pending du handshake)
+                    let real_key = real_e2e_key(&e2e_key_ref).await;
 
-                    // Déchiffrer / filtrer le trafic entrant
-                    let data = if raw.first() == Some(&ENCRYPTED_MAGIC) {
-                        match real_key {
-                            Some(ref k) => match open_frame(k, &raw) {
-                                Ok(p) => p,
-                                Err(e) => { eprintln!("[INPUT] déchiffrement échoué: {}", e); continue; }
-                            },
-                            None => continue, // trame chiffrée reçue sans clé → ignorée
-                        }
-                    } else if real_key.is_some() {
-                        // SÉCURITÉ (F1): clé E2E active → refuser tout contrôle en clair
-                        // (empêche l'injection d'input par un relais VPS malveillant).
-                        eprintln!("[INPUT] ⚠️ message de contrôle en clair rejeté (clé E2E active)");
+                    // Réassembler, déchiffrer et filtrer le trafic entrant (SÉCURITÉ F1 :
+                    // clé E2E active → tout contrôle en clair est refusé)
+                    let Some(data) = decoder.decode(raw, real_key.as_deref()) else {
                         continue;
-                    } else {
-                        raw // phase handshake : KeyExchangeAccept circule en clair
                     };
 
                     if let Ok(msg) = ControlMessage::from_bytes(&data) {
@@ -1191,11 +2076,10 @@ async fn start_input_handler(
                                 // Finaliser l'échange de clés E2E
                                 let pending = {
                                     let guard = e2e_key_ref.lock().await;
-                                    guard.as_ref().and_then(|k| {
-                                        if k.starts_with(b"PENDING:") {
-                                            Some(k[8..].to_vec()) // Extraire la clé privée
-                                        } else { None }
-                                    })
+                                    match guard.as_ref() {
+                                        Some(SessionKeyState::Pending(priv_key)) => Some(priv_key.clone()),
+                                        _ => None,
+                                    }
                                 };
                                 if let Some(priv_key) = pending {
                                     let kex = KeyExchange::new();
@@ -1203,17 +2087,29 @@ async fn start_input_handler(
                                         Ok(shared) => {
                                             // Lier la clé au mot de passe (anti-MITM du relais)
                                             let auth = e2e_auth_ref.lock().await.clone();
-                                            match derive_session_key(&shared, auth.as_deref()) {
-                                                Ok(session_key) => {
+                                            let cipher = derive_session_key(&shared, auth.as_deref())
+                                                .and_then(|key| FrameCipher::new(key, FrameRole::Host))
+                                                .map(|cipher| cipher.with_rekey(rekey_ref));
+                                            match cipher {
+                                                Ok(cipher) => {
                                                     let authenticated = auth.is_some();
-                                                    let fingerprint = session_fingerprint(&session_key);
-                                                    *e2e_key_ref.lock().await = Some(session_key);
+                                                    let fingerprint = cipher.fingerprint().to_string();
+                                                    *e2e_key_ref.lock().await = Some(SessionKeyState::Active(Arc::new(cipher)));
                                                     let mode = if authenticated { "authentifié (mot de passe)" } else { "non authentifié (sans mot de passe)" };
                                                     println!("[CRYPTO] Hôte: clé E2E dérivée — AES-256-GCM actif — {} — empreinte {}", mode, fingerprint);
                                                     let _ = app_for_secure.emit("ghosthand-session-secure", serde_json::json!({
                                                         "fingerprint": fingerprint,
                                                         "authenticated": authenticated,
                                                     }));
+                                                    // Informer le viewer de la permission accordée
+                                                    let perm = ControlMessage::PermissionChanged {
+                                                        view_only: view_only_ref.load(Ordering::SeqCst),
+                                                    };
+                                                    if let Ok(bytes) = perm.to_bytes() {
+                                                        let payload = seal_control(&e2e_key_ref, bytes).await;
+                                                        let _ = webrtc_perm.send_data(&payload).await;
+                                                    }
+                                                    request_file_resumes(&file_manager_ref, &webrtc_perm, &e2e_key_ref, &app_for_files).await;
                                                 }
                                                 Err(e) => {
                                                     eprintln!("[CRYPTO] Erreur dérivation HKDF: {}", e);
@@ -1234,7 +2130,17 @@ async fn start_input_handler(
                                 if let Some(ref cap) = *cap_opt {
                                     let mut cap_guard = cap.lock().await;
                                     match cap_guard.select_display(display_id) {
-                                        Ok(_) => println!("[INPUT] Moniteur switché → {}", display_id),
+                                        Ok(_) => {
+                                            println!("[INPUT] Moniteur switché → {}", display_id);
+                                            // Les coordonnées normalisées suivent l'écran sélectionné
+                                            let display = cap_guard.get_displays().ok()
+                                                .and_then(|d| d.into_iter().find(|d| d.id == display_id));
+                                            if let Some(d) = display {
+                                                handler_clone
+                                                    .set_display_bounds(d.x, d.y, d.width as i32, d.height as i32)
+                                                    .await;
+                                            }
+                                        }
                                         Err(e) => println!("[INPUT] Erreur switch moniteur: {}", e),
                                     }
                                 }
@@ -1249,12 +2155,61 @@ async fn start_input_handler(
                                     println!("[INPUT] Résolution changée → {:?}", target);
                                 }
                             }
+                            msg @ (ControlMessage::FileTransferStart { .. }
+                            | ControlMessage::FileTransferChunk { .. }
+                            | ControlMessage::FileTransferComplete { .. }
+                            | ControlMessage::FolderTransferStart { .. }
+                            | ControlMessage::FileTransferCancel { .. }) => {
+                                handle_incoming_file_message(
+                                    &file_manager_ref, &file_acks_ref, &webrtc_perm, &e2e_key_ref, &app_for_files, msg,
+                                ).await;
+                            }
+                            ControlMessage::FileTransferResume { id, missing } => {
+                                let file_manager = file_manager_ref.clone();
+                                let acks = file_acks_ref.clone();
+                                let transport = webrtc_perm.clone();
+                                let key = e2e_key_ref.clone();
+                                let app = app_for_files.clone();
+                                tokio::spawn(async move {
+                                    resume_outgoing_file(&file_manager, &acks, &transport, &key, &app, id, missing).await;
+                                });
+                            }
+                            ControlMessage::FileTransferAck { id, received, .. } => {
+                                file_acks_ref.ack(&id, received);
+                            }
+                            ControlMessage::FileTransferResponse { id, accepted, reason } => {
+                                log_file_response(&id, accepted, reason.as_deref());
+                                file_acks_ref.respond(&id, accepted);
+                            }
+                            msg @ (ControlMessage::ListDirectory { .. }
+                            | ControlMessage::ReadFile { .. }
+                            | ControlMessage::MakeDirectory { .. }
+                            | ControlMessage::DeletePath { .. }) => {
+                                // Accès aux fichiers de l'hôte : uniquement une fois la clé E2E établie
+                                if real_key.is_none() {
+                                    eprintln!("[FILE] Requête de navigation reçue avant la clé E2E, ignorée");
+                                    continue;
+                                }
+                                let browser = file_browser.clone();
+                                let file_manager = file_manager_ref.clone();
+                                let acks = file_acks_ref.clone();
+                                let transport = webrtc_perm.clone();
+                                let key = e2e_key_ref.clone();
+                                let app = app_for_files.clone();
+                                tokio::spawn(async move {
+                                    handle_file_browser_request(browser, &file_manager, &acks, &transport, &key, &app, msg).await;
+                                });
+                            }
                             other => {
                                 let _ = handler_clone.handle_message(other).await;
                             }
                         }
                     }
                 }
+                // Canal fermé (transport perdu) : ne rien laisser enfoncé sur l'hôte
+                if let Err(e) = handler_clone.release_all().await {
+                    eprintln!("[INPUT] Erreur relâchement input: {}", e);
+                }
             });
 
             println!("[TAURI] Input handler démarré (avec support multi-monitor)");
@@ -1272,6 +2227,8 @@ async fn start_input_handler(
 async fn accept_connection(
     state: State<'_, AppState>,
     from: String,
+    view_only: Option<bool>,
+    clipboard_policy: Option<ClipboardSyncPolicy>,
 ) -> Result<(), String> {
     diag_log(&format!("accept_connection: APPELÉ pour {}", from));
 
@@ -1285,6 +2242,21 @@ async fn accept_connection(
         // Handshake E2E neuf : réinitialiser la clé, mémoriser le secret d'auth (anti-MITM)
         *state.e2e_session_key.lock().await = None;
         *state.e2e_auth_secret.lock().await = session.auth_secret();
+        *state.active_peer_id.lock().await = Some(from.clone());
+        {
+            let mut manager = state.file_transfer_manager.lock().await;
+            manager.set_peer(Some(from.clone()));
+            manager.set_auto_accept(peer_auto_accepts_files(&from));
+        }
+
+        // Permission accordée au viewer (contrôle total par défaut)
+        let view_only = view_only.unwrap_or(false);
+        state.view_only.store(view_only, Ordering::SeqCst);
+        if view_only {
+            println!("[TAURI] Session accordée en lecture seule à {}", from);
+        }
+        let default_policy = state.config.lock().await.clipboard_policy;
+        set_session_clipboard_policy(&state.clipboard_policy, clipboard_policy.unwrap_or(default_policy));
 
         // Enregistrer le canal relay pour le listener
         if let Some(transport) = &session.webrtc {
@@ -1313,6 +2285,51 @@ async fn accept_connection(
     }
 }
 
+/// Basculer la session courante entre lecture seule et contrôle total (côté hôte)
+#[tauri::command]
+async fn set_session_permission(
+    state: State<'_, AppState>,
+    view_only: bool,
+) -> Result<(), String> {
+    let peer_id = state.active_peer_id.lock().await.clone()
+        .ok_or_else(|| "Aucune session active".to_string())?;
+
+    let previous = state.view_only.swap(view_only, Ordering::SeqCst);
+    if previous == view_only {
+        return Ok(());
+    }
+
+    audit_log(
+        AuditLevel::Security,
+        AuditEvent::SessionPermissionChanged {
+            peer_id: peer_id.clone(),
+            view_only,
+        },
+    );
+    println!("[TAURI] Permission de session ({}) → {}", peer_id,
+        if view_only { "lecture seule" } else { "contrôle total" });
+
+    // Passage en lecture seule : relâcher immédiatement ce que le viewer maintenait
+    if view_only {
+        if let Some(handler) = state.input_handler.lock().await.as_ref() {
+            if let Err(e) = handler.release_all().await {
+                eprintln!("[INPUT] Erreur relâchement input: {}", e);
+            }
+        }
+    }
+
+    // Notifier le viewer
+    let session_guard = state.session_manager.lock().await;
+    if let Some(webrtc) = session_guard.as_ref().and_then(|s| s.webrtc.as_ref()) {
+        let msg = ControlMessage::PermissionChanged { view_only };
+        let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
+        let payload = seal_control(&state.e2e_session_key, bytes).await;
+        webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+    }
+
+    Ok(())
+}
+
 /// Rejeter une demande de connexion
 #[tauri::command]
 async fn reject_connection(
@@ -1452,40 +2469,96 @@ async fn start_listening_for_requests(
     Ok(())
 }
 
-/// Synchroniser le presse-papiers : lire le clipboard local et l'envoyer au peer distant
+/// Synchroniser le presse-papiers : lire le clipboard local (texte, HTML ou image)
+/// et l'envoyer au peer distant. Retourne le type MIME et la taille envoyés.
 #[tauri::command]
-async fn sync_clipboard(state: State<'_, AppState>) -> Result<String, String> {
-    let content = {
-        let cm = state.clipboard_manager.lock().map_err(|e| format!("Lock erreur: {}", e))?;
-        cm.get_clipboard().map_err(|e| format!("Erreur clipboard: {}", e))?
+async fn sync_clipboard(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    // Rôle local : un handler d'input actif signifie qu'on est l'hôte
+    let role = if state.input_handler.lock().await.is_some() {
+        ClipboardRole::Host
+    } else {
+        ClipboardRole::Viewer
+    };
+    let policy = *state.clipboard_policy.lock().map_err(|e| format!("Lock erreur: {}", e))?;
+    if !policy.allows_outgoing(role) {
+        return Err(format!("Synchronisation du presse-papiers désactivée dans ce sens ({:?})", policy));
+    }
+
+    let clipboard = state.clipboard_manager.clone();
+    let content = tauri::async_runtime::spawn_blocking(move || {
+        clipboard.get_content().map_err(|e| format!("Erreur clipboard: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Erreur clipboard: {}", e))??;
+
+    // Côté hôte, le contenu sortant passe par le filtre (secrets, taille) et l'audit
+    let content = if role == ClipboardRole::Host {
+        let filter = ClipboardFilter::new(&state.config.lock().await.clipboard_filter)
+            .map_err(|e| format!("Filtre presse-papiers: {}", e))?;
+        let peer_id = state.active_peer_id.lock().await.clone();
+        filter
+            .check(content, ClipboardDirection::HostToViewer, peer_id.as_deref())
+            .ok_or_else(|| "Presse-papiers bloqué par le filtre de l'hôte".to_string())?
+    } else {
+        content
     };
+    let msg = content.to_message().map_err(|e| format!("Erreur clipboard: {}", e))?;
 
-    // Envoyer via WebRTC si connecté
+    // Envoyer via WebRTC si connecté (fragmenté par le transport au-delà de 60 KB)
     let session_guard = state.session_manager.lock().await;
     if let Some(session) = session_guard.as_ref() {
         if let Some(webrtc) = &session.webrtc {
-            let msg = ControlMessage::ClipboardSync { content: content.clone() };
             let bytes = msg.to_bytes().map_err(|e| format!("Erreur sérialisation: {}", e))?;
             let payload = seal_control(&state.e2e_session_key, bytes).await;
             webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
         }
     }
 
-    Ok(content)
+    Ok(serde_json::json!({
+        "mime": content.mime().as_str(),
+        "size": content.size(),
+    }))
 }
 
 /// Récupérer le contenu du presse-papiers
 #[tauri::command]
 fn get_clipboard(state: State<AppState>) -> Result<String, String> {
-    let cm = state.clipboard_manager.lock().map_err(|e| format!("Lock erreur: {}", e))?;
-    cm.get_clipboard().map_err(|e| format!("Erreur: {}", e))
+    state.clipboard_manager.get_clipboard().map_err(|e| format!("Erreur: {}", e))
 }
 
 /// Définir le contenu du presse-papiers
 #[tauri::command]
 fn set_clipboard(state: State<AppState>, content: String) -> Result<(), String> {
-    let cm = state.clipboard_manager.lock().map_err(|e| format!("Lock erreur: {}", e))?;
-    cm.set_clipboard(&content).map_err(|e| format!("Erreur: {}", e))
+    state.clipboard_manager.set_clipboard(&content).map_err(|e| format!("Erreur: {}", e))
+}
+
+/// Changer les sens de synchronisation du presse-papiers pour la session courante
+#[tauri::command]
+fn set_clipboard_policy(state: State<AppState>, policy: ClipboardSyncPolicy) -> Result<(), String> {
+    set_session_clipboard_policy(&state.clipboard_policy, policy);
+    Ok(())
+}
+
+/// Politique de synchronisation du presse-papiers de la session courante
+#[tauri::command]
+fn get_clipboard_policy(state: State<AppState>) -> Result<ClipboardSyncPolicy, String> {
+    state.clipboard_policy.lock().map(|p| *p).map_err(|e| format!("Lock erreur: {}", e))
+}
+
+fn set_session_clipboard_policy(handle: &ClipboardPolicyHandle, policy: ClipboardSyncPolicy) {
+    if let Ok(mut current) = handle.lock() {
+        if *current != policy {
+            println!("[CLIPBOARD] Politique de synchronisation → {:?}", policy);
+        }
+        *current = policy;
+    }
+}
+
+/// Remplacer la tâche de surveillance du presse-papiers (une seule par session)
+async fn restart_clipboard_watcher(state: &AppState, watcher: tokio::task::JoinHandle<()>) {
+    if let Some(previous) = state.clipboard_watcher.lock().await.replace(watcher) {
+        previous.abort();
+    }
 }
 
 /// Envoyer un message de chat
@@ -1587,8 +2660,9 @@ fn get_displays() -> Result<Vec<serde_json::Value>, String> {
 #[tauri::command]
 async fn send_file(
     state: State<'_, AppState>,
+    app_handle: AppHandle,
     file_path: String,
-) -> Result<(), String> {
+) -> Result<String, String> {
     let path = std::path::Path::new(&file_path);
 
     // VULN-FIX-A6: Validation du fichier avant envoi
@@ -1604,12 +2678,13 @@ async fn send_file(
         return Err("Le chemin ne pointe pas vers un fichier régulier".to_string());
     }
 
-    // Limite de taille: 500 MB max par transfert
-    const MAX_FILE_SIZE: u64 = 500 * 1024 * 1024;
-    if metadata.len() > MAX_FILE_SIZE {
+    // Limite de taille : politique de transfert de la configuration
+    let max_file_size = state.config.lock().await.file_transfer.max_file_size;
+    if metadata.len() > max_file_size {
         return Err(format!(
-            "Fichier trop volumineux: {} MB (max: 500 MB)",
-            metadata.len() / (1024 * 1024)
+            "Fichier trop volumineux: {} MB (max: {} MB)",
+            metadata.len() / (1024 * 1024),
+            max_file_size / (1024 * 1024)
         ));
     }
 
@@ -1621,46 +2696,348 @@ async fn send_file(
         return Err("Nom de fichier invalide (caractères interdits)".to_string());
     }
 
-    let (id, name, size, chunks) = ghost_hand_client::file_transfer::FileTransferManager::prepare_send(path)
+    // Calcul du SHA-256 (lecture complète du fichier) hors du runtime async
+    let source = path.to_path_buf();
+    let mut transfer = tokio::task::spawn_blocking(move || FileTransferManager::prepare_send(&source, max_file_size))
+        .await
+        .map_err(|e| format!("Erreur préparation: {}", e))?
         .map_err(|e| format!("Erreur préparation: {}", e))?;
+    let transport = session_transport(&state).await?;
+    send_outgoing_file(
+        &state.file_transfer_manager, &state.file_acks, &transport, &state.e2e_session_key, &app_handle, &mut transfer,
+    ).await?;
+    Ok(transfer.id)
+}
 
+/// Transport de la session en cours (sans garder la session verrouillée pendant l'envoi)
+async fn session_transport(state: &AppState) -> Result<ghost_hand_client::network::Transport, String> {
     let session_guard = state.session_manager.lock().await;
-    if let Some(session) = session_guard.as_ref() {
-        if let Some(webrtc) = &session.webrtc {
-            // Envoyer FileTransferStart (chiffré)
-            let start_msg = ControlMessage::FileTransferStart {
-                id: id.clone(), name, size,
-            };
-            let bytes = start_msg.to_bytes().map_err(|e| format!("Erreur: {}", e))?;
-            let payload = seal_control(&state.e2e_session_key, bytes).await;
-            webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+    let session = session_guard.as_ref().ok_or("Non connecté")?;
+    session.webrtc.clone().ok_or_else(|| "Pas de connexion WebRTC".to_string())
+}
+
+/// Envoyer un fichier préparé, avec contrôle de flux et reprise possible
+async fn send_outgoing_file(
+    manager: &Mutex<FileTransferManager>,
+    acks: &AckRegistry,
+    transport: &ghost_hand_client::network::Transport,
+    e2e_key: &SessionKeyHandle,
+    app: &AppHandle,
+    transfer: &mut OutgoingTransfer,
+) -> Result<(), String> {
+    // Mémoriser l'envoi : le destinataire pourra le reprendre après une coupure
+    if let Err(e) = manager.lock().await.register_outgoing(transfer) {
+        eprintln!("[FILE] Envoi {} non reprenable: {}", transfer.id, e);
+    }
+
+    // Un fichier seul a sa propre entrée dans la file ; ceux d'un dossier
+    // avancent l'entrée du dossier, déjà active (voir `send_folder`)
+    let queue = manager.lock().await.queue().clone();
+    let standalone = transfer.folder.is_none();
+    let entry = transfer.folder.clone().unwrap_or_else(|| transfer.id.clone());
+    let base = if standalone {
+        queue.enqueue(&entry, &transfer.name, TransferDirection::Send, transfer.size);
+        0
+    } else {
+        queue.get(&entry).map_or(0, |t| t.transferred)
+    };
+
+    let ack_rx = match &transfer.folder {
+        Some(folder) => acks.register_in_folder(&transfer.id, folder),
+        None => acks.register(&transfer.id),
+    };
+    let result = async {
+        if standalone {
+            queue.acquire(&entry).await?;
+        }
+        FileSender::new()
+            .with_queue(queue.clone(), entry.as_str())
+            .send_over(transport, e2e_key, transfer, None, ack_rx, |progress| {
+                queue.update(&entry, base + progress.acked);
+                let _ = app.emit("ghosthand-file-send-progress", &progress);
+            })
+            .await
+    }
+    .await;
+    acks.unregister(&transfer.id);
+    if standalone {
+        queue.finish(&entry, result.as_ref().err().map(|e| e.to_string()));
+    }
+    match result {
+        Ok(()) => {}
+        // Refusé ou annulé : l'envoi ne sera pas repris
+        Err(e @ GhostHandError::TransferCancelled { .. }) => {
+            manager.lock().await.finish_outgoing(&transfer.id);
+            return Err(e.to_string());
+        }
+        Err(e) => return Err(format!("Erreur envoi: {}", e)),
+    }
+
+    manager.lock().await.finish_outgoing(&transfer.id);
+    Ok(())
+}
+
+/// Envoyer un dossier au pair : manifeste (une seule annonce côté réception),
+/// puis chaque fichier. La taille totale est bornée par `file_transfer.max_file_size`.
+#[tauri::command]
+async fn send_folder(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    folder_path: String,
+) -> Result<String, String> {
+    let root = std::path::PathBuf::from(&folder_path);
+    let max_file_size = state.config.lock().await.file_transfer.max_file_size;
+    let folder_id = format!("fd-{}", std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis());
+
+    // Parcours et SHA-256 de chaque fichier hors du runtime async
+    let id = folder_id.clone();
+    let (manifest, mut transfers) = tokio::task::spawn_blocking(move || {
+        let manifest = FolderManifest::build(&root, max_file_size)?;
+        let transfers = manifest.transfers(&id)?;
+        Ok::<_, GhostHandError>((manifest, transfers))
+    })
+    .await
+    .map_err(|e| format!("Erreur préparation: {}", e))?
+    .map_err(|e| format!("Erreur préparation: {}", e))?;
+    println!(
+        "[FILE] Envoi du dossier {} ({} fichiers, {} bytes)",
+        manifest.name, transfers.len(), manifest.total_size()
+    );
 
-            // Envoyer les chunks (chiffrés)
-            let mut offset = 0u64;
-            for chunk in chunks {
-                let chunk_len = chunk.len() as u64;
-                let chunk_msg = ControlMessage::FileTransferChunk {
-                    id: id.clone(), data: chunk, offset,
-                };
-                let bytes = chunk_msg.to_bytes().map_err(|e| format!("Erreur: {}", e))?;
-                let payload = seal_control(&state.e2e_session_key, bytes).await;
-                webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
-                offset += chunk_len;
+    let transport = session_transport(&state).await?;
+    let (folder_name, total_size) = (manifest.name.clone(), manifest.total_size());
+    let start = ControlMessage::FolderTransferStart {
+        id: folder_id.clone(),
+        name: manifest.name,
+        entries: manifest.entries,
+    };
+    // Le dossier occupe une seule place dans la file, le temps de tous ses fichiers
+    let queue = state.transfer_queue.clone();
+    queue.enqueue(&folder_id, &folder_name, TransferDirection::Send, total_size);
+    // Suivi du dossier entier : acceptation par le destinataire, puis annulation éventuelle
+    let mut folder_acks = state.file_acks.register(&folder_id);
+    let result = async {
+        queue.acquire(&folder_id).await.map_err(|e| e.to_string())?;
+        send_control(&transport, &state.e2e_session_key, start).await?;
+        FileSender::new()
+            .wait_accepted(&mut folder_acks, &folder_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        for transfer in &mut transfers {
+            if folder_acks.borrow().cancelled {
+                return Err(format!("Dossier {} annulé", folder_id));
             }
+            send_outgoing_file(
+                &state.file_transfer_manager, &state.file_acks, &transport, &state.e2e_session_key, &app_handle, transfer,
+            ).await?;
+        }
+        Ok(())
+    }
+    .await;
+    state.file_acks.unregister(&folder_id);
+    queue.finish(&folder_id, result.as_ref().err().cloned());
+    result.map(|()| folder_id)
+}
 
-            // Envoyer FileTransferComplete (chiffré)
-            let complete_msg = ControlMessage::FileTransferComplete { id };
-            let bytes = complete_msg.to_bytes().map_err(|e| format!("Erreur: {}", e))?;
-            let payload = seal_control(&state.e2e_session_key, bytes).await;
-            webrtc.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))?;
+/// Délai de réponse de l'hôte à une requête de navigation distante
+const BROWSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Envoyer une requête de navigation à l'hôte et attendre sa réponse
+async fn remote_file_request(
+    state: &AppState,
+    request: impl FnOnce(u64) -> ControlMessage,
+) -> Result<FileBrowserResult, String> {
+    let transport = session_transport(state).await?;
+    let (request_id, response) = state.browse_requests.register();
+    let sent = async {
+        let bytes = request(request_id).to_bytes().map_err(|e| format!("Erreur: {}", e))?;
+        let payload = seal_control(&state.e2e_session_key, bytes).await;
+        transport.send_data(&payload).await.map_err(|e| format!("Erreur envoi: {}", e))
+    }
+    .await;
+    if let Err(e) = sent {
+        state.browse_requests.cancel(request_id);
+        return Err(e);
+    }
+    match tokio::time::timeout(BROWSE_TIMEOUT, response).await {
+        Ok(Ok(FileBrowserResult::Error { message })) => Err(message),
+        Ok(Ok(result)) => Ok(result),
+        Ok(Err(_)) => Err("Requête abandonnée".to_string()),
+        Err(_) => {
+            state.browse_requests.cancel(request_id);
+            Err("L'hôte n'a pas répondu".to_string())
+        }
+    }
+}
 
-            Ok(())
+/// Lister un dossier de l'hôte (`root` absent = racines exposées par l'hôte)
+#[tauri::command]
+async fn list_remote_directory(
+    state: State<'_, AppState>,
+    root: Option<String>,
+    path: Option<String>,
+) -> Result<FileBrowserResult, String> {
+    remote_file_request(&state, |request_id| ControlMessage::ListDirectory {
+        request_id,
+        root: root.unwrap_or_default(),
+        path: path.unwrap_or_default(),
+    })
+    .await
+}
+
+/// Télécharger un fichier de l'hôte ; renvoie l'id du transfert entrant
+#[tauri::command]
+async fn download_remote_file(
+    state: State<'_, AppState>,
+    root: String,
+    path: String,
+) -> Result<String, String> {
+    match remote_file_request(&state, |request_id| ControlMessage::ReadFile { request_id, root, path }).await? {
+        FileBrowserResult::Transfer { transfer_id } => Ok(transfer_id),
+        other => Err(format!("Réponse inattendue: {:?}", other)),
+    }
+}
+
+/// Créer un dossier sur l'hôte (racine en lecture-écriture)
+#[tauri::command]
+async fn create_remote_directory(
+    state: State<'_, AppState>,
+    root: String,
+    path: String,
+) -> Result<(), String> {
+    remote_file_request(&state, |request_id| ControlMessage::MakeDirectory { request_id, root, path }).await?;
+    Ok(())
+}
+
+/// Supprimer un fichier ou un dossier vide sur l'hôte (racine en lecture-écriture)
+#[tauri::command]
+async fn delete_remote_path(
+    state: State<'_, AppState>,
+    root: String,
+    path: String,
+) -> Result<(), String> {
+    remote_file_request(&state, |request_id| ControlMessage::DeletePath { request_id, root, path }).await?;
+    Ok(())
+}
+
+/// Le pair accepte-t-il ses fichiers sans confirmation (`KnownPeer::auto_accept_files`) ?
+fn peer_auto_accepts_files(peer_id: &str) -> bool {
+    global_storage()
+        .and_then(|s| s.lock().ok().and_then(|s| s.get_known_peer(peer_id).map(|p| p.auto_accept_files)))
+        .unwrap_or(false)
+}
+
+/// Accepter ou refuser un fichier/dossier annoncé par le pair (`ghosthand-file-offer`)
+#[tauri::command]
+async fn respond_file_offer(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    id: String,
+    accept: bool,
+) -> Result<(), String> {
+    let transport = session_transport(&state).await?;
+    let refusal = {
+        let mut manager = state.file_transfer_manager.lock().await;
+        if accept {
+            start_offer(&mut manager, &app_handle, &id).err().map(|e| e.to_string())
         } else {
-            Err("Pas de connexion WebRTC".to_string())
+            manager.reject(&id).ok_or_else(|| format!("Aucune annonce en attente pour {}", id))?;
+            Some("refusé par l'utilisateur".to_string())
         }
-    } else {
-        Err("Non connecté".to_string())
+    };
+    let response = ControlMessage::FileTransferResponse {
+        id: id.clone(),
+        accepted: refusal.is_none(),
+        reason: refusal.clone(),
+    };
+    send_control(&transport, &state.e2e_session_key, response).await?;
+    match refusal {
+        Some(reason) if accept => Err(format!("Transfert {} impossible: {}", id, reason)),
+        _ => Ok(()),
+    }
+}
+
+/// Annonces de fichiers en attente de décision
+#[tauri::command]
+async fn get_pending_file_offers(state: State<'_, AppState>) -> Result<Vec<FileOffer>, String> {
+    Ok(state.file_transfer_manager.lock().await.pending_offers())
+}
+
+/// Annuler un transfert en cours, entrant ou sortant (l'id d'un dossier annule
+/// tous ses fichiers) ; le pair est prévenu et l'état partiel supprimé
+#[tauri::command]
+async fn cancel_file_transfer(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    id: String,
+) -> Result<(), String> {
+    // Un envoi encore dans la file n'a pas d'acquittements enregistrés
+    let queued = state.transfer_queue.finish(&id, Some("annulé".to_string()));
+    let outgoing = state.file_acks.cancel(&id);
+    let incoming = state.file_transfer_manager.lock().await.cancel(&id);
+    if !queued && !outgoing && !incoming {
+        return Err(format!("Transfert {} introuvable", id));
     }
+    let _ = app_handle.emit("ghosthand-file-cancelled", serde_json::json!({
+        "id": id, "reason": null, "by_peer": false,
+    }));
+    let transport = session_transport(&state).await?;
+    send_control(&transport, &state.e2e_session_key, ControlMessage::FileTransferCancel { id, reason: None }).await
+}
+
+/// Accepter (ou non) sans confirmation les fichiers d'un pair connu
+#[tauri::command]
+async fn set_peer_auto_accept_files(
+    state: State<'_, AppState>,
+    peer_id: String,
+    auto_accept: bool,
+) -> Result<(), String> {
+    let storage_mutex = global_storage().ok_or("Storage non initialisé")?;
+    {
+        let mut storage = storage_mutex.lock().map_err(|_| "Impossible de verrouiller le storage")?;
+        if !storage.set_peer_auto_accept_files(&peer_id, auto_accept) {
+            return Err(format!("Pair {} introuvable", peer_id));
+        }
+        storage.save().map_err(|e| format!("Erreur sauvegarde: {}", e))?;
+    }
+    // Effet immédiat si le pair est connecté
+    if state.active_peer_id.lock().await.as_deref() == Some(peer_id.as_str()) {
+        state.file_transfer_manager.lock().await.set_auto_accept(auto_accept);
+    }
+    Ok(())
+}
+
+/// État de la file des transferts (envois et réceptions)
+#[tauri::command]
+async fn get_transfers(state: State<'_, AppState>) -> Result<Vec<TransferInfo>, String> {
+    Ok(state.transfer_queue.snapshot())
+}
+
+/// Mettre en pause un envoi : il libère sa place dans la file avant son prochain chunk
+#[tauri::command]
+async fn pause_transfer(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    if !state.transfer_queue.pause(&id) {
+        return Err(format!("Envoi {} introuvable ou non suspendable", id));
+    }
+    Ok(())
+}
+
+/// Reprendre un envoi en pause, à son tour dans la file
+#[tauri::command]
+async fn resume_transfer(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    if !state.transfer_queue.resume(&id) {
+        return Err(format!("Envoi {} introuvable ou pas en pause", id));
+    }
+    Ok(())
+}
+
+/// Retirer de la file les transferts terminés
+#[tauri::command]
+async fn clear_finished_transfers(state: State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.transfer_queue.clear_finished())
 }
 
 /// Statistiques système temps-réel (CPU, RAM, Disque, Uptime)
@@ -1703,7 +3080,7 @@ fn get_system_stats(state: State<AppState>) -> Result<serde_json::Value, String>
 /// la session E2E. `None` tant que le handshake n'est pas terminé.
 #[tauri::command]
 async fn get_session_fingerprint(state: State<'_, AppState>) -> Result<Option<String>, String> {
-    Ok(real_e2e_key(&state.e2e_session_key).await.map(|k| session_fingerprint(&k)))
+    Ok(real_e2e_key(&state.e2e_session_key).await.map(|cipher| cipher.fingerprint().to_string()))
 }
 
 fn main() {
@@ -1817,6 +3194,9 @@ fn main() {
             .with_memory(MemoryRefreshKind::everything()),
     );
 
+    let clipboard_policy = config.clipboard_policy;
+    let file_transfer_manager = FileTransferManager::from_config(&config.file_transfer);
+    let transfer_queue = file_transfer_manager.queue().clone();
     let app_state = AppState {
         device_id: device_id.clone(),
         data_dir: data_dir.clone(),
@@ -1825,14 +3205,28 @@ fn main() {
         pending_requests: Arc::new(Mutex::new(Vec::new())),
         streamer_handle: Arc::new(Mutex::new(None)),
         discovered_peers,
-        clipboard_manager: Arc::new(std::sync::Mutex::new(ClipboardManager::new())),
-        file_transfer_manager: Arc::new(Mutex::new(FileTransferManager::new())),
+        clipboard_manager: ClipboardManager::new(),
+        clipboard_policy: Arc::new(std::sync::Mutex::new(clipboard_policy)),
+        clipboard_watcher: Arc::new(Mutex::new(None)),
+        mouse_flush_task: Arc::new(Mutex::new(None)),
+        file_transfer_manager: Arc::new(Mutex::new(file_transfer_manager)),
+        file_acks: AckRegistry::new(),
+        transfer_queue,
+        browse_requests: BrowseRequests::new(),
         active_capturer: Arc::new(Mutex::new(None)),
         active_encoder: Arc::new(Mutex::new(None)),
         e2e_session_key: Arc::new(Mutex::new(None)),
         e2e_auth_secret: Arc::new(Mutex::new(None)),
         sys_handle: Arc::new(std::sync::Mutex::new(sys_init)),
         relay_data_tx: Arc::new(Mutex::new(None)),
+        active_peer_id: Arc::new(Mutex::new(None)),
+        view_only: Arc::new(AtomicBool::new(false)),
+        input_handler: Arc::new(Mutex::new(None)),
+        local_input_tx: Arc::new(std::sync::Mutex::new(None)),
+        keyboard_mode: Arc::new(Mutex::new((KeyInputMode::Logical, None))),
+        move_coalescer: Arc::new(MouseMoveCoalescer::new()),
+        macro_recorder: Arc::new(MacroRecorder::new()),
+        macro_playback: Arc::new(std::sync::Mutex::new(None)),
     };
 
     // Cloner pour les closures
@@ -1850,6 +3244,8 @@ fn main() {
             connect_to_device,
             disconnect,
             send_mouse_event,
+            send_pointer_event,
+            send_key_sequence,
             send_keyboard_event,
             get_config,
             update_config,
@@ -1860,6 +3256,19 @@ fn main() {
             start_receiving,
             start_input_handler,
             accept_connection,
+            set_session_permission,
+            start_local_input_capture,
+            push_local_input,
+            send_text,
+            start_macro_recording,
+            stop_macro_recording,
+            play_macro,
+            abort_macro,
+            list_macros,
+            delete_macro,
+            export_macro,
+            import_macro,
+            set_keyboard_mode,
             reject_connection,
             get_pending_requests,
             start_listening_for_requests,
@@ -1867,6 +3276,8 @@ fn main() {
             sync_clipboard,
             get_clipboard,
             set_clipboard,
+            set_clipboard_policy,
+            get_clipboard_policy,
             // Chat
             send_chat_message,
             // Multi-monitor
@@ -1876,6 +3287,20 @@ fn main() {
             change_resolution,
             // File transfer
             send_file,
+            send_folder,
+            respond_file_offer,
+            get_pending_file_offers,
+            cancel_file_transfer,
+            set_peer_auto_accept_files,
+            get_transfers,
+            pause_transfer,
+            resume_transfer,
+            clear_finished_transfers,
+            // Remote file browser
+            list_remote_directory,
+            download_remote_file,
+            create_remote_directory,
+            delete_remote_path,
             // Settings commands
             load_settings,
             save_settings,
@@ -1906,6 +3331,24 @@ fn main() {
                 eprintln!("[TAURI] Impossible de définir le titre: {}", e);
             }
 
+            // Relayer les changements de la file des transferts vers l'UI
+            let mut transfer_events = app.state::<AppState>().transfer_queue.subscribe();
+            let app_for_transfers = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match transfer_events.recv().await {
+                        Ok(event) => {
+                            let _ = app_for_transfers.emit("ghosthand-transfer", &event);
+                        }
+                        // Événements perdus : l'UI se resynchronise via `get_transfers`
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                            let _ = app_for_transfers.emit("ghosthand-transfer-lagged", n);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+
             // Configurer le System Tray
             let device_id_label = device_id_for_title.clone();
             let open_item = MenuItemBuilder::with_id("open", "Ouvrir GhostHandDesk").build(app)?;
@@ -1972,6 +3415,15 @@ fn main() {
                 tauri::WindowEvent::Destroyed => {
                     println!("[APP] Fenêtre détruite, nettoyage en cours...");
                 }
+                tauri::WindowEvent::Focused(focused) => {
+                    // La capture d'input locale ne transmet que si le viewer a le focus
+                    let state = window.state::<AppState>();
+                    if let Ok(tx) = state.local_input_tx.lock() {
+                        if let Some(tx) = tx.as_ref() {
+                            let _ = tx.send(RawInputEvent::FocusChanged { focused: *focused });
+                        }
+                    }
+                }
                 _ => {}
             }
         })
@@ -1,3 +1,4 @@
+use ghost_hand_client::input_macro::{self, InputMacro};
 use ghost_hand_client::storage::{global_storage, ConnectionHistory};
 
 /// Obtenir l'historique des connexions
@@ -77,3 +78,68 @@ pub async fn get_storage_stats() -> Result<ghost_hand_client::storage::StorageSt
         Err("Storage non initialisé".to_string())
     }
 }
+
+/// Lister les macros d'input enregistrées
+#[tauri::command]
+pub fn list_macros() -> Result<Vec<String>, String> {
+    if let Some(storage_mutex) = global_storage() {
+        if let Ok(storage) = storage_mutex.lock() {
+            Ok(input_macro::stored_macro_names(&storage))
+        } else {
+            Err("Impossible de verrouiller le storage".to_string())
+        }
+    } else {
+        Err("Storage non initialisé".to_string())
+    }
+}
+
+/// Supprimer une macro d'input
+#[tauri::command]
+pub fn delete_macro(name: String) -> Result<(), String> {
+    if let Some(storage_mutex) = global_storage() {
+        if let Ok(mut storage) = storage_mutex.lock() {
+            if input_macro::remove_stored_macro(&mut storage, &name) {
+                storage.save().map_err(|e| format!("Erreur sauvegarde: {}", e))?;
+                Ok(())
+            } else {
+                Err(format!("Macro {} introuvable", name))
+            }
+        } else {
+            Err("Impossible de verrouiller le storage".to_string())
+        }
+    } else {
+        Err("Storage non initialisé".to_string())
+    }
+}
+
+/// Exporter une macro vers un fichier JSON portable
+#[tauri::command]
+pub fn export_macro(name: String, file_path: String) -> Result<(), String> {
+    let input_macro = load_macro(&name)?;
+    input_macro.save_to_file(&file_path).map_err(|e| format!("Erreur export: {}", e))
+}
+
+/// Importer une macro depuis un fichier JSON (remplace une macro du même nom)
+#[tauri::command]
+pub fn import_macro(file_path: String) -> Result<String, String> {
+    let input_macro = InputMacro::load_from_file(&file_path).map_err(|e| format!("Macro invalide: {}", e))?;
+    save_macro(&input_macro)?;
+    Ok(input_macro.name)
+}
+
+/// Lire une macro stockée
+pub fn load_macro(name: &str) -> Result<InputMacro, String> {
+    let storage_mutex = global_storage().ok_or_else(|| "Storage non initialisé".to_string())?;
+    let storage = storage_mutex.lock().map_err(|_| "Impossible de verrouiller le storage".to_string())?;
+    input_macro::load_stored_macro(&storage, name)
+        .map_err(|e| format!("Macro invalide: {}", e))?
+        .ok_or_else(|| format!("Macro {} introuvable", name))
+}
+
+/// Enregistrer une macro dans le storage et sauvegarder
+pub fn save_macro(input_macro: &InputMacro) -> Result<(), String> {
+    let storage_mutex = global_storage().ok_or_else(|| "Storage non initialisé".to_string())?;
+    let mut storage = storage_mutex.lock().map_err(|_| "Impossible de verrouiller le storage".to_string())?;
+    input_macro::store_macro(&mut storage, input_macro).map_err(|e| format!("Macro invalide: {}", e))?;
+    storage.save().map_err(|e| format!("Erreur sauvegarde: {}", e))
+}
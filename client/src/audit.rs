@@ -71,6 +71,69 @@ pub enum AuditEvent {
         // Ne pas logger les touches pour la confidentialité
     },
 
+    /// Texte injecté sur l'hôte (`TypeText`) — seule la longueur est journalisée
+    TextInjected {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+        chars: usize,
+    },
+
+    /// Séquence de touches approuvée par l'hôte injectée (`SendKeySequence`)
+    KeySequenceInjected {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Presse-papiers synchronisé — jamais le contenu, seulement sa nature
+    ClipboardSynced {
+        direction: String, // "host_to_viewer", "viewer_to_host"
+        mime: String,
+        size: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Presse-papiers bloqué ou masqué par le filtre (règle déclenchée)
+    ClipboardFiltered {
+        direction: String,
+        mime: String,
+        size: usize,
+        rule: String,
+        action: String, // "blocked", "redacted"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Accès du navigateur de fichiers distant (autorisé ou refusé)
+    FileBrowserAccess {
+        operation: String, // "list", "read", "mkdir", "delete"
+        root: String,
+        path: String,
+        allowed: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Trame chiffrée rejetée par la protection anti-rejeu (doublon, trop
+    /// ancienne ou renvoyée dans le mauvais sens)
+    FrameReplayRejected {
+        sequence: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        highest: Option<u64>,
+        reason: String, // "duplicate", "too_old", "wrong_direction", "stale_epoch"
+        /// Rejets non audités depuis le précédent événement (limitation du débit)
+        suppressed: u64,
+    },
+
+    /// Clé de trame renouvelée (ratchet HKDF) dans un sens de la session
+    SessionRekeyed {
+        direction: String, // "send", "receive"
+        epoch: u32,
+    },
+
     /// Erreur de sécurité détectée
     SecurityError {
         error_code: String,
@@ -79,6 +142,27 @@ pub enum AuditEvent {
         peer_id: Option<String>,
     },
 
+    /// Événement d'input refusé par la politique d'input (règle correspondante)
+    InputPolicyDenied {
+        rule: String,
+        input: String, // "key:f4", "mouse:press:right"...
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Input distant écarté car la session est en lecture seule
+    ViewOnlyInputDropped {
+        input: String, // "mouse_move", "key_press", "clipboard_sync"...
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peer_id: Option<String>,
+    },
+
+    /// Permission de session modifiée par l'hôte (lecture seule / contrôle total)
+    SessionPermissionChanged {
+        peer_id: String,
+        view_only: bool,
+    },
+
     /// Tentative suspecte détectée
     SuspiciousActivity {
         description: String,
@@ -1,10 +1,173 @@
 use crate::error::{GhostHandError, Result};
+use crate::protocol::{ClipboardMime, ControlMessage};
+use base64::Engine;
+use std::borrow::Cow;
+use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 use tracing::{debug, warn};
 
-/// Gestionnaire de presse-papiers bidirectionnel
+/// Taille max d'un contenu synchronisé (octets bruts, avant encodage base64)
+pub const MAX_CLIPBOARD_BYTES: usize = 8 * 1024 * 1024;
+/// Dimension max (largeur ou hauteur) d'une image reçue, contre les « PNG bombs »
+const MAX_CLIPBOARD_IMAGE_DIMENSION: u32 = 16_384;
+
+/// Contenu du presse-papiers, tel qu'échangé entre les pairs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardContent {
+    Text(String),
+    Html {
+        html: String,
+        alt_text: Option<String>,
+    },
+    /// Image encodée en PNG
+    Image(Vec<u8>),
+}
+
+impl ClipboardContent {
+    pub fn mime(&self) -> ClipboardMime {
+        match self {
+            ClipboardContent::Text(_) => ClipboardMime::TextPlain,
+            ClipboardContent::Html { .. } => ClipboardMime::TextHtml,
+            ClipboardContent::Image(_) => ClipboardMime::ImagePng,
+        }
+    }
+
+    /// Taille du contenu en octets (texte de repli inclus)
+    pub fn size(&self) -> usize {
+        match self {
+            ClipboardContent::Text(text) => text.len(),
+            ClipboardContent::Html { html, alt_text } => {
+                html.len() + alt_text.as_ref().map_or(0, String::len)
+            }
+            ClipboardContent::Image(png) => png.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Construire le message `ClipboardSync`, en refusant les contenus trop volumineux
+    pub fn to_message(&self) -> Result<ControlMessage> {
+        check_size(self.size())?;
+        let (content, alt_text) = match self {
+            ClipboardContent::Text(text) => (text.clone(), None),
+            ClipboardContent::Html { html, alt_text } => (html.clone(), alt_text.clone()),
+            ClipboardContent::Image(png) => {
+                (base64::engine::general_purpose::STANDARD.encode(png), None)
+            }
+        };
+        Ok(ControlMessage::ClipboardSync {
+            content,
+            mime: self.mime(),
+            alt_text,
+        })
+    }
+
+    /// Reconstruire un contenu reçu (champs d'un `ClipboardSync`)
+    pub fn from_sync(content: String, mime: ClipboardMime, alt_text: Option<String>) -> Result<Self> {
+        let content = match mime {
+            ClipboardMime::TextPlain => ClipboardContent::Text(content),
+            ClipboardMime::TextHtml => ClipboardContent::Html { html: content, alt_text },
+            ClipboardMime::ImagePng => {
+                // Refuser avant décodage : base64 ≈ 4/3 de la taille brute
+                check_size(content.len() / 4 * 3)?;
+                let png = base64::engine::general_purpose::STANDARD
+                    .decode(content.as_bytes())
+                    .map_err(|e| GhostHandError::Validation(format!("Image presse-papiers invalide: {}", e)))?;
+                if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+                    return Err(GhostHandError::Validation(
+                        "Image presse-papiers invalide: signature PNG absente".to_string(),
+                    ));
+                }
+                ClipboardContent::Image(png)
+            }
+        };
+        check_size(content.size())?;
+        Ok(content)
+    }
+}
+
+fn check_size(size: usize) -> Result<()> {
+    if size > MAX_CLIPBOARD_BYTES {
+        return Err(GhostHandError::Validation(format!(
+            "Presse-papiers trop volumineux: {} octets (max {})",
+            size, MAX_CLIPBOARD_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// Encoder une image RGBA en PNG
+pub fn encode_png(width: u32, height: u32, rgba: Vec<u8>) -> Result<Vec<u8>> {
+    let img = image::RgbaImage::from_raw(width, height, rgba).ok_or_else(|| {
+        GhostHandError::Internal("Image presse-papiers: taille du buffer RGBA incohérente".to_string())
+    })?;
+    let mut cursor = Cursor::new(Vec::new());
+    img.write_to(&mut cursor, image::ImageFormat::Png)
+        .map_err(|e| GhostHandError::Internal(format!("Erreur encodage PNG: {}", e)))?;
+    Ok(cursor.into_inner())
+}
+
+/// Décoder un PNG en RGBA, avec des dimensions bornées
+pub fn decode_png(png: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_CLIPBOARD_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_CLIPBOARD_IMAGE_DIMENSION);
+    let mut reader = image::ImageReader::with_format(Cursor::new(png), image::ImageFormat::Png);
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| GhostHandError::Validation(format!("Image presse-papiers invalide: {}", e)))?
+        .to_rgba8();
+    Ok((img.width(), img.height(), img.into_raw()))
+}
+
+/// Contenu lu dans le presse-papiers système, avant l'encodage PNG des images
+enum RawContent {
+    Image { width: usize, height: usize, rgba: Vec<u8> },
+    Html { html: String, alt_text: Option<String> },
+    Text(String),
+}
+
+impl RawContent {
+    /// Empreinte bon marché (dimensions + hash des octets bruts) : détecter un
+    /// changement sans réencoder l'image à chaque scrutation
+    fn fingerprint(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        match self {
+            RawContent::Image { width, height, rgba } => (0u8, width, height, rgba).hash(&mut hasher),
+            RawContent::Html { html, alt_text } => (1u8, html, alt_text).hash(&mut hasher),
+            RawContent::Text(text) => (2u8, text).hash(&mut hasher),
+        }
+        hasher.finish()
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            RawContent::Image { rgba, .. } => rgba.is_empty(),
+            RawContent::Html { html, alt_text } => html.is_empty() && alt_text.as_ref().is_none_or(String::is_empty),
+            RawContent::Text(text) => text.is_empty(),
+        }
+    }
+
+    fn encode(self) -> Result<ClipboardContent> {
+        Ok(match self {
+            RawContent::Image { width, height, rgba } => {
+                ClipboardContent::Image(encode_png(width as u32, height as u32, rgba)?)
+            }
+            RawContent::Html { html, alt_text } => ClipboardContent::Html { html, alt_text },
+            RawContent::Text(text) => ClipboardContent::Text(text),
+        })
+    }
+}
+
+/// Gestionnaire de presse-papiers bidirectionnel (les clones partagent le cache)
+#[derive(Clone)]
 pub struct ClipboardManager {
-    last_content: Arc<Mutex<String>>,
+    /// Empreinte du dernier contenu vu ou écrit (voir `RawContent::fingerprint`)
+    last_fingerprint: Arc<Mutex<Option<u64>>>,
 }
 
 impl Default for ClipboardManager {
@@ -16,15 +179,19 @@ impl Default for ClipboardManager {
 impl ClipboardManager {
     pub fn new() -> Self {
         Self {
-            last_content: Arc::new(Mutex::new(String::new())),
+            last_fingerprint: Arc::new(Mutex::new(None)),
         }
     }
 
+    fn open() -> Result<arboard::Clipboard> {
+        arboard::Clipboard::new().map_err(|e| {
+            GhostHandError::Internal(format!("Impossible d'accéder au presse-papiers: {}", e))
+        })
+    }
+
     /// Lire le contenu actuel du presse-papiers
     pub fn get_clipboard(&self) -> Result<String> {
-        let mut ctx = arboard::Clipboard::new().map_err(|e| {
-            GhostHandError::Internal(format!("Impossible d'accéder au presse-papiers: {}", e))
-        })?;
+        let mut ctx = Self::open()?;
         ctx.get_text().map_err(|e| {
             GhostHandError::Internal(format!("Erreur lecture presse-papiers: {}", e))
         })
@@ -32,38 +199,160 @@ impl ClipboardManager {
 
     /// Écrire dans le presse-papiers local
     pub fn set_clipboard(&self, content: &str) -> Result<()> {
-        let mut ctx = arboard::Clipboard::new().map_err(|e| {
-            GhostHandError::Internal(format!("Impossible d'accéder au presse-papiers: {}", e))
-        })?;
-        ctx.set_text(content).map_err(|e| {
+        self.set_content(&ClipboardContent::Text(content.to_string()))
+    }
+
+    /// Lire le contenu le plus riche disponible : image, puis HTML, puis texte
+    pub fn get_content(&self) -> Result<ClipboardContent> {
+        Self::read(&mut Self::open()?)
+    }
+
+    fn read(ctx: &mut arboard::Clipboard) -> Result<ClipboardContent> {
+        Self::read_raw(ctx)?.encode()
+    }
+
+    fn read_raw(ctx: &mut arboard::Clipboard) -> Result<RawContent> {
+        if let Ok(image) = ctx.get_image() {
+            return Ok(RawContent::Image {
+                width: image.width,
+                height: image.height,
+                rgba: image.bytes.into_owned(),
+            });
+        }
+        if let Ok(html) = ctx.get().html() {
+            let alt_text = ctx.get_text().ok().filter(|t| !t.is_empty());
+            return Ok(RawContent::Html { html, alt_text });
+        }
+        ctx.get_text().map(RawContent::Text).map_err(|e| {
+            GhostHandError::Internal(format!("Erreur lecture presse-papiers: {}", e))
+        })
+    }
+
+    /// Écrire un contenu (texte, HTML ou image) dans le presse-papiers local
+    pub fn set_content(&self, content: &ClipboardContent) -> Result<()> {
+        let mut ctx = Self::open()?;
+        let result = match content {
+            ClipboardContent::Text(text) => ctx.set_text(text.as_str()),
+            ClipboardContent::Html { html, alt_text } => {
+                ctx.set_html(html.as_str(), alt_text.as_deref())
+            }
+            ClipboardContent::Image(png) => {
+                let (width, height, rgba) = decode_png(png)?;
+                ctx.set_image(arboard::ImageData {
+                    width: width as usize,
+                    height: height as usize,
+                    bytes: Cow::Owned(rgba),
+                })
+            }
+        };
+        result.map_err(|e| {
             GhostHandError::Internal(format!("Erreur écriture presse-papiers: {}", e))
         })?;
 
-        // Mettre à jour le cache pour éviter les boucles
-        if let Ok(mut last) = self.last_content.lock() {
-            *last = content.to_string();
+        // Mettre à jour le cache pour éviter les boucles. On relit le contenu tel que
+        // le système l'a stocké (HTML réencadré, image convertie...) : c'est cette
+        // forme que `has_changed` verra ensuite.
+        if let Ok(stored) = Self::read_raw(&mut ctx) {
+            if let Ok(mut last) = self.last_fingerprint.lock() {
+                *last = Some(stored.fingerprint());
+            }
         }
 
-        debug!("Presse-papiers mis à jour ({} chars)", content.len());
+        debug!(
+            "Presse-papiers mis à jour ({}, {} octets)",
+            content.mime().as_str(),
+            content.size()
+        );
         Ok(())
     }
 
-    /// Vérifier si le presse-papiers a changé depuis la dernière vérification
-    pub fn has_changed(&self) -> Option<String> {
-        match self.get_clipboard() {
-            Ok(current) => {
-                if let Ok(mut last) = self.last_content.lock() {
-                    if current != *last && !current.is_empty() {
-                        *last = current.clone();
-                        return Some(current);
-                    }
-                }
-                None
-            }
+    /// Vérifier si le presse-papiers a changé depuis la dernière vérification.
+    /// Une image n'est encodée en PNG que si son empreinte a changé.
+    pub fn has_changed(&self) -> Option<ClipboardContent> {
+        let raw = match Self::open().and_then(|mut ctx| Self::read_raw(&mut ctx)) {
+            Ok(raw) => raw,
             Err(e) => {
                 warn!("Erreur vérification presse-papiers: {}", e);
+                return None;
+            }
+        };
+        if raw.is_empty() {
+            return None;
+        }
+        let fingerprint = raw.fingerprint();
+        {
+            let mut last = self.last_fingerprint.lock().ok()?;
+            if *last == Some(fingerprint) {
+                return None;
+            }
+            *last = Some(fingerprint);
+        }
+        match raw.encode() {
+            Ok(content) => Some(content),
+            Err(e) => {
+                warn!("Erreur encodage presse-papiers: {}", e);
                 None
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_roundtrip_through_message() {
+        let rgba: Vec<u8> = (0..4 * 3 * 2).map(|i| i as u8).collect();
+        let png = encode_png(3, 2, rgba.clone()).unwrap();
+        let content = ClipboardContent::Image(png);
+
+        let bytes = content.to_message().unwrap().to_bytes().unwrap();
+        let received = match ControlMessage::from_bytes(&bytes).unwrap() {
+            ControlMessage::ClipboardSync { content, mime, alt_text } => {
+                assert_eq!(mime, ClipboardMime::ImagePng);
+                ClipboardContent::from_sync(content, mime, alt_text).unwrap()
+            }
+            other => panic!("message inattendu: {:?}", other),
+        };
+        assert_eq!(received, content);
+
+        let ClipboardContent::Image(png) = received else { unreachable!() };
+        assert_eq!(decode_png(&png).unwrap(), (3, 2, rgba));
+    }
+
+    #[test]
+    fn test_raw_fingerprint_tracks_pixels_and_dimensions() {
+        let image = |width, height, rgba: Vec<u8>| RawContent::Image { width, height, rgba };
+        let rgba: Vec<u8> = (0..4 * 3 * 2).map(|i| i as u8).collect();
+        let base = image(3, 2, rgba.clone()).fingerprint();
+        assert_eq!(image(3, 2, rgba.clone()).fingerprint(), base);
+        assert_ne!(image(2, 3, rgba.clone()).fingerprint(), base);
+        let mut changed = rgba;
+        changed[5] ^= 1;
+        assert_ne!(image(3, 2, changed).fingerprint(), base);
+        assert_ne!(RawContent::Text("a".into()).fingerprint(), RawContent::Html { html: "a".into(), alt_text: None }.fingerprint());
+    }
+
+    #[test]
+    fn test_legacy_text_message_defaults_to_plain() {
+        let msg: ControlMessage =
+            serde_json::from_str(r#"{"type":"ClipboardSync","content":"bonjour"}"#).unwrap();
+        let ControlMessage::ClipboardSync { content, mime, alt_text } = msg else {
+            panic!("message inattendu");
+        };
+        assert_eq!(
+            ClipboardContent::from_sync(content, mime, alt_text).unwrap(),
+            ClipboardContent::Text("bonjour".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rejects_oversized_and_invalid_payloads() {
+        let big = ClipboardContent::Text("x".repeat(MAX_CLIPBOARD_BYTES + 1));
+        assert!(matches!(big.to_message(), Err(GhostHandError::Validation(_))));
+
+        let not_png = base64::engine::general_purpose::STANDARD.encode(b"GIF89a");
+        assert!(ClipboardContent::from_sync(not_png, ClipboardMime::ImagePng, None).is_err());
+    }
+}
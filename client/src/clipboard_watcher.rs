@@ -0,0 +1,278 @@
+//! Synchronisation automatique du presse-papiers
+//!
+//! Une tâche de fond surveille le presse-papiers local et pousse chaque
+//! changement stable (anti-rebond) vers le pair, selon une politique
+//! directionnelle propre à la session. Les contenus reçus du pair sont écrits
+//! via le même `ClipboardManager`, dont le cache évite de les renvoyer (écho).
+//! La surveillance est suspendue tant que la session est en lecture seule.
+//! Côté hôte, un `ClipboardFilter` peut bloquer ou masquer le contenu avant envoi.
+
+use crate::clipboard::{ClipboardContent, ClipboardManager};
+use crate::clipboard_filter::{ClipboardDirection, ClipboardFilter};
+use crate::error::Result;
+use crate::network::Transport;
+use crate::protocol::ControlMessage;
+use crate::streaming::{real_session_key, SessionKeyHandle};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, warn};
+
+/// Intervalle de lecture du presse-papiers local
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Durée pendant laquelle un contenu doit rester stable avant d'être envoyé
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Sens autorisés pour la synchronisation du presse-papiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardSyncPolicy {
+    /// Aucune synchronisation (ni automatique, ni manuelle)
+    Off,
+    HostToViewer,
+    ViewerToHost,
+    #[default]
+    Bidirectional,
+}
+
+/// Rôle local dans la session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardRole {
+    Host,
+    Viewer,
+}
+
+impl ClipboardSyncPolicy {
+    /// Le pair local peut-il envoyer son presse-papiers ?
+    pub fn allows_outgoing(&self, role: ClipboardRole) -> bool {
+        matches!(
+            (self, role),
+            (ClipboardSyncPolicy::Bidirectional, _)
+                | (ClipboardSyncPolicy::HostToViewer, ClipboardRole::Host)
+                | (ClipboardSyncPolicy::ViewerToHost, ClipboardRole::Viewer)
+        )
+    }
+
+    /// Le pair local peut-il écrire un presse-papiers reçu ?
+    pub fn allows_incoming(&self, role: ClipboardRole) -> bool {
+        let peer = match role {
+            ClipboardRole::Host => ClipboardRole::Viewer,
+            ClipboardRole::Viewer => ClipboardRole::Host,
+        };
+        self.allows_outgoing(peer)
+    }
+}
+
+/// Politique partagée, modifiable en cours de session
+pub type ClipboardPolicyHandle = Arc<Mutex<ClipboardSyncPolicy>>;
+
+fn current_policy(handle: &ClipboardPolicyHandle) -> ClipboardSyncPolicy {
+    handle.lock().map(|p| *p).unwrap_or(ClipboardSyncPolicy::Off)
+}
+
+/// Anti-rebond : un contenu n'est émis qu'après `delay` sans nouveau changement
+#[derive(Debug)]
+struct Debounce {
+    delay: Duration,
+    pending: Option<(ClipboardContent, Instant)>,
+}
+
+impl Debounce {
+    fn new(delay: Duration) -> Self {
+        Self { delay, pending: None }
+    }
+
+    fn push(&mut self, content: ClipboardContent, now: Instant) {
+        self.pending = Some((content, now));
+    }
+
+    fn poll(&mut self, now: Instant) -> Option<ClipboardContent> {
+        match &self.pending {
+            Some((_, since)) if now.duration_since(*since) >= self.delay => {
+                self.pending.take().map(|(content, _)| content)
+            }
+            _ => None,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Surveillance du presse-papiers local et envoi automatique au pair
+pub struct ClipboardWatcher {
+    manager: ClipboardManager,
+    role: ClipboardRole,
+    policy: ClipboardPolicyHandle,
+    view_only: Arc<AtomicBool>,
+    poll_interval: Duration,
+    debounce: Duration,
+    /// Filtre appliqué avant envoi, avec l'ID du pair pour l'audit
+    filter: Option<(Arc<ClipboardFilter>, Option<String>)>,
+}
+
+impl ClipboardWatcher {
+    /// `manager` doit être celui qui écrit les contenus reçus (suppression de l'écho)
+    pub fn new(manager: ClipboardManager, role: ClipboardRole, policy: ClipboardPolicyHandle) -> Self {
+        Self {
+            manager,
+            role,
+            policy,
+            view_only: Arc::new(AtomicBool::new(false)),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+            filter: None,
+        }
+    }
+
+    /// Filtrer (et auditer) chaque contenu avant envoi au pair `peer_id`
+    pub fn with_filter(mut self, filter: Arc<ClipboardFilter>, peer_id: Option<String>) -> Self {
+        self.filter = Some((filter, peer_id));
+        self
+    }
+
+    /// Partager le drapeau de lecture seule de la session (suspend la surveillance)
+    pub fn with_view_only(mut self, view_only: Arc<AtomicBool>) -> Self {
+        self.view_only = view_only;
+        self
+    }
+
+    /// Ajuster le délai d'anti-rebond
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// La surveillance doit-elle envoyer en ce moment ?
+    fn is_active(&self) -> bool {
+        !self.view_only.load(Ordering::SeqCst) && current_policy(&self.policy).allows_outgoing(self.role)
+    }
+
+    /// Démarrer la tâche de fond ; chaque changement stable est passé à `send`
+    pub fn spawn<F, Fut>(self, send: F) -> JoinHandle<()>
+    where
+        F: Fn(ControlMessage) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send,
+    {
+        tokio::spawn(async move {
+            let mut debounce = Debounce::new(self.debounce);
+            // Le contenu présent au démarrage n'est pas une copie de l'utilisateur
+            let manager = self.manager.clone();
+            let _ = tokio::task::spawn_blocking(move || manager.has_changed()).await;
+
+            loop {
+                sleep(self.poll_interval).await;
+
+                // La lecture met le cache à jour même en pause : ce qui a été copié
+                // pendant la pause n'est pas envoyé à la reprise
+                let manager = self.manager.clone();
+                let changed = match tokio::task::spawn_blocking(move || manager.has_changed()).await {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        warn!("Surveillance presse-papiers interrompue: {}", e);
+                        return;
+                    }
+                };
+                let now = Instant::now();
+
+                if !self.is_active() {
+                    debounce.clear();
+                    continue;
+                }
+                if let Some(content) = changed {
+                    debounce.push(content, now);
+                    continue;
+                }
+                let Some(mut content) = debounce.poll(now) else {
+                    continue;
+                };
+                if let Some((filter, peer_id)) = &self.filter {
+                    let direction = match self.role {
+                        ClipboardRole::Host => ClipboardDirection::HostToViewer,
+                        ClipboardRole::Viewer => ClipboardDirection::ViewerToHost,
+                    };
+                    match filter.check(content, direction, peer_id.as_deref()) {
+                        Some(filtered) => content = filtered,
+                        None => continue,
+                    }
+                }
+
+                let msg = match content.to_message() {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        warn!("Presse-papiers non synchronisé: {}", e);
+                        continue;
+                    }
+                };
+                debug!(
+                    "Presse-papiers envoyé au pair ({}, {} octets)",
+                    content.mime().as_str(),
+                    content.size()
+                );
+                if let Err(e) = send(msg).await {
+                    warn!("Erreur envoi presse-papiers: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Démarrer la surveillance sur un `Transport`, trames scellées dès que la clé E2E existe
+    pub fn spawn_over(self, transport: Transport, session_key: SessionKeyHandle) -> JoinHandle<()> {
+        self.spawn(move |msg| {
+            let transport = transport.clone();
+            let session_key = session_key.clone();
+            async move {
+                let bytes = msg.to_bytes()?;
+                let payload = match real_session_key(&*session_key.lock().await) {
+                    Some(cipher) => cipher.seal(&bytes)?,
+                    None => bytes,
+                };
+                transport.send_data(&payload).await
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_directions() {
+        use ClipboardRole::{Host, Viewer};
+
+        let host_to_viewer = ClipboardSyncPolicy::HostToViewer;
+        assert!(host_to_viewer.allows_outgoing(Host) && host_to_viewer.allows_incoming(Viewer));
+        assert!(!host_to_viewer.allows_outgoing(Viewer) && !host_to_viewer.allows_incoming(Host));
+
+        let viewer_to_host = ClipboardSyncPolicy::ViewerToHost;
+        assert!(viewer_to_host.allows_outgoing(Viewer) && viewer_to_host.allows_incoming(Host));
+        assert!(!viewer_to_host.allows_outgoing(Host));
+
+        for role in [Host, Viewer] {
+            assert!(ClipboardSyncPolicy::Bidirectional.allows_outgoing(role));
+            assert!(!ClipboardSyncPolicy::Off.allows_outgoing(role));
+            assert!(!ClipboardSyncPolicy::Off.allows_incoming(role));
+        }
+    }
+
+    #[test]
+    fn test_debounce_waits_for_stable_content() {
+        let start = Instant::now();
+        let mut debounce = Debounce::new(Duration::from_millis(500));
+        let text = |s: &str| ClipboardContent::Text(s.to_string());
+
+        debounce.push(text("a"), start);
+        assert_eq!(debounce.poll(start + Duration::from_millis(300)), None);
+        // Un nouveau changement relance le délai et remplace le contenu en attente
+        debounce.push(text("ab"), start + Duration::from_millis(300));
+        assert_eq!(debounce.poll(start + Duration::from_millis(600)), None);
+        assert_eq!(debounce.poll(start + Duration::from_millis(800)), Some(text("ab")));
+        assert_eq!(debounce.poll(start + Duration::from_millis(900)), None);
+    }
+}
@@ -1,3 +1,10 @@
+use crate::clipboard_filter::ClipboardFilterConfig;
+use crate::clipboard_watcher::ClipboardSyncPolicy;
+use crate::crypto::RekeyPolicy;
+use crate::file_browser::FileBrowserConfig;
+use crate::file_transfer::FileTransferConfig;
+use crate::input_policy::{InputPolicy, KeySequence};
+use crate::touch_input::TouchMapping;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -20,6 +27,34 @@ pub struct Config {
 
     /// Security settings
     pub security_config: SecurityConfig,
+
+    /// Input policy applied to remote keyboard/mouse events (host side)
+    #[serde(default)]
+    pub input_policy: InputPolicy,
+
+    /// Mapping of remote touch/pen contacts to pointer injection (host side)
+    #[serde(default)]
+    pub touch_mapping: TouchMapping,
+
+    /// Named key sequences a remote peer may trigger despite the input policy (host side)
+    #[serde(default)]
+    pub key_sequences: Vec<KeySequence>,
+
+    /// Default clipboard sync directions for new sessions (overridable per session)
+    #[serde(default)]
+    pub clipboard_policy: ClipboardSyncPolicy,
+
+    /// Size cap and secret-detection rules for synced clipboard content (host side)
+    #[serde(default)]
+    pub clipboard_filter: ClipboardFilterConfig,
+
+    /// File transfer limits and download directory
+    #[serde(default)]
+    pub file_transfer: FileTransferConfig,
+
+    /// Host directories a remote viewer may browse and download from (host side)
+    #[serde(default)]
+    pub file_browser: FileBrowserConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +118,10 @@ pub struct SecurityConfig {
     /// Password hash for incoming connection verification (PBKDF2)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password_hash: Option<String>,
+
+    /// Automatic renewal of the E2E frame keys during long sessions
+    #[serde(default)]
+    pub rekey: RekeyPolicy,
 }
 
 impl Default for Config {
@@ -107,6 +146,13 @@ impl Default for Config {
             video_config: VideoConfig::default(),
             network_config: NetworkConfig::default(),
             security_config: SecurityConfig::default(),
+            input_policy: InputPolicy::default(),
+            touch_mapping: TouchMapping::default(),
+            key_sequences: Vec::new(),
+            clipboard_policy: ClipboardSyncPolicy::default(),
+            clipboard_filter: ClipboardFilterConfig::default(),
+            file_transfer: FileTransferConfig::default(),
+            file_browser: FileBrowserConfig::default(),
         }
     }
 }
@@ -140,6 +186,7 @@ impl Default for SecurityConfig {
             require_auth: true,
             cert_path: None,
             password_hash: None,
+            rekey: RekeyPolicy::default(),
         }
     }
 }
@@ -1,10 +1,16 @@
-use crate::error::{GhostHandError, Result};
+use crate::audit::{audit_log, AuditEvent, AuditLevel};
+use crate::error::{error_codes, GhostHandError, Result};
 use base64::prelude::*;
-use ring::aead::{Aad, BoundKey, Nonce, NonceSequence, SealingKey, UnboundKey, AES_256_GCM};
+use ring::aead::{Aad, BoundKey, LessSafeKey, Nonce, NonceSequence, SealingKey, UnboundKey, AES_256_GCM};
 use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
 use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
 use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+use zeroize::Zeroize;
 
 const NONCE_SIZE: usize = 12;
 const KEY_SIZE: usize = 32;
@@ -13,9 +19,19 @@ const KEY_SIZE: usize = 32;
 const PBKDF2_ITERATIONS: u32 = 100_000;
 
 /// Magic byte préfixant une trame chiffrée AES-256-GCM au niveau applicatif.
-/// Format d'une trame scellée : `[0xE2][nonce(12)][ciphertext+tag]`.
+/// Format d'une trame scellée : `[0xE2][nonce(12)][ciphertext+tag]`, le nonce
+/// valant `[époque(3)][sens(1)][numéro de séquence(8)]` (big-endian).
 pub const ENCRYPTED_MAGIC: u8 = 0xE2;
 
+/// Dernière époque de clé représentable dans le nonce (24 bits)
+const MAX_EPOCH: u32 = 0x00FF_FFFF;
+
+/// Trames mémorisées derrière la plus récente reçue : une trame plus ancienne
+/// est rejetée, une trame de la fenêtre n'est acceptée qu'une fois
+pub const REPLAY_WINDOW: u64 = 1024;
+/// Intervalle minimal entre deux événements d'audit de rejeu
+const REPLAY_AUDIT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Cryptography manager for E2E encryption
 pub struct CryptoManager {
     rng: SystemRandom,
@@ -310,28 +326,418 @@ pub fn session_fingerprint(session_key: &[u8]) -> String {
     )
 }
 
-/// Sceller une trame applicative : `[0xE2][nonce(12)][ciphertext+tag]`.
-pub fn seal_frame(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
-    let crypto = CryptoManager::new();
-    let enc = crypto.encrypt(key, plaintext)?;
-    let mut out = Vec::with_capacity(1 + enc.nonce.len() + enc.ciphertext.len());
-    out.push(ENCRYPTED_MAGIC);
-    out.extend_from_slice(&enc.nonce);
-    out.extend_from_slice(&enc.ciphertext);
-    Ok(out)
+/// Rôle d'un pair dans la session : chacun scelle ses trames avec sa propre
+/// clé et son propre octet de sens, les deux sens ne partagent donc jamais un nonce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRole {
+    Host,
+    Viewer,
+}
+
+impl FrameRole {
+    fn nonce_tag(self) -> u8 {
+        match self {
+            FrameRole::Host => 1,
+            FrameRole::Viewer => 2,
+        }
+    }
+
+    fn peer(self) -> Self {
+        match self {
+            FrameRole::Host => FrameRole::Viewer,
+            FrameRole::Viewer => FrameRole::Host,
+        }
+    }
+}
+
+/// Politique de renouvellement automatique des clés de trame
+/// (`security_config.rekey`). 0 désactive le critère correspondant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RekeyPolicy {
+    /// Octets scellés avec une même clé avant de la renouveler
+    pub after_bytes: u64,
+    /// Durée d'utilisation d'une même clé, en secondes
+    pub after_secs: u64,
+}
+
+impl Default for RekeyPolicy {
+    fn default() -> Self {
+        Self {
+            after_bytes: 1 << 30, // 1 Gio
+            after_secs: 3600,
+        }
+    }
+}
+
+impl RekeyPolicy {
+    fn is_due(&self, bytes: u64, since: Instant) -> bool {
+        (self.after_bytes > 0 && bytes >= self.after_bytes)
+            || (self.after_secs > 0 && since.elapsed() >= Duration::from_secs(self.after_secs))
+    }
+}
+
+/// Clé AES d'un sens de la session pour une époque donnée. L'époque 0 est
+/// dérivée de la clé de session, chaque suivante de la précédente par HKDF :
+/// une clé renouvelée ne permet pas de remonter aux précédentes.
+struct EpochKey {
+    epoch: u32,
+    secret: [u8; KEY_SIZE],
+    key: LessSafeKey,
+}
+
+impl EpochKey {
+    fn first(session_key: &[u8], role: FrameRole) -> Result<Self> {
+        let mut secret = hkdf_key(b"ghd-frame-key-v1", session_key, &[role.nonce_tag()])?;
+        let key = Self::with_secret(0, &secret);
+        secret.zeroize();
+        key
+    }
+
+    fn next(&self) -> Result<Self> {
+        let mut secret = hkdf_key(b"ghd-frame-ratchet-v1", &self.secret, b"next")?;
+        let key = Self::with_secret(self.epoch + 1, &secret);
+        secret.zeroize();
+        key
+    }
+
+    fn with_secret(epoch: u32, secret: &[u8; KEY_SIZE]) -> Result<Self> {
+        let unbound = UnboundKey::new(&AES_256_GCM, secret)
+            .map_err(|_| GhostHandError::Crypto("Clé de trame invalide".to_string()))?;
+        Ok(Self { epoch, secret: *secret, key: LessSafeKey::new(unbound) })
+    }
 }
 
-/// Ouvrir une trame scellée. Échoue si le magic byte est absent ou la trame trop courte.
-pub fn open_frame(key: &[u8], framed: &[u8]) -> Result<Vec<u8>> {
-    if framed.first() != Some(&ENCRYPTED_MAGIC) || framed.len() < 1 + NONCE_SIZE {
-        return Err(GhostHandError::Crypto(
-            "Trame non chiffrée ou trop courte".to_string(),
-        ));
+impl Drop for EpochKey {
+    fn drop(&mut self) {
+        // Le secret de l'époque permet de dériver toutes les clés suivantes
+        self.secret.zeroize();
+    }
+}
+
+/// HKDF-SHA256 vers une clé de `KEY_SIZE` octets
+fn hkdf_key(salt: &[u8], ikm: &[u8], info: &[u8]) -> Result<[u8; KEY_SIZE]> {
+    use ring::hkdf;
+
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(ikm);
+    let info: &[&[u8]] = &[info];
+    let okm = prk
+        .expand(info, hkdf::HKDF_SHA256)
+        .map_err(|_| GhostHandError::Crypto("HKDF expand failed".to_string()))?;
+    let mut key = [0u8; KEY_SIZE];
+    okm.fill(&mut key)
+        .map_err(|_| GhostHandError::Crypto("HKDF fill failed".to_string()))?;
+    Ok(key)
+}
+
+/// État d'émission : clé courante et volume scellé depuis son entrée en service
+struct SendKey {
+    key: Arc<EpochKey>,
+    bytes: u64,
+    since: Instant,
+}
+
+/// Clés de réception : la courante et la précédente, gardée pour les trames
+/// scellées juste avant que le pair ne renouvelle sa clé
+struct ReceiveKeys {
+    current: Arc<EpochKey>,
+    previous: Option<Arc<EpochKey>>,
+}
+
+/// Fenêtre glissante anti-rejeu sur les numéros de séquence reçus
+#[derive(Debug, Clone, Default)]
+pub struct ReplayWindow {
+    /// Plus grand numéro accepté
+    highest: Option<u64>,
+    /// Numéros acceptés dans la fenêtre (bit `seq % REPLAY_WINDOW`)
+    seen: [u64; (REPLAY_WINDOW / 64) as usize],
+}
+
+impl ReplayWindow {
+    /// Vérifier un numéro sans l'enregistrer ; Err(motif) si rejoué ou trop ancien
+    pub fn check(&self, seq: u64) -> std::result::Result<(), &'static str> {
+        let Some(highest) = self.highest else { return Ok(()) };
+        if seq > highest {
+            return Ok(());
+        }
+        if highest - seq >= REPLAY_WINDOW {
+            return Err("too_old");
+        }
+        if self.is_seen(seq) {
+            return Err("duplicate");
+        }
+        Ok(())
+    }
+
+    /// Enregistrer un numéro vérifié (après authentification de la trame)
+    pub fn accept(&mut self, seq: u64) {
+        match self.highest {
+            Some(highest) if seq <= highest => {}
+            Some(highest) if seq - highest < REPLAY_WINDOW => {
+                // La fenêtre avance : libérer les positions des numéros sautés
+                for skipped in highest + 1..seq {
+                    self.set_seen(skipped, false);
+                }
+                self.highest = Some(seq);
+            }
+            _ => {
+                self.seen = Default::default();
+                self.highest = Some(seq);
+            }
+        }
+        self.set_seen(seq, true);
+    }
+
+    pub fn highest(&self) -> Option<u64> {
+        self.highest
+    }
+
+    fn is_seen(&self, seq: u64) -> bool {
+        let bit = seq % REPLAY_WINDOW;
+        self.seen[(bit / 64) as usize] & (1 << (bit % 64)) != 0
+    }
+
+    fn set_seen(&mut self, seq: u64, seen: bool) {
+        let bit = seq % REPLAY_WINDOW;
+        let word = &mut self.seen[(bit / 64) as usize];
+        if seen {
+            *word |= 1 << (bit % 64);
+        } else {
+            *word &= !(1 << (bit % 64));
+        }
+    }
+}
+
+/// Limitation du débit des événements d'audit de rejeu
+#[derive(Default)]
+struct ReplayAudit {
+    last: Option<Instant>,
+    suppressed: u64,
+}
+
+/// Chiffrement des trames d'une session E2E (AES-256-GCM). Chaque sens a sa
+/// propre clé, renouvelée par ratchet HKDF selon la `RekeyPolicy` ; l'époque de
+/// la clé et un numéro de séquence continu sont portés par le nonce. À la
+/// réception, une trame du mauvais sens, déjà vue, sortie de la fenêtre
+/// (`REPLAY_WINDOW`) ou d'une époque révolue est rejetée et auditée. Partagé
+/// par toutes les tâches qui émettent ou reçoivent.
+pub struct FrameCipher {
+    /// Empreinte de la clé de session ; la clé elle-même n'est pas conservée,
+    /// elle permettrait de re-dériver toutes les clés d'époque
+    fingerprint: String,
+    role: FrameRole,
+    rekey: RekeyPolicy,
+    send: Mutex<SendKey>,
+    receive: Mutex<ReceiveKeys>,
+    /// Prochain numéro de séquence à émettre (continu d'une époque à l'autre)
+    next_seq: AtomicU64,
+    window: Mutex<ReplayWindow>,
+    audit: Mutex<ReplayAudit>,
+}
+
+impl FrameCipher {
+    pub fn new(mut session_key: Vec<u8>, role: FrameRole) -> Result<Self> {
+        if session_key.len() != KEY_SIZE {
+            session_key.zeroize();
+            return Err(GhostHandError::Crypto(format!(
+                "Clé de session invalide ({} bytes)",
+                session_key.len()
+            )));
+        }
+        let send = EpochKey::first(&session_key, role);
+        let receive = EpochKey::first(&session_key, role.peer());
+        let fingerprint = session_fingerprint(&session_key);
+        session_key.zeroize();
+        let (send, receive) = (send?, receive?);
+        Ok(Self {
+            role,
+            rekey: RekeyPolicy::default(),
+            send: Mutex::new(SendKey { key: Arc::new(send), bytes: 0, since: Instant::now() }),
+            receive: Mutex::new(ReceiveKeys { current: Arc::new(receive), previous: None }),
+            fingerprint,
+            next_seq: AtomicU64::new(0),
+            window: Mutex::new(ReplayWindow::default()),
+            audit: Mutex::new(ReplayAudit::default()),
+        })
+    }
+
+    /// Remplacer la politique de renouvellement par défaut
+    pub fn with_rekey(mut self, policy: RekeyPolicy) -> Self {
+        self.rekey = policy;
+        self
+    }
+
+    /// Empreinte de la clé de session issue du handshake (`session_fingerprint`).
+    /// Elle ne change pas lors des renouvellements : l'empreinte authentifie
+    /// l'échange de clés, que le ratchet prolonge sans nouvel échange.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn role(&self) -> FrameRole {
+        self.role
+    }
+
+    /// Époque de la clé d'émission courante
+    pub fn send_epoch(&self) -> u32 {
+        self.send.lock().map(|send| send.key.epoch).unwrap_or_default()
+    }
+
+    /// Sceller une trame : `[0xE2][nonce(12)][ciphertext+tag]`
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let key = {
+            let mut send = self.send.lock().map_err(|_| GhostHandError::Internal("Clé d'émission inaccessible".into()))?;
+            if send.key.epoch < MAX_EPOCH && self.rekey.is_due(send.bytes, send.since) {
+                let next = send.key.next()?;
+                info!("Clé d'émission renouvelée (époque {})", next.epoch);
+                audit_log(AuditLevel::Info, AuditEvent::SessionRekeyed {
+                    direction: "send".to_string(),
+                    epoch: next.epoch,
+                });
+                *send = SendKey { key: Arc::new(next), bytes: 0, since: Instant::now() };
+            }
+            send.bytes = send.bytes.saturating_add(plaintext.len() as u64);
+            send.key.clone()
+        };
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        if seq == u64::MAX {
+            return Err(GhostHandError::crypto_with_code(
+                error_codes::CRYPTO_ENCRYPTION_FAILED,
+                "Numéros de séquence épuisés pour cette clé",
+            ));
+        }
+        let nonce = Self::nonce(key.epoch, self.role, seq);
+        let mut out = Vec::with_capacity(1 + NONCE_SIZE + plaintext.len() + AES_256_GCM.tag_len());
+        out.push(ENCRYPTED_MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(plaintext);
+        let tag = key.key
+            .seal_in_place_separate_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut out[1 + NONCE_SIZE..])
+            .map_err(|_| GhostHandError::crypto_with_code(error_codes::CRYPTO_ENCRYPTION_FAILED, "Chiffrement échoué"))?;
+        out.extend_from_slice(tag.as_ref());
+        Ok(out)
+    }
+
+    /// Ouvrir une trame scellée par le pair. Échoue si le magic byte est absent,
+    /// la trame trop courte ou altérée, ou si elle est rejouée (erreur `E6005`).
+    /// La première trame d'une nouvelle époque fait avancer la clé de réception ;
+    /// celles encore en vol de l'époque précédente restent lisibles.
+    pub fn open(&self, framed: &[u8]) -> Result<Vec<u8>> {
+        if framed.first() != Some(&ENCRYPTED_MAGIC) || framed.len() < 1 + NONCE_SIZE {
+            return Err(GhostHandError::Crypto(
+                "Trame non chiffrée ou trop courte".to_string(),
+            ));
+        }
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce.copy_from_slice(&framed[1..1 + NONCE_SIZE]);
+        let epoch = u32::from_be_bytes([0, nonce[0], nonce[1], nonce[2]]);
+        let mut seq_bytes = [0u8; 8];
+        seq_bytes.copy_from_slice(&nonce[4..]);
+        let seq = u64::from_be_bytes(seq_bytes);
+
+        // Une trame renvoyée dans le mauvais sens (réflexion de nos propres
+        // trames par le relais) porte notre octet de sens, pas celui du pair
+        if nonce[3] != self.role.peer().nonce_tag() {
+            return Err(self.reject(seq, "wrong_direction"));
+        }
+        // Vérification bon marché avant le déchiffrement, enregistrement après :
+        // une trame forgée ne fait pas avancer la fenêtre
+        if let Err(reason) = self.window.lock().map_err(|_| "window").and_then(|w| w.check(seq)) {
+            return Err(self.reject(seq, reason));
+        }
+
+        let (key, advanced) = {
+            let keys = self.receive.lock().map_err(|_| GhostHandError::Internal("Clés de réception inaccessibles".into()))?;
+            if epoch == keys.current.epoch {
+                (keys.current.clone(), false)
+            } else if epoch == keys.current.epoch + 1 && epoch <= MAX_EPOCH {
+                (Arc::new(keys.current.next()?), true)
+            } else if let Some(previous) = keys.previous.as_ref().filter(|k| k.epoch == epoch) {
+                (previous.clone(), false)
+            } else {
+                drop(keys);
+                return Err(self.reject(seq, "stale_epoch"));
+            }
+        };
+
+        let mut in_out = framed[1 + NONCE_SIZE..].to_vec();
+        let len = key.key
+            .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+            .map_err(|_| GhostHandError::crypto_with_code(error_codes::CRYPTO_DECRYPTION_FAILED, "Trame altérée ou clé différente"))?
+            .len();
+        in_out.truncate(len);
+
+        // La nouvelle clé n'est adoptée qu'une fois une trame authentifiée avec
+        // elle ; une autre tâche a pu l'adopter entre-temps
+        if advanced {
+            let mut keys = self.receive.lock().map_err(|_| GhostHandError::Internal("Clés de réception inaccessibles".into()))?;
+            if keys.current.epoch + 1 == key.epoch {
+                info!("Clé de réception renouvelée (époque {})", key.epoch);
+                audit_log(AuditLevel::Info, AuditEvent::SessionRekeyed {
+                    direction: "receive".to_string(),
+                    epoch: key.epoch,
+                });
+                keys.previous = Some(std::mem::replace(&mut keys.current, key));
+            }
+        }
+
+        // Re-vérifier sous le verrou : deux copies de la même trame peuvent être
+        // déchiffrées en parallèle
+        let mut window = self.window.lock().map_err(|_| GhostHandError::Internal("Fenêtre anti-rejeu inaccessible".into()))?;
+        if let Err(reason) = window.check(seq) {
+            drop(window);
+            return Err(self.reject(seq, reason));
+        }
+        window.accept(seq);
+        Ok(in_out)
+    }
+
+    fn nonce(epoch: u32, role: FrameRole, seq: u64) -> [u8; NONCE_SIZE] {
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce[..3].copy_from_slice(&epoch.to_be_bytes()[1..]);
+        nonce[3] = role.nonce_tag();
+        nonce[4..].copy_from_slice(&seq.to_be_bytes());
+        nonce
+    }
+
+    /// Auditer un rejet (au plus un événement par seconde) et construire l'erreur
+    fn reject(&self, seq: u64, reason: &str) -> GhostHandError {
+        let highest = self.window.lock().ok().and_then(|w| w.highest());
+        if let Ok(mut audit) = self.audit.lock() {
+            if audit.last.is_some_and(|last| last.elapsed() < REPLAY_AUDIT_INTERVAL) {
+                audit.suppressed += 1;
+            } else {
+                audit_log(AuditLevel::Warning, AuditEvent::FrameReplayRejected {
+                    sequence: seq,
+                    highest,
+                    reason: reason.to_string(),
+                    suppressed: std::mem::take(&mut audit.suppressed),
+                });
+                audit.last = Some(Instant::now());
+            }
+        }
+        GhostHandError::crypto_with_code(
+            error_codes::CRYPTO_REPLAY_DETECTED,
+            format!("Trame {} rejetée ({})", seq, reason),
+        )
+    }
+}
+
+/// Clé E2E d'une session : en cours de négociation (clé privée X25519
+/// éphémère de l'hôte, en attente du `KeyExchangeAccept`) ou établie
+pub enum SessionKeyState {
+    Pending(Vec<u8>),
+    Active(Arc<FrameCipher>),
+}
+
+impl SessionKeyState {
+    /// Chiffrement actif, None pendant le handshake
+    pub fn cipher(&self) -> Option<Arc<FrameCipher>> {
+        match self {
+            SessionKeyState::Active(cipher) => Some(cipher.clone()),
+            SessionKeyState::Pending(_) => None,
+        }
     }
-    let nonce = framed[1..1 + NONCE_SIZE].to_vec();
-    let ciphertext = framed[1 + NONCE_SIZE..].to_vec();
-    let crypto = CryptoManager::new();
-    crypto.decrypt(key, &EncryptedData { nonce, ciphertext })
 }
 
 /// Encrypted data container
@@ -550,17 +956,89 @@ mod tests {
     fn test_seal_open_frame_roundtrip() -> Result<()> {
         let crypto = CryptoManager::new();
         let key = crypto.generate_key()?;
+        let host = FrameCipher::new(key.clone(), FrameRole::Host)?;
+        let viewer = FrameCipher::new(key, FrameRole::Viewer)?;
         let plaintext = b"payload de controle sensible";
 
-        let sealed = seal_frame(&key, plaintext)?;
+        let sealed = host.seal(plaintext)?;
         assert_eq!(sealed[0], ENCRYPTED_MAGIC);
         assert_ne!(&sealed[1..], plaintext); // effectivement chiffré
 
-        let opened = open_frame(&key, &sealed)?;
+        let opened = viewer.open(&sealed)?;
         assert_eq!(opened.as_slice(), plaintext);
 
         // Une trame en clair (sans magic) doit être rejetée
-        assert!(open_frame(&key, plaintext).is_err());
+        assert!(viewer.open(plaintext).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_replayed_and_reflected_frames_are_rejected() -> Result<()> {
+        let key = CryptoManager::new().generate_key()?;
+        let host = FrameCipher::new(key.clone(), FrameRole::Host)?;
+        let viewer = FrameCipher::new(key, FrameRole::Viewer)?;
+        let replayed = |e: GhostHandError| {
+            matches!(e, GhostHandError::CryptoWithCode { ref code, .. } if code == error_codes::CRYPTO_REPLAY_DETECTED)
+        };
+
+        let frames: Vec<Vec<u8>> = (0..3).map(|i| viewer.seal(&[i])).collect::<Result<_>>()?;
+        // Désordre toléré, doublon refusé
+        assert_eq!(host.open(&frames[1])?, vec![1]);
+        assert_eq!(host.open(&frames[0])?, vec![0]);
+        assert!(replayed(host.open(&frames[1]).unwrap_err()));
+        assert_eq!(host.open(&frames[2])?, vec![2]);
+
+        // Trame renvoyée à son émetteur par le relais
+        let own = host.seal(b"clic")?;
+        assert!(replayed(host.open(&own).unwrap_err()));
+        assert_eq!(viewer.open(&own)?, b"clic".to_vec());
+
+        // Sortie de la fenêtre
+        for _ in 0..REPLAY_WINDOW {
+            host.open(&viewer.seal(b"x")?)?;
+        }
+        assert!(replayed(host.open(&frames[2]).unwrap_err()));
+
+        // Une trame altérée n'avance pas la fenêtre
+        let mut forged = viewer.seal(b"y")?;
+        let last = forged.len() - 1;
+        forged[last] ^= 1;
+        let highest = host.window.lock().unwrap().highest();
+        assert!(host.open(&forged).is_err());
+        assert_eq!(host.window.lock().unwrap().highest(), highest);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rekey_keeps_in_flight_frames_and_drops_stale_epochs() -> Result<()> {
+        let key = CryptoManager::new().generate_key()?;
+        let policy = RekeyPolicy { after_bytes: 16, after_secs: 0 };
+        let host = FrameCipher::new(key.clone(), FrameRole::Host)?.with_rekey(policy);
+        let viewer = FrameCipher::new(key.clone(), FrameRole::Viewer)?.with_rekey(policy);
+
+        // 10 octets par trame : époques 0, 0, 1, 1, 2
+        let frames: Vec<Vec<u8>> = (0..5).map(|i| viewer.seal(&[i; 10])).collect::<Result<_>>()?;
+        assert_eq!(viewer.send_epoch(), 2);
+        assert_ne!(frames[0][1..5], frames[2][1..5]);
+
+        // La nouvelle époque arrive avant les trames encore en vol de l'ancienne
+        assert_eq!(host.open(&frames[2])?, vec![2; 10]);
+        assert_eq!(host.open(&frames[0])?, vec![0; 10]);
+        assert_eq!(host.open(&frames[1])?, vec![1; 10]);
+        assert_eq!(host.open(&frames[3])?, vec![3; 10]);
+        assert_eq!(host.open(&frames[4])?, vec![4; 10]);
+        assert!(host.open(&frames[2]).is_err());
+
+        // Une trame neuve scellée avec la clé de l'époque 0 n'est plus acceptée
+        let stale = FrameCipher::new(key.clone(), FrameRole::Viewer)?;
+        for _ in 0..frames.len() {
+            stale.seal(b"")?;
+        }
+        let err = host.open(&stale.seal(b"vieux")?).unwrap_err();
+        assert!(matches!(err, GhostHandError::CryptoWithCode { ref code, .. } if code == error_codes::CRYPTO_REPLAY_DETECTED));
+
+        // L'empreinte de session ne dépend pas des renouvellements
+        assert_eq!(viewer.fingerprint(), session_fingerprint(&key));
         Ok(())
     }
 
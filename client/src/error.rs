@@ -34,10 +34,16 @@ pub mod error_codes {
     pub const CRYPTO_ENCRYPTION_FAILED: &str = "E6002";
     pub const CRYPTO_DECRYPTION_FAILED: &str = "E6003";
     pub const CRYPTO_KEY_EXCHANGE_FAILED: &str = "E6004";
+    pub const CRYPTO_REPLAY_DETECTED: &str = "E6005";
 
     // Erreurs configuration (7xxx)
     pub const CONFIG_INVALID: &str = "E7001";
     pub const CONFIG_LOAD_FAILED: &str = "E7002";
+
+    // Erreurs transfert de fichiers (8xxx)
+    pub const FILE_INTEGRITY_MISMATCH: &str = "E8001";
+    pub const FILE_ACCESS_DENIED: &str = "E8002";
+    pub const FILE_TRANSFER_CANCELLED: &str = "E8003";
 }
 
 #[derive(Error, Debug)]
@@ -70,6 +76,28 @@ pub enum GhostHandError {
     #[error("Rate limit atteint: {0}")]
     RateLimit(String),
 
+    /// Données reçues différentes de celles envoyées (SHA-256 divergent)
+    #[error("[{code}] Intégrité compromise pour {name}: SHA-256 attendu {expected}, obtenu {actual}", code = error_codes::FILE_INTEGRITY_MISMATCH)]
+    IntegrityMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// Accès distant hors des racines autorisées ou interdit par leur permission
+    #[error("[{code}] Accès refusé à {path}: {reason}", code = error_codes::FILE_ACCESS_DENIED)]
+    AccessDenied {
+        path: String,
+        reason: String,
+    },
+
+    /// Transfert refusé par le destinataire ou annulé par l'un des pairs
+    #[error("[{code}] Transfert {id} annulé: {reason}", code = error_codes::FILE_TRANSFER_CANCELLED)]
+    TransferCancelled {
+        id: String,
+        reason: String,
+    },
+
     #[error("Erreur interne: {0}")]
     Internal(String),
 
@@ -159,3 +187,16 @@ impl GhostHandError {
 }
 
 pub type Result<T> = std::result::Result<T, GhostHandError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_errors_use_code_constants() {
+        let err = GhostHandError::TransferCancelled { id: "ft-1".into(), reason: "refusé".into() };
+        assert!(err.to_string().starts_with(&format!("[{}]", error_codes::FILE_TRANSFER_CANCELLED)));
+        let err = GhostHandError::AccessDenied { path: "/etc".into(), reason: "hors racine".into() };
+        assert!(err.to_string().starts_with(&format!("[{}]", error_codes::FILE_ACCESS_DENIED)));
+    }
+}
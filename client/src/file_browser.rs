@@ -0,0 +1,446 @@
+//! Navigation distante dans les fichiers de l'hôte
+//!
+//! Le viewer liste des dossiers (`ListDirectory`) et télécharge des fichiers
+//! (`ReadFile`) de l'hôte, uniquement sous des racines déclarées dans la
+//! configuration de l'hôte. Chaque racine est en lecture seule ou en
+//! lecture-écriture (`MakeDirectory`, `DeletePath`). Les chemins reçus sont
+//! relatifs et revalidés ; aucun lien symbolique n'est suivi et le chemin
+//! canonique doit rester sous la racine canonique. Chaque accès, autorisé ou
+//! refusé, est audité.
+
+use crate::audit::{audit_log, AuditEvent, AuditLevel};
+use crate::error::{GhostHandError, Result};
+use crate::folder_transfer::relative_path;
+use crate::protocol::{ControlMessage, FileBrowserResult, RemoteEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// Nombre max d'entrées renvoyées pour un dossier
+pub const MAX_LISTING_ENTRIES: usize = 1000;
+
+/// Permission accordée au viewer sur une racine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowseAccess {
+    #[default]
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Dossier de l'hôte exposé au viewer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrowseRoot {
+    /// Nom présenté au viewer (le chemin local n'est jamais envoyé)
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub access: BrowseAccess,
+}
+
+/// Configuration du navigateur distant (section `file_browser` de la config)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileBrowserConfig {
+    /// Désactivé par défaut : toute requête est refusée
+    pub enabled: bool,
+    pub roots: Vec<BrowseRoot>,
+}
+
+/// Racine résolue au démarrage
+#[derive(Debug, Clone)]
+struct Root {
+    name: String,
+    /// Chemin canonique (liens résolus une fois pour toutes)
+    path: PathBuf,
+    access: BrowseAccess,
+}
+
+/// Résultat d'une requête traitée par l'hôte
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrowseOutcome {
+    Listing { entries: Vec<RemoteEntry>, truncated: bool },
+    /// Fichier à envoyer au viewer (chemin canonique sous la racine)
+    Read(PathBuf),
+    Done,
+}
+
+/// Navigation confinée aux racines configurées (côté hôte)
+pub struct FileBrowser {
+    enabled: bool,
+    roots: Vec<Root>,
+    max_file_size: u64,
+    peer_id: Option<String>,
+    /// Session en lecture seule : les écritures sont refusées
+    view_only: Arc<AtomicBool>,
+}
+
+fn denied(path: &str, reason: impl Into<String>) -> GhostHandError {
+    GhostHandError::AccessDenied { path: path.to_string(), reason: reason.into() }
+}
+
+fn mtime_secs(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata.modified().ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+impl FileBrowser {
+    /// Canonicaliser les racines ; une racine absente ou en double est une erreur
+    pub fn new(config: &FileBrowserConfig) -> Result<Self> {
+        let mut roots: Vec<Root> = Vec::new();
+        for root in &config.roots {
+            if relative_path(&root.name).is_err() || root.name.contains('/') {
+                return Err(GhostHandError::Config(format!("Nom de racine invalide: {:?}", root.name)));
+            }
+            if roots.iter().any(|r| r.name == root.name) {
+                return Err(GhostHandError::Config(format!("Racine en double: {}", root.name)));
+            }
+            let path = root.path.canonicalize().map_err(|e| {
+                GhostHandError::Config(format!("Racine '{}' inaccessible ({}): {}", root.name, root.path.display(), e))
+            })?;
+            if !path.is_dir() {
+                return Err(GhostHandError::Config(format!("Racine '{}' n'est pas un dossier", root.name)));
+            }
+            roots.push(Root { name: root.name.clone(), path, access: root.access });
+        }
+        Ok(Self {
+            enabled: config.enabled,
+            roots,
+            max_file_size: u64::MAX,
+            peer_id: None,
+            view_only: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Navigateur refusant toute requête (configuration absente ou invalide)
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            roots: Vec::new(),
+            max_file_size: 0,
+            peer_id: None,
+            view_only: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Pair connecté, pour l'audit
+    pub fn with_peer(mut self, peer_id: Option<String>) -> Self {
+        self.peer_id = peer_id;
+        self
+    }
+
+    /// Partager le drapeau de lecture seule de la session (refuse les écritures)
+    pub fn with_view_only(mut self, view_only: Arc<AtomicBool>) -> Self {
+        self.view_only = view_only;
+        self
+    }
+
+    /// Taille max d'un fichier téléchargé
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Traiter une requête de navigation ; None si `msg` n'en est pas une
+    pub fn handle(&self, msg: &ControlMessage) -> Option<(u64, Result<BrowseOutcome>)> {
+        let (request_id, operation, root, path) = match msg {
+            ControlMessage::ListDirectory { request_id, root, path } => (*request_id, "list", root, path),
+            ControlMessage::ReadFile { request_id, root, path } => (*request_id, "read", root, path),
+            ControlMessage::MakeDirectory { request_id, root, path } => (*request_id, "mkdir", root, path),
+            ControlMessage::DeletePath { request_id, root, path } => (*request_id, "delete", root, path),
+            _ => return None,
+        };
+        let result = self.execute(operation, root, path);
+        self.audit(operation, root, path, &result);
+        Some((request_id, result))
+    }
+
+    fn execute(&self, operation: &str, root: &str, path: &str) -> Result<BrowseOutcome> {
+        if !self.enabled {
+            return Err(denied(path, "navigation distante désactivée"));
+        }
+        if operation == "list" && root.is_empty() {
+            return Ok(self.list_roots());
+        }
+        let root = self.roots.iter()
+            .find(|r| r.name == root)
+            .ok_or_else(|| denied(path, format!("racine inconnue: {:?}", root)))?;
+
+        match operation {
+            "list" => self.list(root, &self.resolve(root, path)?),
+            "read" => {
+                let file = self.resolve(root, path)?;
+                let metadata = std::fs::metadata(&file)?;
+                if !metadata.is_file() {
+                    return Err(GhostHandError::Validation(format!("{} n'est pas un fichier", path)));
+                }
+                if metadata.len() > self.max_file_size {
+                    return Err(GhostHandError::Validation(format!(
+                        "Fichier trop gros: {} bytes (max: {} bytes)", metadata.len(), self.max_file_size
+                    )));
+                }
+                Ok(BrowseOutcome::Read(file))
+            }
+            _ => {
+                self.check_writable(root, path)?;
+                if path.is_empty() {
+                    return Err(denied(path, "la racine elle-même n'est pas modifiable"));
+                }
+                if operation == "mkdir" {
+                    std::fs::create_dir(self.resolve_new(root, path)?)?;
+                } else {
+                    let target = self.resolve(root, path)?;
+                    if std::fs::symlink_metadata(&target)?.is_dir() {
+                        // Dossiers vides uniquement : pas de suppression récursive à distance
+                        std::fs::remove_dir(&target)?;
+                    } else {
+                        std::fs::remove_file(&target)?;
+                    }
+                }
+                Ok(BrowseOutcome::Done)
+            }
+        }
+    }
+
+    fn check_writable(&self, root: &Root, path: &str) -> Result<()> {
+        if root.access != BrowseAccess::ReadWrite {
+            return Err(denied(path, format!("racine '{}' en lecture seule", root.name)));
+        }
+        if self.view_only.load(Ordering::SeqCst) {
+            return Err(denied(path, "session en lecture seule"));
+        }
+        Ok(())
+    }
+
+    fn list_roots(&self) -> BrowseOutcome {
+        let entries = self.roots.iter()
+            .map(|r| RemoteEntry {
+                name: r.name.clone(),
+                dir: true,
+                size: 0,
+                mtime: None,
+                writable: r.access == BrowseAccess::ReadWrite,
+            })
+            .collect();
+        BrowseOutcome::Listing { entries, truncated: false }
+    }
+
+    /// Lister un dossier résolu ; liens symboliques et fichiers spéciaux ignorés
+    fn list(&self, root: &Root, dir: &Path) -> Result<BrowseOutcome> {
+        let writable = root.access == BrowseAccess::ReadWrite;
+        let mut entries = Vec::new();
+        for child in std::fs::read_dir(dir)?.flatten() {
+            let Some(name) = child.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            // Un nom qui ne pourrait pas être redemandé n'est pas listé
+            if relative_path(&name).is_err() {
+                continue;
+            }
+            let Ok(metadata) = std::fs::symlink_metadata(child.path()) else {
+                continue;
+            };
+            let file_type = metadata.file_type();
+            if !file_type.is_dir() && !file_type.is_file() {
+                continue;
+            }
+            entries.push(RemoteEntry {
+                name,
+                dir: file_type.is_dir(),
+                size: if file_type.is_file() { metadata.len() } else { 0 },
+                mtime: mtime_secs(&metadata),
+                writable: writable && !metadata.permissions().readonly(),
+            });
+        }
+        entries.sort_by(|a, b| b.dir.cmp(&a.dir).then_with(|| a.name.cmp(&b.name)));
+        let truncated = entries.len() > MAX_LISTING_ENTRIES;
+        entries.truncate(MAX_LISTING_ENTRIES);
+        Ok(BrowseOutcome::Listing { entries, truncated })
+    }
+
+    /// Résoudre un chemin existant sous la racine, sans suivre aucun lien
+    fn resolve(&self, root: &Root, path: &str) -> Result<PathBuf> {
+        if path.is_empty() {
+            return Ok(root.path.clone());
+        }
+        let relative = relative_path(path).map_err(|_| denied(path, "chemin invalide"))?;
+        let mut current = root.path.clone();
+        for component in relative.components() {
+            let Component::Normal(part) = component else {
+                return Err(denied(path, "chemin invalide"));
+            };
+            current.push(part);
+            if std::fs::symlink_metadata(&current)?.file_type().is_symlink() {
+                return Err(denied(path, "lien symbolique"));
+            }
+        }
+        // Défense en profondeur (points de jonction, montages...)
+        let canonical = current.canonicalize()?;
+        if !canonical.starts_with(&root.path) {
+            return Err(denied(path, "hors de la racine"));
+        }
+        Ok(canonical)
+    }
+
+    /// Résoudre un chemin à créer : le parent doit exister sous la racine
+    fn resolve_new(&self, root: &Root, path: &str) -> Result<PathBuf> {
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        relative_path(name).map_err(|_| denied(path, "chemin invalide"))?;
+        let target = self.resolve(root, parent)?.join(name);
+        match std::fs::symlink_metadata(&target) {
+            Ok(_) => Err(GhostHandError::Validation(format!("{} existe déjà", path))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(target),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn audit(&self, operation: &str, root: &str, path: &str, result: &Result<BrowseOutcome>) {
+        let (level, allowed, reason) = match result {
+            Ok(_) => (AuditLevel::Info, true, None),
+            Err(e @ GhostHandError::AccessDenied { .. }) => {
+                warn!("Navigation distante refusée ({} {}/{}): {}", operation, root, path, e);
+                (AuditLevel::Security, false, Some(e.to_string()))
+            }
+            Err(e) => (AuditLevel::Warning, true, Some(e.to_string())),
+        };
+        audit_log(level, AuditEvent::FileBrowserAccess {
+            operation: operation.to_string(),
+            root: root.to_string(),
+            path: path.to_string(),
+            allowed,
+            reason,
+            peer_id: self.peer_id.clone(),
+        });
+    }
+}
+
+/// Requêtes de navigation en attente de réponse (côté viewer), partagées entre
+/// les commandes et la boucle de réception des messages
+#[derive(Clone, Default)]
+pub struct BrowseRequests {
+    next_id: Arc<AtomicU64>,
+    pending: Arc<Mutex<HashMap<u64, oneshot::Sender<FileBrowserResult>>>>,
+}
+
+impl BrowseRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Réserver un identifiant de requête et attendre sa réponse
+    pub fn register(&self) -> (u64, oneshot::Receiver<FileBrowserResult>) {
+        let request_id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(request_id, tx);
+        }
+        (request_id, rx)
+    }
+
+    /// Transmettre un `FileBrowserResponse` ; false si la requête est inconnue
+    pub fn resolve(&self, request_id: u64, result: FileBrowserResult) -> bool {
+        let tx = self.pending.lock().ok().and_then(|mut p| p.remove(&request_id));
+        tx.is_some_and(|tx| tx.send(result).is_ok())
+    }
+
+    /// Abandonner une requête (délai dépassé, envoi impossible)
+    pub fn cancel(&self, request_id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&request_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser(root: &Path, access: BrowseAccess) -> FileBrowser {
+        FileBrowser::new(&FileBrowserConfig {
+            enabled: true,
+            roots: vec![BrowseRoot { name: "docs".into(), path: root.to_path_buf(), access }],
+        })
+        .unwrap()
+    }
+
+    fn run(browser: &FileBrowser, msg: ControlMessage) -> Result<BrowseOutcome> {
+        browser.handle(&msg).unwrap().1
+    }
+
+    fn list(path: &str) -> ControlMessage {
+        ControlMessage::ListDirectory { request_id: 1, root: "docs".into(), path: path.into() }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_access_confined_to_root() {
+        let base = std::env::temp_dir().join(format!("ghd-browse-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let root = base.join("root");
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/a.txt"), b"hello").unwrap();
+        std::fs::write(base.join("secret.txt"), b"secret").unwrap();
+        std::os::unix::fs::symlink(base.join("secret.txt"), root.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(&base, root.join("escape")).unwrap();
+
+        let ro = browser(&root, BrowseAccess::ReadOnly);
+        let Ok(BrowseOutcome::Listing { entries, .. }) = run(&ro, list("")) else { panic!("listing") };
+        let names: Vec<_> = entries.iter().map(|e| (e.name.as_str(), e.dir)).collect();
+        assert_eq!(names, vec![("sub", true)], "liens symboliques non listés");
+
+        let read = |path: &str| ControlMessage::ReadFile { request_id: 2, root: "docs".into(), path: path.into() };
+        assert!(matches!(run(&ro, read("sub/a.txt")), Ok(BrowseOutcome::Read(_))));
+        for bad in ["link.txt", "escape/secret.txt", "../secret.txt", "/etc/passwd", "sub/../../secret.txt"] {
+            assert!(
+                matches!(run(&ro, read(bad)), Err(GhostHandError::AccessDenied { .. })),
+                "{:?} accepté", bad
+            );
+        }
+        let unknown = ControlMessage::ReadFile { request_id: 3, root: "other".into(), path: "a".into() };
+        assert!(matches!(run(&ro, unknown), Err(GhostHandError::AccessDenied { .. })));
+
+        let mkdir = ControlMessage::MakeDirectory { request_id: 4, root: "docs".into(), path: "sub/new".into() };
+        assert!(matches!(run(&ro, mkdir.clone()), Err(GhostHandError::AccessDenied { .. })));
+
+        let view_only = Arc::new(AtomicBool::new(true));
+        let rw = browser(&root, BrowseAccess::ReadWrite).with_view_only(view_only.clone());
+        assert!(matches!(run(&rw, mkdir.clone()), Err(GhostHandError::AccessDenied { .. })));
+        view_only.store(false, Ordering::SeqCst);
+        assert_eq!(run(&rw, mkdir).unwrap(), BrowseOutcome::Done);
+        assert!(root.join("sub/new").is_dir());
+
+        let delete = |path: &str| ControlMessage::DeletePath { request_id: 5, root: "docs".into(), path: path.into() };
+        assert!(run(&rw, delete("sub")).is_err(), "dossier non vide");
+        assert!(matches!(run(&rw, delete("link.txt")), Err(GhostHandError::AccessDenied { .. })));
+        assert_eq!(run(&rw, delete("sub/a.txt")).unwrap(), BrowseOutcome::Done);
+        assert!(base.join("secret.txt").exists());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_disabled_and_pending_requests() {
+        let root = std::env::temp_dir();
+        let disabled = FileBrowser::new(&FileBrowserConfig {
+            enabled: false,
+            roots: vec![BrowseRoot { name: "tmp".into(), path: root, access: BrowseAccess::ReadOnly }],
+        })
+        .unwrap();
+        let roots = ControlMessage::ListDirectory { request_id: 1, root: String::new(), path: String::new() };
+        assert!(matches!(run(&disabled, roots), Err(GhostHandError::AccessDenied { .. })));
+        assert!(disabled.handle(&ControlMessage::Ping).is_none());
+
+        let requests = BrowseRequests::new();
+        let (first, mut rx) = requests.register();
+        let (second, _) = requests.register();
+        assert_ne!(first, second);
+        assert!(requests.resolve(first, FileBrowserResult::Done));
+        assert_eq!(rx.try_recv().unwrap(), FileBrowserResult::Done);
+        assert!(!requests.resolve(first, FileBrowserResult::Done), "réponse en double ignorée");
+    }
+}
@@ -0,0 +1,487 @@
+//! Envoi de fichiers avec contrôle de flux
+//!
+//! Les chunks sont lus sur disque au fil de l'envoi : le fichier n'est jamais
+//! chargé en mémoire. Le destinataire acquitte chaque chunk écrit
+//! (`FileTransferAck`) et au plus `window` chunks restent sans acquittement.
+//! L'émetteur se met aussi en pause tant que le tampon du transport est plein.
+//! La progression (octets envoyés, acquittés, débit) est remontée à l'appelant.
+//! Aucun chunk ne part avant que le destinataire ait accepté le transfert
+//! (`FileTransferResponse`), et un `FileTransferCancel` interrompt l'envoi.
+//! Rattaché à la file des transferts, l'envoi y attend sa place avant chaque chunk.
+
+use crate::error::{error_codes, GhostHandError, Result};
+use crate::file_transfer::{chunk_ranges, read_chunk, sha256_hex, OutgoingTransfer};
+use crate::network::Transport;
+use crate::protocol::ControlMessage;
+use crate::streaming::{real_session_key, SessionKeyHandle};
+use crate::transfer_queue::TransferQueue;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::watch;
+use tokio::time::{sleep, timeout, Duration};
+use tracing::{debug, info};
+
+/// Chunks sans acquittement au maximum (16 × 48 KB ≈ 768 KB en vol)
+pub const DEFAULT_WINDOW: usize = 16;
+/// Délai sans acquittement au-delà duquel l'envoi est abandonné
+const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Délai laissé au destinataire pour accepter ou refuser le transfert
+const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Tampon du transport au-delà duquel l'émetteur attend
+const MAX_TRANSPORT_BUFFER: usize = 1024 * 1024;
+/// Intervalle minimal entre deux rapports de progression
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Acquittements reçus pour un envoi
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AckState {
+    /// Nombre de chunks acquittés
+    pub chunks: u64,
+    /// Octets reçus au total selon le destinataire
+    pub received: u64,
+    /// Réponse du destinataire à l'annonce (None = pas encore répondu)
+    pub accepted: Option<bool>,
+    /// Envoi annulé par l'un des pairs
+    pub cancelled: bool,
+}
+
+/// Acquittements des envois en cours, partagés entre la boucle de réception
+/// des messages et les tâches d'envoi
+#[derive(Clone, Default)]
+pub struct AckRegistry {
+    transfers: Arc<Mutex<HashMap<String, watch::Sender<AckState>>>>,
+    /// Envois en cours de chaque dossier (id du dossier → ids de ses fichiers)
+    folders: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl AckRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suivre les acquittements d'un envoi
+    pub fn register(&self, id: &str) -> watch::Receiver<AckState> {
+        let (tx, rx) = watch::channel(AckState::default());
+        if let Ok(mut transfers) = self.transfers.lock() {
+            transfers.insert(id.to_string(), tx);
+        }
+        rx
+    }
+
+    /// Suivre les acquittements d'un fichier du dossier `folder` : annuler le
+    /// dossier annule aussi cet envoi
+    pub fn register_in_folder(&self, id: &str, folder: &str) -> watch::Receiver<AckState> {
+        if let Ok(mut folders) = self.folders.lock() {
+            folders.entry(folder.to_string()).or_default().push(id.to_string());
+        }
+        self.register(id)
+    }
+
+    /// Enregistrer un `FileTransferAck` ; false si l'envoi est inconnu
+    pub fn ack(&self, id: &str, received: u64) -> bool {
+        let Ok(transfers) = self.transfers.lock() else {
+            return false;
+        };
+        match transfers.get(id) {
+            Some(tx) => {
+                tx.send_modify(|state| {
+                    state.chunks += 1;
+                    state.received = state.received.max(received);
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Enregistrer un `FileTransferResponse` ; false si l'envoi est inconnu
+    pub fn respond(&self, id: &str, accepted: bool) -> bool {
+        let Ok(transfers) = self.transfers.lock() else {
+            return false;
+        };
+        match transfers.get(id) {
+            Some(tx) => {
+                tx.send_modify(|state| state.accepted = Some(accepted));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Interrompre un envoi ; l'id d'un dossier interrompt aussi les fichiers
+    /// enregistrés via `register_in_folder`. Retourne false si aucun envoi ne correspond.
+    pub fn cancel(&self, id: &str) -> bool {
+        let children = self.folders.lock().ok()
+            .and_then(|folders| folders.get(id).cloned())
+            .unwrap_or_default();
+        let Ok(transfers) = self.transfers.lock() else {
+            return false;
+        };
+        let mut found = false;
+        for tid in std::iter::once(id).chain(children.iter().map(String::as_str)) {
+            if let Some(tx) = transfers.get(tid) {
+                tx.send_modify(|state| state.cancelled = true);
+                found = true;
+            }
+        }
+        found
+    }
+
+    pub fn unregister(&self, id: &str) {
+        if let Ok(mut transfers) = self.transfers.lock() {
+            transfers.remove(id);
+        }
+        if let Ok(mut folders) = self.folders.lock() {
+            folders.remove(id);
+            folders.retain(|_, children| {
+                children.retain(|child| child != id);
+                !children.is_empty()
+            });
+        }
+    }
+}
+
+/// Progression d'un envoi, remontée à l'appelant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SendProgress {
+    pub id: String,
+    pub size: u64,
+    /// Octets envoyés pendant cet envoi
+    pub sent: u64,
+    /// Octets reçus au total selon le destinataire
+    pub acked: u64,
+    /// Débit acquitté, lissé (octets/s)
+    pub bytes_per_sec: f64,
+}
+
+/// Débit lissé (moyenne mobile exponentielle)
+struct Throughput {
+    last: Instant,
+    last_bytes: u64,
+    rate: f64,
+}
+
+impl Throughput {
+    fn new(bytes: u64) -> Self {
+        Self { last: Instant::now(), last_bytes: bytes, rate: 0.0 }
+    }
+
+    /// Mettre à jour le débit si l'intervalle de rapport est écoulé
+    fn update(&mut self, bytes: u64, force: bool) -> bool {
+        let elapsed = self.last.elapsed();
+        if !force && elapsed < PROGRESS_INTERVAL {
+            return false;
+        }
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            let instant = bytes.saturating_sub(self.last_bytes) as f64 / secs;
+            self.rate = if self.rate == 0.0 { instant } else { 0.7 * self.rate + 0.3 * instant };
+        }
+        self.last = Instant::now();
+        self.last_bytes = bytes;
+        true
+    }
+}
+
+fn check_cancelled(state: &AckState, id: &str) -> Result<()> {
+    if state.cancelled {
+        return Err(GhostHandError::TransferCancelled { id: id.to_string(), reason: "envoi annulé".to_string() });
+    }
+    Ok(())
+}
+
+/// Émetteur de fichier à fenêtre glissante
+pub struct FileSender {
+    window: usize,
+    stall_timeout: Duration,
+    response_timeout: Duration,
+    /// File des transferts et entrée de l'envoi (pause, limite de concurrence)
+    queue: Option<(TransferQueue, String)>,
+}
+
+impl Default for FileSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSender {
+    pub fn new() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            stall_timeout: DEFAULT_STALL_TIMEOUT,
+            response_timeout: DEFAULT_RESPONSE_TIMEOUT,
+            queue: None,
+        }
+    }
+
+    /// Nombre max de chunks sans acquittement
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Délai max sans acquittement avant abandon
+    pub fn with_stall_timeout(mut self, stall_timeout: Duration) -> Self {
+        self.stall_timeout = stall_timeout;
+        self
+    }
+
+    /// Délai max laissé au destinataire pour accepter le transfert
+    pub fn with_response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+        self
+    }
+
+    /// Attendre avant chaque chunk que l'entrée `entry` de la file puisse
+    /// s'exécuter (celle du fichier, ou de son dossier)
+    pub fn with_queue(mut self, queue: TransferQueue, entry: impl Into<String>) -> Self {
+        self.queue = Some((queue, entry.into()));
+        self
+    }
+
+    /// Envoyer `transfer` puis le `FileTransferComplete` une fois tout acquitté.
+    /// `ranges` : None = fichier entier, annoncé par un `FileTransferStart` dont
+    /// l'acceptation est attendue ; Some(plages) = reprise des seules plages manquantes.
+    /// Chaque message est passé à `send` ; `progress` reçoit la progression.
+    pub async fn send<F, Fut, P>(
+        &self,
+        transfer: &OutgoingTransfer,
+        ranges: Option<&[(u64, u64)]>,
+        mut acks: watch::Receiver<AckState>,
+        send: F,
+        mut progress: P,
+    ) -> Result<()>
+    where
+        F: Fn(ControlMessage) -> Fut,
+        Fut: Future<Output = Result<()>>,
+        P: FnMut(SendProgress),
+    {
+        let id = &transfer.id;
+        let full = [(0, transfer.size)];
+        if ranges.is_none() {
+            send(ControlMessage::FileTransferStart {
+                id: id.clone(),
+                name: transfer.name.clone(),
+                size: transfer.size,
+                sha256: Some(transfer.sha256.clone()),
+                folder: transfer.folder.clone(),
+            })
+            .await?;
+            self.wait_accepted(&mut acks, id).await?;
+        }
+
+        let chunks = chunk_ranges(ranges.unwrap_or(&full));
+        let total = chunks.len() as u64;
+        let mut sent_bytes = 0u64;
+        let mut throughput = Throughput::new(acks.borrow().received);
+        let report = |sent: u64, acks: &AckState, throughput: &Throughput| SendProgress {
+            id: id.clone(),
+            size: transfer.size,
+            sent,
+            acked: acks.received,
+            bytes_per_sec: throughput.rate,
+        };
+
+        for (sent_chunks, (offset, len)) in chunks.into_iter().enumerate() {
+            // Fenêtre pleine : attendre un acquittement
+            while (sent_chunks as u64).saturating_sub(acks.borrow().chunks) >= self.window as u64 {
+                self.wait_ack(&mut acks, id).await?;
+            }
+            check_cancelled(&acks.borrow(), id)?;
+            if let Some((queue, entry)) = &self.queue {
+                queue.acquire(entry).await?;
+            }
+
+            let data = read_chunk(&transfer.path, offset, len)?;
+            let sha256 = Some(sha256_hex(&data));
+            send(ControlMessage::FileTransferChunk { id: id.clone(), data, offset, sha256 }).await?;
+            sent_bytes += len as u64;
+
+            let state = *acks.borrow();
+            if throughput.update(state.received, false) {
+                progress(report(sent_bytes, &state, &throughput));
+            }
+        }
+
+        // Le Complete n'est envoyé qu'une fois tous les chunks écrits chez le pair
+        while acks.borrow().chunks < total {
+            self.wait_ack(&mut acks, id).await?;
+        }
+        send(ControlMessage::FileTransferComplete { id: id.clone(), sha256: Some(transfer.sha256.clone()) })
+            .await?;
+
+        let state = *acks.borrow();
+        throughput.update(state.received, true);
+        progress(report(sent_bytes, &state, &throughput));
+        info!("Fichier envoyé: {} ({} chunks)", transfer.name, total);
+        Ok(())
+    }
+
+    /// Attendre la réponse du destinataire à l'annonce d'un fichier ou d'un dossier
+    pub async fn wait_accepted(&self, acks: &mut watch::Receiver<AckState>, id: &str) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.response_timeout;
+        loop {
+            let state = *acks.borrow_and_update();
+            check_cancelled(&state, id)?;
+            match state.accepted {
+                Some(true) => return Ok(()),
+                Some(false) => {
+                    return Err(GhostHandError::TransferCancelled {
+                        id: id.to_string(),
+                        reason: "refusé par le destinataire".to_string(),
+                    })
+                }
+                None => {}
+            }
+            match tokio::time::timeout_at(deadline, acks.changed()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => return Err(GhostHandError::Internal(format!("Envoi {} annulé", id))),
+                Err(_) => {
+                    return Err(GhostHandError::network_with_code(
+                        error_codes::NETWORK_TIMEOUT,
+                        format!("Envoi {}: pas de réponse du destinataire depuis {:?}", id, self.response_timeout),
+                    ))
+                }
+            }
+        }
+    }
+
+    async fn wait_ack(&self, acks: &mut watch::Receiver<AckState>, id: &str) -> Result<()> {
+        match timeout(self.stall_timeout, acks.changed()).await {
+            Ok(Ok(())) => check_cancelled(&acks.borrow(), id),
+            Ok(Err(_)) => Err(GhostHandError::Internal(format!("Envoi {} annulé", id))),
+            Err(_) => Err(GhostHandError::network_with_code(
+                error_codes::NETWORK_TIMEOUT,
+                format!("Envoi {}: aucun acquittement depuis {:?}", id, self.stall_timeout),
+            )),
+        }
+    }
+
+    /// Envoyer sur un `Transport`, trames scellées dès que la clé E2E existe.
+    /// L'émetteur attend tant que le tampon du transport est plein.
+    pub async fn send_over<P>(
+        &self,
+        transport: &Transport,
+        session_key: &SessionKeyHandle,
+        transfer: &OutgoingTransfer,
+        ranges: Option<&[(u64, u64)]>,
+        acks: watch::Receiver<AckState>,
+        progress: P,
+    ) -> Result<()>
+    where
+        P: FnMut(SendProgress),
+    {
+        let stall_timeout = self.stall_timeout;
+        let send = |msg: ControlMessage| async move {
+            let deadline = Instant::now() + stall_timeout;
+            while transport.buffered_amount().await > MAX_TRANSPORT_BUFFER {
+                if Instant::now() >= deadline {
+                    return Err(GhostHandError::network_with_code(
+                        error_codes::NETWORK_TIMEOUT,
+                        "Transport saturé",
+                    ));
+                }
+                debug!("Tampon du transport plein, envoi en pause");
+                sleep(Duration::from_millis(10)).await;
+            }
+            let bytes = msg.to_bytes()?;
+            let payload = match real_session_key(&*session_key.lock().await) {
+                Some(cipher) => cipher.seal(&bytes)?,
+                None => bytes,
+            };
+            transport.send_data(&payload).await
+        };
+        self.send(transfer, ranges, acks, send, progress).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_transfer::CHUNK_SIZE;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test]
+    async fn test_window_limits_unacknowledged_chunks() {
+        let dir = std::env::temp_dir().join(format!("ghd-sender-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.bin");
+        let size = (CHUNK_SIZE * 10 + 7) as u64;
+        std::fs::write(&path, vec![7u8; size as usize]).unwrap();
+        let transfer = crate::file_transfer::FileTransferManager::prepare_send(&path, size).unwrap();
+
+        let registry = AckRegistry::new();
+        let acks = registry.register(&transfer.id);
+        let in_flight = Arc::new(AtomicU64::new(0));
+        let max_in_flight = Arc::new(AtomicU64::new(0));
+        let received = Arc::new(AtomicU64::new(0));
+
+        // Destinataire simulé : acquitte chaque chunk après un court délai
+        let send = |msg: ControlMessage| {
+            let (registry, in_flight, max_in_flight, received) =
+                (registry.clone(), in_flight.clone(), max_in_flight.clone(), received.clone());
+            async move {
+                if let ControlMessage::FileTransferStart { id, .. } = &msg {
+                    registry.respond(id, true);
+                }
+                if let ControlMessage::FileTransferChunk { id, data, .. } = msg {
+                    let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(n, Ordering::SeqCst);
+                    tokio::spawn(async move {
+                        sleep(Duration::from_millis(2)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        let total = received.fetch_add(data.len() as u64, Ordering::SeqCst) + data.len() as u64;
+                        registry.ack(&id, total);
+                    });
+                }
+                Ok(())
+            }
+        };
+
+        let mut reports = Vec::new();
+        FileSender::new()
+            .with_window(3)
+            .send(&transfer, None, acks, send, |p| reports.push(p))
+            .await
+            .unwrap();
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3);
+        assert_eq!(reports.last().map(|p| (p.sent, p.acked)), Some((size, size)));
+
+        // Sans acquittement, l'envoi s'arrête au bout du délai
+        let acks = registry.register("ft-muet");
+        let silent = OutgoingTransfer { id: "ft-muet".into(), ..transfer };
+        let err = FileSender::new()
+            .with_window(2)
+            .with_stall_timeout(Duration::from_millis(50))
+            .send(&silent, Some(&[(0, size)]), acks, |_| async { Ok(()) }, |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GhostHandError::NetworkWithCode { code, .. } if code == error_codes::NETWORK_TIMEOUT));
+
+        // Refus du destinataire : aucun chunk envoyé
+        let acks = registry.register("fd-1-0");
+        registry.respond("fd-1-0", false);
+        let refused = OutgoingTransfer { id: "fd-1-0".into(), ..silent.clone() };
+        let sent_chunk = |msg: ControlMessage| async move {
+            assert!(!matches!(msg, ControlMessage::FileTransferChunk { .. }), "chunk envoyé malgré le refus");
+            Ok(())
+        };
+        let err = FileSender::new().send(&refused, None, acks, sent_chunk, |_| {}).await.unwrap_err();
+        assert!(matches!(err, GhostHandError::TransferCancelled { .. }));
+
+        // Annulation du dossier : ses fichiers en cours s'arrêtent, pas les
+        // envois dont l'id commence seulement de la même façon
+        let other = registry.register("fd-1-9");
+        let acks = registry.register_in_folder("fd-1-0", "fd-1");
+        assert!(!registry.cancel("fd"));
+        assert!(registry.cancel("fd-1"));
+        assert!(!other.borrow().cancelled);
+        let err = FileSender::new().send(&refused, Some(&[(0, size)]), acks, sent_chunk, |_| {}).await.unwrap_err();
+        assert!(matches!(err, GhostHandError::TransferCancelled { .. }));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,23 +1,330 @@
 use crate::error::{GhostHandError, Result};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::folder_transfer::{apply_metadata, relative_path, validate_manifest};
+use crate::protocol::FolderEntry;
+use crate::transfer_queue::{TransferDirection, TransferQueue, DEFAULT_MAX_ACTIVE};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
-const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100 MB
-const CHUNK_SIZE: usize = 48 * 1024; // 48 KB par chunk
+/// Taille max par défaut d'un transfert (les données vont sur disque, pas en RAM)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024; // 2 GB
+pub const CHUNK_SIZE: usize = 48 * 1024; // 48 KB par chunk
+/// Extension des fichiers partiels, dans le dossier de téléchargement
+const PARTIAL_EXTENSION: &str = "part";
+/// Extension de l'état persistant d'un transfert entrant (à côté du partiel)
+const RECORD_EXTENSION: &str = "part.json";
+/// Sous-dossier (du dossier de téléchargement) des envois reprenables
+const OUTGOING_DIR: &str = ".outgoing";
+/// Âge par défaut au-delà duquel un transfert interrompu est supprimé
+pub const DEFAULT_RESUME_MAX_AGE_SECS: u64 = 24 * 3600;
+/// Progression entre deux sauvegardes de l'état d'un transfert entrant
+const PERSIST_EVERY_BYTES: u64 = 4 * 1024 * 1024;
+/// Nombre max de plages dans un `FileTransferResume`
+const MAX_RESUME_RANGES: usize = 4096;
 
-/// État d'un transfert en cours
+/// Réglages des transferts de fichiers (section `file_transfer` de la config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileTransferConfig {
+    /// Taille max d'un fichier, en émission comme en réception
+    pub max_file_size: u64,
+    /// Dossier de réception (None = Téléchargements/GhostHandDesk)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+    /// Âge (secondes) au-delà duquel un transfert interrompu n'est plus reprenable
+    pub resume_max_age_secs: u64,
+    /// Demander confirmation avant d'accepter un fichier ou un dossier entrant
+    /// (sauf pair connu marqué `auto_accept_files`)
+    pub confirm_incoming: bool,
+    /// Nombre d'envois simultanés, les suivants attendent dans la file
+    pub max_concurrent_transfers: usize,
+}
+
+impl Default for FileTransferConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            download_dir: None,
+            resume_max_age_secs: DEFAULT_RESUME_MAX_AGE_SECS,
+            confirm_incoming: true,
+            max_concurrent_transfers: DEFAULT_MAX_ACTIVE,
+        }
+    }
+}
+
+/// SHA-256 d'un bloc de données, en hexadécimal minuscule
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref())
+}
+
+/// SHA-256 d'un fichier, lu par blocs
+pub fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        ctx.update(&buf[..n]);
+    }
+    Ok(to_hex(ctx.finish().as_ref()))
+}
+
+/// Lire `len` octets d'un fichier à partir de `offset`
+pub fn read_chunk(path: &Path, offset: u64, len: usize) -> Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![0u8; len];
+    file.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Découper des plages `[début, fin)` en chunks `(offset, longueur)` à envoyer
+pub fn chunk_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, usize)> {
+    let mut chunks = Vec::new();
+    for &(start, end) in ranges {
+        let mut offset = start;
+        while offset < end {
+            let len = (end - offset).min(CHUNK_SIZE as u64);
+            chunks.push((offset, len as usize));
+            offset += len;
+        }
+    }
+    chunks
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Écrire un état JSON via un fichier temporaire, pour ne jamais laisser d'état tronqué
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(value)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let data = std::fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Normaliser une empreinte annoncée par le pair (64 caractères hexadécimaux)
+fn parse_sha256(hash: &str) -> Result<String> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(GhostHandError::Validation(format!("Empreinte SHA-256 invalide: {:?}", hash)));
+    }
+    Ok(hash.to_ascii_lowercase())
+}
+
+/// Fichier à envoyer ; conservé sur disque pour pouvoir reprendre l'envoi
+/// après une reconnexion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutgoingTransfer {
+    pub id: String,
+    /// Nom annoncé au destinataire
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// SHA-256 (hex) du fichier entier
+    pub sha256: String,
+    /// Dossier d'appartenance (`FolderTransferStart`) ; `name` est alors le chemin relatif
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Pair destinataire : seul lui peut demander la reprise
+    pub peer_id: Option<String>,
+    /// Date de modification du fichier source, pour détecter un changement
+    pub modified: Option<u64>,
+    pub updated_at: u64,
+}
+
+/// État persistant d'un transfert entrant (`.<id>.part.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IncomingRecord {
+    id: String,
+    name: String,
+    size: u64,
+    sha256: Option<String>,
+    peer_id: Option<String>,
+    received: RangeSet,
+    updated_at: u64,
+    #[serde(default)]
+    folder: Option<FolderFile>,
+}
+
+/// Fichier ou dossier annoncé par le pair, en attente d'acceptation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileOffer {
+    File {
+        id: String,
+        name: String,
+        size: u64,
+        sha256: Option<String>,
+    },
+    Folder {
+        id: String,
+        name: String,
+        entries: Vec<FolderEntry>,
+    },
+}
+
+impl FileOffer {
+    pub fn id(&self) -> &str {
+        match self {
+            FileOffer::File { id, .. } | FileOffer::Folder { id, .. } => id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            FileOffer::File { name, .. } | FileOffer::Folder { name, .. } => name,
+        }
+    }
+
+    /// Taille totale annoncée
+    pub fn size(&self) -> u64 {
+        match self {
+            FileOffer::File { size, .. } => *size,
+            FileOffer::Folder { entries, .. } => entries.iter().map(|e| e.size).sum(),
+        }
+    }
+
+    /// Nombre de fichiers annoncés
+    pub fn files(&self) -> usize {
+        match self {
+            FileOffer::File { .. } => 1,
+            FileOffer::Folder { entries, .. } => entries.iter().filter(|e| !e.dir).count(),
+        }
+    }
+}
+
+/// Fichier d'un dossier reçu : destination et métadonnées issues du manifeste
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderFile {
+    pub folder: String,
+    pub dest: PathBuf,
+    pub entry: FolderEntry,
+}
+
+/// Dossier en cours de réception
+struct FolderState {
+    root: PathBuf,
+    /// Fichiers pas encore annoncés, par chemin relatif
+    pending: HashMap<String, FolderEntry>,
+    /// Fichiers annoncés, en cours de réception : un chemin n'est reçu qu'une fois
+    started: HashSet<String>,
+    dirs: Vec<FolderEntry>,
+}
+
+/// Plages d'octets reçues `[début, fin)`, triées et fusionnées
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeSet {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    /// Ajouter une plage (les recouvrements et plages contiguës sont fusionnés)
+    pub fn insert(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+        let (mut start, mut end) = (start, end);
+        self.ranges.retain(|&(s, e)| {
+            if e < start || s > end {
+                return true;
+            }
+            start = start.min(s);
+            end = end.max(e);
+            false
+        });
+        let index = self.ranges.partition_point(|&(s, _)| s < start);
+        self.ranges.insert(index, (start, end));
+    }
+
+    /// Nombre d'octets couverts
+    pub fn covered(&self) -> u64 {
+        self.ranges.iter().map(|(s, e)| e - s).sum()
+    }
+
+    /// Toutes les données de `[0, size)` sont-elles reçues ?
+    pub fn is_complete(&self, size: u64) -> bool {
+        size == 0 || self.ranges.first() == Some(&(0, size))
+    }
+
+    /// Plages encore manquantes dans `[0, size)`
+    pub fn missing(&self, size: u64) -> Vec<(u64, u64)> {
+        let mut missing = Vec::new();
+        let mut cursor = 0;
+        for &(s, e) in &self.ranges {
+            if s > cursor {
+                missing.push((cursor, s.min(size)));
+            }
+            cursor = cursor.max(e);
+        }
+        if cursor < size {
+            missing.push((cursor, size));
+        }
+        missing
+    }
+}
+
+/// État d'un transfert en cours : les chunks sont écrits à leur offset dans
+/// un fichier partiel, renommé atomiquement à la fin
 pub struct FileTransferState {
     pub name: String,
     pub size: u64,
-    pub received: u64,
-    pub data: Vec<u8>,
+    pub received: RangeSet,
+    pub temp_path: PathBuf,
+    /// SHA-256 attendu du fichier entier (annoncé au départ ou à la fin)
+    pub sha256: Option<String>,
+    /// Pair émetteur : seul lui peut reprendre le transfert
+    pub peer_id: Option<String>,
+    /// Fichier appartenant à un dossier
+    pub folder: Option<FolderFile>,
+    file: File,
+    /// Octets reçus lors de la dernière sauvegarde de l'état
+    persisted: u64,
 }
 
 /// Gestionnaire de transferts de fichiers
 pub struct FileTransferManager {
     transfers: HashMap<String, FileTransferState>,
     download_dir: PathBuf,
+    max_file_size: u64,
+    resume_max_age_secs: u64,
+    /// Pair de la session en cours
+    peer_id: Option<String>,
+    folders: HashMap<String, FolderState>,
+    /// Dossiers entièrement reçus, pas encore signalés
+    finished_folders: Vec<(String, PathBuf)>,
+    /// Annonces en attente de la décision de l'utilisateur
+    offers: HashMap<String, FileOffer>,
+    /// Transferts annulés pendant la session : leurs messages tardifs sont ignorés
+    cancelled: HashSet<String>,
+    confirm_incoming: bool,
+    /// Le pair courant est autorisé à envoyer sans confirmation
+    auto_accept: bool,
+    /// File des envois et réceptions (état, progression, événements)
+    queue: TransferQueue,
 }
 
 impl Default for FileTransferManager {
@@ -31,75 +338,757 @@ impl FileTransferManager {
         let download_dir = dirs_next::download_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("GhostHandDesk");
+        Self::new_with_dir(download_dir)
+    }
 
+    /// Créer un gestionnaire recevant dans `download_dir`
+    pub fn new_with_dir(download_dir: PathBuf) -> Self {
         // Créer le dossier si nécessaire
         let _ = std::fs::create_dir_all(&download_dir);
 
         Self {
             transfers: HashMap::new(),
             download_dir,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            resume_max_age_secs: DEFAULT_RESUME_MAX_AGE_SECS,
+            peer_id: None,
+            folders: HashMap::new(),
+            finished_folders: Vec::new(),
+            offers: HashMap::new(),
+            cancelled: HashSet::new(),
+            confirm_incoming: true,
+            auto_accept: false,
+            queue: TransferQueue::default(),
+        }
+    }
+
+    /// Créer un gestionnaire à partir de la configuration
+    pub fn from_config(config: &FileTransferConfig) -> Self {
+        let manager = match &config.download_dir {
+            Some(dir) => Self::new_with_dir(dir.clone()),
+            None => Self::new(),
+        };
+        let manager = manager
+            .with_max_file_size(config.max_file_size)
+            .with_resume_max_age(config.resume_max_age_secs)
+            .with_confirm_incoming(config.confirm_incoming);
+        manager.queue.set_max_active(config.max_concurrent_transfers);
+        manager.gc_stale();
+        manager
+    }
+
+    /// Limiter la taille des fichiers acceptés
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Âge max (secondes) d'un transfert interrompu avant suppression
+    pub fn with_resume_max_age(mut self, secs: u64) -> Self {
+        self.resume_max_age_secs = secs;
+        self
+    }
+
+    /// Demander (ou non) confirmation avant d'accepter un transfert entrant
+    pub fn with_confirm_incoming(mut self, confirm_incoming: bool) -> Self {
+        self.confirm_incoming = confirm_incoming;
+        self
+    }
+
+    /// File des transferts, à cloner pour attendre une place ou s'abonner
+    pub fn queue(&self) -> &TransferQueue {
+        &self.queue
+    }
+
+    /// Définir le pair de la session en cours (None = déconnecté). Les annonces
+    /// en attente et l'acceptation automatique ne survivent pas au changement.
+    pub fn set_peer(&mut self, peer_id: Option<String>) {
+        if peer_id != self.peer_id {
+            for id in self.offers.keys() {
+                self.queue.finish(id, Some("pair déconnecté".to_string()));
+            }
+            self.offers.clear();
+            self.cancelled.clear();
+            self.auto_accept = false;
+        }
+        self.peer_id = peer_id;
+    }
+
+    /// Accepter sans confirmation les transferts du pair courant (`KnownPeer::auto_accept_files`)
+    pub fn set_auto_accept(&mut self, auto_accept: bool) {
+        self.auto_accept = auto_accept;
+    }
+
+    /// Enregistrer une annonce du pair, validée immédiatement (taille, id,
+    /// manifeste). Retourne true si elle peut être acceptée sans confirmation ;
+    /// sinon elle attend `accept` ou `reject`.
+    pub fn offer(&mut self, offer: FileOffer) -> Result<bool> {
+        Self::validate_id(offer.id())?;
+        match &offer {
+            FileOffer::File { size, sha256, .. } => {
+                if *size > self.max_file_size {
+                    return Err(GhostHandError::Validation(format!(
+                        "Fichier trop gros: {} bytes (max: {} bytes)", size, self.max_file_size
+                    )));
+                }
+                sha256.as_deref().map(parse_sha256).transpose()?;
+            }
+            FileOffer::Folder { entries, .. } => validate_manifest(entries, self.max_file_size)?,
+        }
+        let id = offer.id().to_string();
+        if self.offers.contains_key(&id) || self.transfers.contains_key(&id) || self.folders.contains_key(&id) {
+            return Err(GhostHandError::Validation(format!("Transfert {} déjà en cours", id)));
+        }
+        self.cancelled.remove(&id);
+        self.queue.enqueue(&id, offer.name(), TransferDirection::Receive, offer.size());
+        self.offers.insert(id, offer);
+        Ok(self.auto_accept || !self.confirm_incoming)
+    }
+
+    /// Accepter une annonce et démarrer la réception ; pour un dossier, retourne
+    /// aussi son emplacement local
+    pub fn accept(&mut self, id: &str) -> Result<(FileOffer, Option<PathBuf>)> {
+        let offer = self.offers.remove(id).ok_or_else(|| {
+            GhostHandError::Validation(format!("Aucune annonce en attente pour {}", id))
+        })?;
+        let root = match &offer {
+            FileOffer::File { id, name, size, sha256 } => {
+                self.start_receive(id.clone(), name.clone(), *size, sha256.as_deref())?;
+                None
+            }
+            FileOffer::Folder { id, name, entries } => Some(self.start_folder(id.clone(), name, entries.clone())?),
+        };
+        self.queue.start(id);
+        Ok((offer, root))
+    }
+
+    /// Refuser une annonce
+    pub fn reject(&mut self, id: &str) -> Option<FileOffer> {
+        let offer = self.offers.remove(id)?;
+        info!("Transfert {} refusé ({})", id, offer.name());
+        self.queue.finish(id, Some("refusé".to_string()));
+        Some(offer)
+    }
+
+    /// Annonces en attente de décision
+    pub fn pending_offers(&self) -> Vec<FileOffer> {
+        self.offers.values().cloned().collect()
+    }
+
+    /// Annuler un transfert (annonce, réception en cours ou envoi reprenable) et
+    /// supprimer son état partiel. L'id d'un dossier annule tous ses fichiers ;
+    /// les fichiers déjà complets (et leurs dossiers) sont conservés. Retourne false si l'id est inconnu.
+    pub fn cancel(&mut self, id: &str) -> bool {
+        if Self::validate_id(id).is_err() {
+            return false;
+        }
+        self.queue.finish(id, Some("annulé".to_string()));
+        let mut found = self.offers.remove(id).is_some();
+        let ids: Vec<String> = self.transfers.iter()
+            .filter(|(tid, state)| {
+                tid.as_str() == id || state.folder.as_ref().is_some_and(|f| f.folder == id)
+            })
+            .map(|(tid, _)| tid.clone())
+            .collect();
+        for tid in ids {
+            found |= self.abort(&tid);
+            // Les messages tardifs de ses fichiers restent reconnus comme annulés
+            self.cancelled.insert(tid);
+        }
+        if let Some(folder) = self.folders.remove(id) {
+            info!("Dossier {} annulé ({})", id, folder.root.display());
+            // Seuls les dossiers restés vides sont supprimés, les plus profonds d'abord
+            for dir in folder.dirs.iter().rev() {
+                if let Ok(path) = relative_path(&dir.path) {
+                    let _ = std::fs::remove_dir(folder.root.join(path));
+                }
+            }
+            let _ = std::fs::remove_dir(&folder.root);
+            found = true;
+        }
+        found |= self.forget_outgoing(id);
+        self.cancelled.insert(id.to_string());
+        found
+    }
+
+    /// Supprimer l'état reprenable d'un envoi et, pour un dossier, de ses fichiers
+    fn forget_outgoing(&self, id: &str) -> bool {
+        let mut found = std::fs::remove_file(self.outgoing_path(id)).is_ok();
+        if let Ok(entries) = std::fs::read_dir(self.download_dir.join(OUTGOING_DIR)) {
+            for path in entries.flatten().map(|e| e.path()) {
+                let is_child = read_json::<OutgoingTransfer>(&path)
+                    .is_some_and(|o| o.folder.as_deref() == Some(id));
+                if is_child && std::fs::remove_file(&path).is_ok() {
+                    found = true;
+                }
+            }
+        }
+        found
+    }
+
+    /// Erreur dédiée pour les messages d'un transfert annulé (ignorés sans bruit).
+    /// Un fichier en cours est aussi annulé avec le dossier auquel il appartient.
+    fn check_cancelled(&self, id: &str) -> Result<()> {
+        let folder = self.transfers.get(id).and_then(|state| state.folder.as_ref()).map(|f| f.folder.as_str());
+        match [Some(id), folder].into_iter().flatten().find(|i| self.cancelled.contains(*i)) {
+            Some(cancelled) => Err(GhostHandError::TransferCancelled {
+                id: cancelled.to_string(),
+                reason: "transfert annulé".to_string(),
+            }),
+            None => Ok(()),
         }
     }
 
     /// Démarrer un nouveau transfert (côté réception)
-    pub fn start_receive(&mut self, id: String, name: String, size: u64) -> Result<()> {
-        if size > MAX_FILE_SIZE {
-            return Err(GhostHandError::Internal(format!(
-                "Fichier trop gros: {} bytes (max: {} bytes)", size, MAX_FILE_SIZE
+    pub fn start_receive(&mut self, id: String, name: String, size: u64, sha256: Option<&str>) -> Result<()> {
+        self.open_partial(id, name, size, sha256, None)
+    }
+
+    /// Démarrer la réception d'un dossier : le manifeste est validé et
+    /// l'arborescence créée sous un nom libre du dossier de téléchargement
+    pub fn start_folder(&mut self, id: String, name: &str, entries: Vec<FolderEntry>) -> Result<PathBuf> {
+        Self::validate_id(&id)?;
+        validate_manifest(&entries, self.max_file_size)?;
+        if self.folders.contains_key(&id) {
+            return Err(GhostHandError::Validation(format!("Dossier {} déjà en cours", id)));
+        }
+        let safe_name = Path::new(name)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .filter(|n| relative_path(n).is_ok())
+            .unwrap_or("received_folder");
+        let root = Self::unique_path(&self.download_dir, safe_name);
+        std::fs::create_dir(&root)?;
+
+        let (dirs, files): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| e.dir);
+        for dir in &dirs {
+            std::fs::create_dir_all(root.join(relative_path(&dir.path)?))?;
+        }
+        info!("Début réception dossier: {} ({} fichiers)", root.display(), files.len());
+        let pending = files.into_iter().map(|e| (e.path.clone(), e)).collect();
+        self.folders.insert(id.clone(), FolderState { root: root.clone(), pending, started: HashSet::new(), dirs });
+        self.finish_folder_if_done(&id);
+        Ok(root)
+    }
+
+    /// Démarrer la réception d'un fichier annoncé dans le manifeste du dossier `folder`
+    pub fn start_receive_in_folder(
+        &mut self,
+        folder: &str,
+        id: String,
+        path: String,
+        size: u64,
+        sha256: Option<&str>,
+    ) -> Result<()> {
+        self.check_cancelled(&id)?;
+        self.check_cancelled(folder)?;
+        let state = self.folders.get(folder).ok_or_else(|| {
+            GhostHandError::Validation(format!("Dossier {} inconnu", folder))
+        })?;
+        if state.started.contains(&path) {
+            return Err(GhostHandError::Validation(format!("{} déjà reçu ou en cours de réception", path)));
+        }
+        let entry = state.pending.get(&path)
+            .filter(|entry| entry.size == size)
+            .cloned()
+            .ok_or_else(|| GhostHandError::Validation(format!("{} absent du manifeste", path)))?;
+        let dest = state.root.join(relative_path(&path)?);
+        let folder_file = FolderFile { folder: folder.to_string(), dest, entry };
+        self.open_partial(id, path.clone(), size, sha256, Some(folder_file))?;
+        if let Some(state) = self.folders.get_mut(folder) {
+            state.pending.remove(&path);
+            state.started.insert(path);
+        }
+        Ok(())
+    }
+
+    /// Dossiers entièrement reçus depuis le dernier appel (id, chemin local)
+    pub fn take_finished_folders(&mut self) -> Vec<(String, PathBuf)> {
+        std::mem::take(&mut self.finished_folders)
+    }
+
+    fn finish_folder_if_done(&mut self, id: &str) {
+        if !self.folders.get(id).is_some_and(|f| f.pending.is_empty() && f.started.is_empty()) {
+            return;
+        }
+        let Some(folder) = self.folders.remove(id) else { return };
+        // Les plus profonds d'abord : la date d'un dossier change quand on écrit dedans
+        for dir in folder.dirs.iter().rev() {
+            if let Ok(path) = relative_path(&dir.path) {
+                apply_metadata(&folder.root.join(path), dir);
+            }
+        }
+        info!("Dossier reçu: {}", folder.root.display());
+        self.queue.finish(id, None);
+        self.finished_folders.push((id.to_string(), folder.root));
+    }
+
+    fn open_partial(
+        &mut self,
+        id: String,
+        name: String,
+        size: u64,
+        sha256: Option<&str>,
+        folder: Option<FolderFile>,
+    ) -> Result<()> {
+        if size > self.max_file_size {
+            return Err(GhostHandError::Validation(format!(
+                "Fichier trop gros: {} bytes (max: {} bytes)", size, self.max_file_size
             )));
         }
+        Self::validate_id(&id)?;
+        let sha256 = sha256.map(parse_sha256).transpose()?;
+        if self.transfers.contains_key(&id) {
+            return Err(GhostHandError::Validation(format!("Transfert {} déjà en cours", id)));
+        }
+
+        // Fichier partiel caché, pré-dimensionné pour accepter les écritures dans le désordre
+        let temp_path = self.download_dir.join(format!(".{}.{}", id, PARTIAL_EXTENSION));
+        let file = File::create(&temp_path).map_err(|e| {
+            GhostHandError::Internal(format!("Erreur création fichier partiel: {}", e))
+        })?;
+        if let Err(e) = file.set_len(size) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(GhostHandError::Internal(format!("Espace disque insuffisant: {}", e)));
+        }
 
         info!("Début réception fichier: {} ({} bytes)", name, size);
-        self.transfers.insert(id, FileTransferState {
+        let mut state = FileTransferState {
             name,
             size,
-            received: 0,
-            data: Vec::with_capacity(size as usize),
-        });
+            received: RangeSet::default(),
+            temp_path,
+            sha256,
+            peer_id: self.peer_id.clone(),
+            folder,
+            file,
+            persisted: 0,
+        };
+        if let Err(e) = self.persist(&id, &mut state) {
+            warn!("État du transfert {} non sauvegardé (reprise impossible): {}", id, e);
+        }
+        self.transfers.insert(id, state);
         Ok(())
     }
 
-    /// Recevoir un chunk de données
-    pub fn receive_chunk(&mut self, id: &str, data: &[u8], offset: u64) -> Result<f64> {
+    /// Recevoir un chunk de données (éventuellement dans le désordre ou en double).
+    /// Si `sha256` est fourni, le chunk est vérifié avant d'être écrit.
+    pub fn receive_chunk(&mut self, id: &str, data: &[u8], offset: u64, sha256: Option<&str>) -> Result<f64> {
+        self.check_cancelled(id)?;
+        let record_path = self.record_path(id);
         let state = self.transfers.get_mut(id).ok_or_else(|| {
             GhostHandError::Internal(format!("Transfert {} non trouvé", id))
         })?;
 
-        // Vérifier l'offset
-        if offset != state.received {
-            warn!("Offset inattendu: attendu {}, reçu {}", state.received, offset);
+        let end = offset.checked_add(data.len() as u64).filter(|&end| end <= state.size);
+        let Some(end) = end else {
+            return Err(GhostHandError::Validation(format!(
+                "Chunk hors limites: offset {} + {} bytes > {} bytes", offset, data.len(), state.size
+            )));
+        };
+
+        if let Some(expected) = sha256 {
+            let expected = parse_sha256(expected)?;
+            let actual = sha256_hex(data);
+            if actual != expected {
+                return Err(GhostHandError::IntegrityMismatch {
+                    name: format!("{} (chunk @{})", state.name, offset),
+                    expected,
+                    actual,
+                });
+            }
         }
 
-        state.data.extend_from_slice(data);
-        state.received += data.len() as u64;
+        state.file.seek(SeekFrom::Start(offset))
+            .and_then(|_| state.file.write_all(data))
+            .map_err(|e| GhostHandError::Internal(format!("Erreur écriture fichier: {}", e)))?;
+        let before = state.received.covered();
+        state.received.insert(offset, end);
+        // Un dossier progresse comme un seul transfert dans la file
+        let entry = state.folder.as_ref().map_or(id, |f| f.folder.as_str());
+        self.queue.advance(entry, state.received.covered() - before);
+
+        // Sauvegarde périodique : une plage n'est déclarée reçue qu'une fois écrite
+        if state.received.covered() - state.persisted >= PERSIST_EVERY_BYTES {
+            if let Err(e) = state.file.sync_data().map_err(GhostHandError::from)
+                .and_then(|_| Self::write_record(&record_path, id, state))
+            {
+                warn!("État du transfert {} non sauvegardé: {}", id, e);
+            }
+        }
 
         // Retourner la progression (0.0 → 1.0)
-        Ok(state.received as f64 / state.size as f64)
+        if state.size == 0 {
+            return Ok(1.0);
+        }
+        Ok(state.received.covered() as f64 / state.size as f64)
     }
 
-    /// Finaliser un transfert
-    pub fn complete(&mut self, id: &str) -> Result<PathBuf> {
+    /// Un fichier (isolé ou d'un dossier) est-il en cours de réception ?
+    pub fn is_receiving(&self, id: &str) -> bool {
+        self.transfers.contains_key(id)
+    }
+
+    /// Octets reçus pour un transfert en cours (pour l'acquittement)
+    pub fn received(&self, id: &str) -> Option<u64> {
+        self.transfers.get(id).map(|state| state.received.covered())
+    }
+
+    /// Finaliser un transfert : le SHA-256 du fichier partiel est vérifié, puis
+    /// le fichier est renommé sous son nom final. Un fichier corrompu est supprimé
+    /// sans jamais apparaître sous son nom définitif.
+    pub fn complete(&mut self, id: &str, sha256: Option<&str>) -> Result<PathBuf> {
+        self.check_cancelled(id)?;
         let state = self.transfers.remove(id).ok_or_else(|| {
             GhostHandError::Internal(format!("Transfert {} non trouvé", id))
         })?;
 
-        // Extraire uniquement le nom de base pour prévenir le path traversal
-        let safe_name = std::path::Path::new(&state.name)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("received_file");
+        if !state.received.is_complete(state.size) {
+            let missing = state.received.missing(state.size);
+            Self::discard(state);
+            return Err(GhostHandError::Validation(format!(
+                "Transfert {} incomplet: {} plage(s) manquante(s)", id, missing.len()
+            )));
+        }
+        if let Err(e) = state.file.sync_all() {
+            Self::discard(state);
+            return Err(GhostHandError::Internal(format!("Erreur écriture fichier: {}", e)));
+        }
+        if let Err(e) = Self::verify(&state, sha256) {
+            Self::discard(state);
+            return Err(e);
+        }
 
-        // Anti-écrasement (F6) : si le fichier existe déjà, suffixer « nom (n).ext »
-        let file_path = Self::unique_path(&self.download_dir, safe_name);
+        // Fichier d'un dossier : destination fixée par le manifeste (chemin validé)
+        let file_path = match &state.folder {
+            Some(folder_file) => {
+                let parent = folder_file.dest.parent().unwrap_or(&self.download_dir);
+                let file_name = folder_file.dest.file_name().and_then(|n| n.to_str()).unwrap_or("received_file");
+                let _ = std::fs::create_dir_all(parent);
+                Self::unique_path(parent, file_name)
+            }
+            None => {
+                // Extraire uniquement le nom de base pour prévenir le path traversal
+                let safe_name = Path::new(&state.name)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("received_file");
 
-        std::fs::write(&file_path, &state.data).map_err(|e| {
-            GhostHandError::Internal(format!("Erreur écriture fichier: {}", e))
-        })?;
+                // Anti-écrasement (F6) : si le fichier existe déjà, suffixer « nom (n).ext »
+                Self::unique_path(&self.download_dir, safe_name)
+            }
+        };
+
+        let FileTransferState { size, temp_path, file, folder, .. } = state;
+        drop(file);
+        let _ = std::fs::remove_file(self.record_path(id));
+        if let Err(e) = std::fs::rename(&temp_path, &file_path) {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(GhostHandError::Internal(format!("Erreur écriture fichier: {}", e)));
+        }
 
-        info!("Fichier reçu: {} ({} bytes)", file_path.display(), state.data.len());
+        info!("Fichier reçu: {} ({} bytes)", file_path.display(), size);
+        match folder {
+            Some(FolderFile { folder, entry, .. }) => {
+                apply_metadata(&file_path, &entry);
+                if let Some(state) = self.folders.get_mut(&folder) {
+                    state.started.remove(&entry.path);
+                }
+                self.finish_folder_if_done(&folder);
+            }
+            None => {
+                self.queue.finish(id, None);
+            }
+        }
         Ok(file_path)
     }
 
+    /// Comparer le SHA-256 du fichier partiel à celui annoncé par l'émetteur
+    fn verify(state: &FileTransferState, sha256: Option<&str>) -> Result<()> {
+        let announced = sha256.map(parse_sha256).transpose()?;
+        if let (Some(start), Some(end)) = (&state.sha256, &announced) {
+            if start != end {
+                return Err(GhostHandError::Validation(format!(
+                    "Empreintes contradictoires pour {}", state.name
+                )));
+            }
+        }
+        let Some(expected) = announced.or_else(|| state.sha256.clone()) else {
+            warn!("Transfert {} sans empreinte SHA-256 : intégrité non vérifiée", state.name);
+            return Ok(());
+        };
+        let actual = file_sha256(&state.temp_path)?;
+        if actual != expected {
+            return Err(GhostHandError::IntegrityMismatch {
+                name: state.name.clone(),
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Abandonner un transfert et supprimer son fichier partiel
+    pub fn abort(&mut self, id: &str) -> bool {
+        match self.transfers.remove(id) {
+            Some(state) => {
+                info!("Transfert {} abandonné ({})", id, state.name);
+                let entry = state.folder.as_ref().map_or(id, |f| f.folder.as_str());
+                self.queue.finish(entry, Some("abandonné".to_string()));
+                Self::discard(state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Abandonner tous les transferts en cours (fin de session)
+    pub fn abort_all(&mut self) {
+        let ids: Vec<String> = self.transfers.keys().cloned().collect();
+        for id in ids {
+            self.abort(&id);
+        }
+    }
+
+    fn discard(state: FileTransferState) {
+        let FileTransferState { temp_path, file, .. } = state;
+        drop(file);
+        let _ = std::fs::remove_file(temp_path.with_extension(RECORD_EXTENSION));
+        if let Err(e) = std::fs::remove_file(&temp_path) {
+            warn!("Fichier partiel {} non supprimé: {}", temp_path.display(), e);
+        }
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.download_dir.join(format!(".{}.{}", id, RECORD_EXTENSION))
+    }
+
+    fn persist(&self, id: &str, state: &mut FileTransferState) -> Result<()> {
+        Self::write_record(&self.record_path(id), id, state)
+    }
+
+    fn write_record(path: &Path, id: &str, state: &mut FileTransferState) -> Result<()> {
+        let record = IncomingRecord {
+            id: id.to_string(),
+            name: state.name.clone(),
+            size: state.size,
+            sha256: state.sha256.clone(),
+            peer_id: state.peer_id.clone(),
+            received: state.received.clone(),
+            updated_at: now_secs(),
+            folder: state.folder.clone(),
+        };
+        write_json(path, &record)?;
+        state.persisted = state.received.covered();
+        Ok(())
+    }
+
+    /// Suspendre les transferts en cours (déconnexion) : l'état est sauvegardé et
+    /// les fichiers partiels sont conservés pour une reprise ultérieure
+    pub fn suspend_all(&mut self) {
+        let ids: Vec<String> = self.transfers.keys().cloned().collect();
+        for id in ids {
+            let Some(mut state) = self.transfers.remove(&id) else { continue };
+            let result = state.file.sync_data().map_err(GhostHandError::from)
+                .and_then(|_| self.persist(&id, &mut state));
+            let entry = state.folder.as_ref().map_or(id.as_str(), |f| f.folder.as_str());
+            match result {
+                Ok(()) => {
+                    self.queue.suspend(entry);
+                    info!(
+                        "Transfert {} suspendu ({} / {} bytes reçus)",
+                        id, state.received.covered(), state.size
+                    )
+                }
+                Err(e) => {
+                    warn!("Transfert {} non reprenable: {}", id, e);
+                    self.queue.finish(entry, Some(format!("non reprenable: {}", e)));
+                    Self::discard(state);
+                }
+            }
+        }
+    }
+
+    /// Recharger les transferts interrompus du pair courant et lister, pour chacun,
+    /// les plages encore manquantes (à demander via `FileTransferResume`)
+    pub fn pending_resumes(&mut self) -> Vec<(String, Vec<(u64, u64)>)> {
+        self.gc_stale();
+        for (record_path, record) in self.incoming_records() {
+            if self.transfers.contains_key(&record.id) || record.peer_id != self.peer_id {
+                continue;
+            }
+            let temp_path = self.download_dir.join(format!(".{}.{}", record.id, PARTIAL_EXTENSION));
+            let file = OpenOptions::new().write(true).open(&temp_path)
+                .ok()
+                .filter(|f| f.metadata().map(|m| m.len() == record.size).unwrap_or(false));
+            let Some(file) = file else {
+                warn!("Transfert {} non reprenable: fichier partiel absent ou altéré", record.id);
+                let _ = std::fs::remove_file(&record_path);
+                let _ = std::fs::remove_file(&temp_path);
+                continue;
+            };
+            let persisted = record.received.covered();
+            self.transfers.insert(record.id, FileTransferState {
+                name: record.name,
+                size: record.size,
+                received: record.received,
+                temp_path,
+                sha256: record.sha256,
+                peer_id: record.peer_id,
+                folder: record.folder,
+                file,
+                persisted,
+            });
+        }
+
+        let resumes: Vec<_> = self.transfers.iter()
+            .filter(|(_, state)| state.peer_id == self.peer_id)
+            .map(|(id, state)| (id.clone(), state.received.missing(state.size)))
+            .collect();
+        // Retour dans la file : une réception suspendue repart, une réception
+        // d'une session précédente y est ajoutée
+        for (id, _) in &resumes {
+            let Some(state) = self.transfers.get(id) else { continue };
+            let entry = state.folder.as_ref().map_or(id.as_str(), |f| f.folder.as_str());
+            if self.queue.enqueue(entry, &state.name, TransferDirection::Receive, state.size) {
+                self.queue.update(entry, state.received.covered());
+            }
+            self.queue.start(entry);
+        }
+        resumes
+    }
+
+    /// États persistants des transferts entrants présents sur disque
+    fn incoming_records(&self) -> Vec<(PathBuf, IncomingRecord)> {
+        let suffix = format!(".{}", RECORD_EXTENSION);
+        let Ok(entries) = std::fs::read_dir(&self.download_dir) else {
+            return Vec::new();
+        };
+        entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name().and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.') && n.ends_with(&suffix))
+            })
+            .filter_map(|path| {
+                let record: IncomingRecord = read_json(&path)?;
+                Self::validate_id(&record.id).ok()?;
+                Some((path, record))
+            })
+            .collect()
+    }
+
+    /// Supprimer les transferts interrompus (entrants comme sortants) plus vieux
+    /// que l'âge max de reprise. Retourne le nombre de transferts supprimés.
+    pub fn gc_stale(&self) -> usize {
+        let cutoff = now_secs().saturating_sub(self.resume_max_age_secs);
+        let mut removed = 0;
+
+        // Entrants : état + fichier partiel
+        let mut known = Vec::new();
+        for (record_path, record) in self.incoming_records() {
+            let temp_path = self.download_dir.join(format!(".{}.{}", record.id, PARTIAL_EXTENSION));
+            if record.updated_at < cutoff && !self.transfers.contains_key(&record.id) {
+                let _ = std::fs::remove_file(&record_path);
+                let _ = std::fs::remove_file(&temp_path);
+                removed += 1;
+            } else {
+                known.push(temp_path);
+            }
+        }
+
+        // Fichiers partiels orphelins (état perdu)
+        if let Ok(entries) = std::fs::read_dir(&self.download_dir) {
+            let suffix = format!(".{}", PARTIAL_EXTENSION);
+            for path in entries.flatten().map(|e| e.path()) {
+                let orphan = path.file_name().and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with('.') && n.ends_with(&suffix))
+                    && !known.contains(&path)
+                    && !self.transfers.values().any(|t| t.temp_path == path);
+                if orphan && modified_secs(&path).is_some_and(|m| m < cutoff) {
+                    let _ = std::fs::remove_file(&path);
+                    removed += 1;
+                }
+            }
+        }
+
+        // Sortants
+        if let Ok(entries) = std::fs::read_dir(self.download_dir.join(OUTGOING_DIR)) {
+            for path in entries.flatten().map(|e| e.path()) {
+                let stale = read_json::<OutgoingTransfer>(&path).is_none_or(|o| o.updated_at < cutoff);
+                if stale {
+                    let _ = std::fs::remove_file(&path);
+                    removed += 1;
+                }
+            }
+        }
+
+        if removed > 0 {
+            info!("{} transfert(s) interrompu(s) expiré(s) supprimé(s)", removed);
+        }
+        removed
+    }
+
+    fn outgoing_path(&self, id: &str) -> PathBuf {
+        self.download_dir.join(OUTGOING_DIR).join(format!("{}.json", id))
+    }
+
+    /// Mémoriser un envoi pour pouvoir le reprendre (destinataire = pair courant)
+    pub fn register_outgoing(&self, outgoing: &mut OutgoingTransfer) -> Result<()> {
+        Self::validate_id(&outgoing.id)?;
+        std::fs::create_dir_all(self.download_dir.join(OUTGOING_DIR))?;
+        outgoing.peer_id = self.peer_id.clone();
+        outgoing.updated_at = now_secs();
+        write_json(&self.outgoing_path(&outgoing.id), outgoing)
+    }
+
+    /// Oublier un envoi terminé
+    pub fn finish_outgoing(&self, id: &str) {
+        if Self::validate_id(id).is_ok() {
+            let _ = std::fs::remove_file(self.outgoing_path(id));
+        }
+    }
+
+    /// Valider une demande de reprise du pair courant et retourner l'envoi à
+    /// poursuivre (fichier source inchangé, plages dans les limites)
+    pub fn prepare_resume(&self, id: &str, missing: &[(u64, u64)]) -> Result<OutgoingTransfer> {
+        Self::validate_id(id)?;
+        let path = self.outgoing_path(id);
+        let mut outgoing: OutgoingTransfer = read_json(&path).ok_or_else(|| {
+            GhostHandError::Validation(format!("Aucun envoi reprenable {}", id))
+        })?;
+        if outgoing.peer_id != self.peer_id {
+            return Err(GhostHandError::Validation(format!("Envoi {} destiné à un autre pair", id)));
+        }
+        if missing.len() > MAX_RESUME_RANGES
+            || missing.iter().any(|&(s, e)| s >= e || e > outgoing.size)
+        {
+            return Err(GhostHandError::Validation(format!("Plages de reprise invalides pour {}", id)));
+        }
+        let unchanged = std::fs::metadata(&outgoing.path).is_ok_and(|m| m.len() == outgoing.size)
+            && modified_secs(&outgoing.path) == outgoing.modified;
+        if !unchanged {
+            self.finish_outgoing(id);
+            return Err(GhostHandError::Validation(format!(
+                "Fichier {} modifié ou supprimé depuis l'envoi", outgoing.path.display()
+            )));
+        }
+        outgoing.updated_at = now_secs();
+        let _ = write_json(&path, &outgoing);
+        Ok(outgoing)
+    }
+
+    /// L'ID (choisi par l'émetteur) sert à nommer le fichier partiel
+    fn validate_id(id: &str) -> Result<()> {
+        let valid = !id.is_empty()
+            && id.len() <= 64
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(GhostHandError::Validation(format!("ID de transfert invalide: {:?}", id)));
+        }
+        Ok(())
+    }
+
     /// Construire un chemin de destination non collisionnel : si `nom.ext` existe,
     /// essayer `nom (1).ext`, `nom (2).ext`, … pour ne jamais écraser un fichier existant.
     fn unique_path(dir: &std::path::Path, file_name: &str) -> PathBuf {
@@ -131,16 +1120,17 @@ impl FileTransferManager {
         dir.join(format!("{}-{}", ts, file_name))
     }
 
-    /// Préparer un fichier pour l'envoi (retourne les chunks)
-    pub fn prepare_send(path: &std::path::Path) -> Result<(String, String, u64, Vec<Vec<u8>>)> {
-        let data = std::fs::read(path).map_err(|e| {
+    /// Préparer un fichier pour l'envoi : le SHA-256 est calculé par blocs,
+    /// les chunks seront lus sur disque au fil de l'envoi
+    pub fn prepare_send(path: &std::path::Path, max_file_size: u64) -> Result<OutgoingTransfer> {
+        let metadata = std::fs::metadata(path).map_err(|e| {
             GhostHandError::Internal(format!("Erreur lecture fichier: {}", e))
         })?;
 
-        let size = data.len() as u64;
-        if size > MAX_FILE_SIZE {
-            return Err(GhostHandError::Internal(format!(
-                "Fichier trop gros: {} bytes (max: {} bytes)", size, MAX_FILE_SIZE
+        let size = metadata.len();
+        if size > max_file_size {
+            return Err(GhostHandError::Validation(format!(
+                "Fichier trop gros: {} bytes (max: {} bytes)", size, max_file_size
             )));
         }
 
@@ -154,10 +1144,264 @@ impl FileTransferManager {
             .unwrap_or_default()
             .as_millis());
 
-        let chunks: Vec<Vec<u8>> = data.chunks(CHUNK_SIZE)
-            .map(|c| c.to_vec())
-            .collect();
+        Ok(OutgoingTransfer {
+            id,
+            name,
+            path: path.to_path_buf(),
+            size,
+            sha256: file_sha256(path)?,
+            folder: None,
+            peer_id: None,
+            modified: modified_secs(path),
+            updated_at: now_secs(),
+        })
+    }
+}
+
+impl Drop for FileTransferManager {
+    fn drop(&mut self) {
+        self.suspend_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer_queue::TransferStatus;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ghd-ft-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_range_set_merges_and_reports_gaps() {
+        let mut ranges = RangeSet::default();
+        ranges.insert(10, 20);
+        ranges.insert(30, 40);
+        ranges.insert(15, 32);
+        ranges.insert(50, 60);
+        assert_eq!(ranges.covered(), 40);
+        assert_eq!(ranges.missing(70), vec![(0, 10), (40, 50), (60, 70)]);
+        ranges.insert(0, 70);
+        assert!(ranges.is_complete(70));
+    }
+
+    #[test]
+    fn test_out_of_order_chunks_are_written_to_disk() {
+        let dir = temp_dir("ooo");
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        manager.start_receive("ft-1".into(), "notes.txt".into(), 10, None).unwrap();
+
+        manager.receive_chunk("ft-1", b"56789", 5, None).unwrap();
+        assert!(manager.complete("ft-1", None).is_err(), "incomplet : refusé");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "partiel supprimé");
+
+        manager.start_receive("ft-2".into(), "notes.txt".into(), 10, None).unwrap();
+        manager.receive_chunk("ft-2", b"56789", 5, None).unwrap();
+        assert_eq!(manager.receive_chunk("ft-2", b"01234", 0, None).unwrap(), 1.0);
+        assert!(manager.receive_chunk("ft-2", b"xx", 9, None).is_err(), "hors limites");
+
+        let path = manager.complete("ft-2", None).unwrap();
+        assert_eq!(path, dir.join("notes.txt"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_abort_and_policy_limits() {
+        let dir = temp_dir("abort");
+        let mut manager = FileTransferManager::new_with_dir(dir.clone()).with_max_file_size(100);
+        assert!(manager.start_receive("ft-big".into(), "big.bin".into(), 101, None).is_err());
+        assert!(manager.start_receive("../evil".into(), "x".into(), 1, None).is_err());
+
+        manager.start_receive("ft-3".into(), "a.bin".into(), 50, None).unwrap();
+        manager.receive_chunk("ft-3", &[1; 20], 0, None).unwrap();
+        assert!(manager.abort("ft-3"));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_integrity_mismatch_discards_partial_file() {
+        let dir = temp_dir("sha");
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        let data = b"contenu important";
+        let good = sha256_hex(data);
+
+        // Chunk altéré en transit : refusé avant écriture
+        manager.start_receive("ft-4".into(), "doc.txt".into(), data.len() as u64, None).unwrap();
+        let err = manager.receive_chunk("ft-4", b"contenu imp0rtant", 0, Some(&good)).unwrap_err();
+        assert!(matches!(err, GhostHandError::IntegrityMismatch { .. }));
+
+        // Fichier complet ne correspondant pas à l'empreinte annoncée au départ
+        manager.complete("ft-4", None).unwrap_err();
+        manager.start_receive("ft-5".into(), "doc.txt".into(), data.len() as u64, Some(&good)).unwrap();
+        manager.receive_chunk("ft-5", b"contenu imp0rtant", 0, None).unwrap();
+        let err = manager.complete("ft-5", None).unwrap_err();
+        assert!(matches!(err, GhostHandError::IntegrityMismatch { .. }));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "rien sous le nom final");
+
+        manager.start_receive("ft-6".into(), "doc.txt".into(), data.len() as u64, Some(&good)).unwrap();
+        manager.receive_chunk("ft-6", data, 0, Some(&good)).unwrap();
+        let path = manager.complete("ft-6", Some(&good.to_uppercase())).unwrap();
+        assert_eq!(file_sha256(&path).unwrap(), good);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_interrupted_transfer_resumes_with_same_peer() {
+        let dir = temp_dir("resume");
+        let data: Vec<u8> = (0..100u8).collect();
+        let sha = sha256_hex(&data);
+
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        manager.set_peer(Some("pair-a".into()));
+        manager.start_receive("ft-7".into(), "data.bin".into(), 100, Some(&sha)).unwrap();
+        manager.receive_chunk("ft-7", &data[..40], 0, None).unwrap();
+        manager.suspend_all();
+        drop(manager);
+
+        // Nouvelle session (reconnexion) : un autre pair ne voit rien
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        manager.set_peer(Some("pair-b".into()));
+        assert!(manager.pending_resumes().is_empty());
+
+        manager.set_peer(Some("pair-a".into()));
+        assert_eq!(manager.pending_resumes(), vec![("ft-7".to_string(), vec![(40, 100)])]);
+        for (offset, len) in chunk_ranges(&[(40, 100)]) {
+            let chunk = &data[offset as usize..offset as usize + len];
+            manager.receive_chunk("ft-7", chunk, offset, None).unwrap();
+        }
+        let path = manager.complete("ft-7", None).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        assert!(!dir.join(".ft-7.part.json").exists());
+
+        // Côté émetteur : reprise refusée si le fichier source a changé
+        let source = dir.join("source.bin");
+        std::fs::write(&source, &data).unwrap();
+        let mut prepared = FileTransferManager::prepare_send(&source, 1000).unwrap();
+        manager.register_outgoing(&mut prepared).unwrap();
+        assert!(manager.prepare_resume(&prepared.id, &[(0, 101)]).is_err(), "hors limites");
+        assert_eq!(manager.prepare_resume(&prepared.id, &[(40, 100)]).unwrap().sha256, sha);
+        std::fs::write(&source, b"autre contenu").unwrap();
+        assert!(manager.prepare_resume(&prepared.id, &[(40, 100)]).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_folder_tree_is_recreated_from_manifest() {
+        let dir = temp_dir("folder");
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        let entry = |path: &str, is_dir: bool, size: u64| FolderEntry {
+            path: path.to_string(), dir: is_dir, size, mtime: Some(1_600_000_000), mode: None, readonly: false,
+        };
+        let traversal = vec![entry("../evil.sh", false, 4)];
+        assert!(manager.start_folder("fd-0".into(), "logs", traversal).is_err());
+
+        let entries = vec![entry("app", true, 0), entry("app/main.log", false, 5), entry("README", false, 2)];
+        let root = manager.start_folder("fd-1".into(), "../../logs", entries).unwrap();
+        assert_eq!(root, dir.join("logs"));
+        assert!(manager.start_receive_in_folder("fd-1", "fd-1-9".into(), "other".into(), 2, None).is_err());
+        assert!(manager.start_receive_in_folder("fd-1", "fd-1-9".into(), "README".into(), 3, None).is_err());
+
+        manager.start_receive_in_folder("fd-1", "fd-1-1".into(), "app/main.log".into(), 5, None).unwrap();
+        manager.receive_chunk("fd-1-1", b"hello", 0, None).unwrap();
+        assert!(
+            manager.start_receive_in_folder("fd-1", "fd-1-5".into(), "app/main.log".into(), 5, None).is_err(),
+            "chemin déjà en cours de réception"
+        );
+        let path = manager.complete("fd-1-1", None).unwrap();
+        assert_eq!(path, root.join("app").join("main.log"));
+        assert_eq!(modified_secs(&path), Some(1_600_000_000));
+        assert!(manager.take_finished_folders().is_empty());
+
+        manager.start_receive_in_folder("fd-1", "fd-1-2".into(), "README".into(), 2, None).unwrap();
+        manager.receive_chunk("fd-1-2", b"ok", 0, None).unwrap();
+        manager.complete("fd-1-2", None).unwrap();
+        assert_eq!(manager.take_finished_folders(), vec![("fd-1".to_string(), root.clone())]);
+        assert_eq!(std::fs::read(root.join("README")).unwrap(), b"ok");
+        assert!(manager.start_receive_in_folder("fd-1", "fd-1-6".into(), "README".into(), 2, None).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_offers_need_confirmation_and_cancel_cleans_up() {
+        let dir = temp_dir("offer");
+        let mut manager = FileTransferManager::new_with_dir(dir.clone());
+        manager.set_peer(Some("GHD-PEER".into()));
+        let file = |id: &str| FileOffer::File { id: id.into(), name: "a.txt".into(), size: 10, sha256: None };
+
+        assert!(!manager.offer(file("ft-1")).unwrap(), "confirmation requise");
+        assert!(manager.offer(file("ft-1")).is_err(), "annonce en double");
+        assert!(manager.receive_chunk("ft-1", b"01234", 0, None).is_err(), "rien avant acceptation");
+        assert_eq!(manager.queue().get("ft-1").map(|t| t.status), Some(TransferStatus::Queued));
+        assert_eq!(manager.reject("ft-1"), Some(file("ft-1")));
+        assert!(manager.pending_offers().is_empty());
+        assert_eq!(manager.queue().get("ft-1").map(|t| t.status), Some(TransferStatus::Failed));
+
+        manager.set_auto_accept(true);
+        assert!(manager.offer(file("ft-2")).unwrap(), "pair de confiance");
+        manager.accept("ft-2").unwrap();
+        manager.receive_chunk("ft-2", b"01234", 0, None).unwrap();
+        let queued = manager.queue().get("ft-2").unwrap();
+        assert_eq!((queued.status, queued.transferred), (TransferStatus::Active, 5));
+        assert!(manager.cancel("ft-2"));
+        assert_eq!(manager.queue().get("ft-2").and_then(|t| t.error), Some("annulé".to_string()));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "partiel et état supprimés");
+        assert!(matches!(
+            manager.receive_chunk("ft-2", b"56789", 5, None),
+            Err(GhostHandError::TransferCancelled { .. })
+        ));
+
+        // Annuler un dossier annule ses fichiers, même annoncés plus tard
+        let entry = FolderEntry { path: "a.txt".into(), dir: false, size: 10, mtime: None, mode: None, readonly: false };
+        manager.offer(FileOffer::Folder { id: "fd-1".into(), name: "logs".into(), entries: vec![entry] }).unwrap();
+        let (_, root) = manager.accept("fd-1").unwrap();
+        let root = root.unwrap();
+        assert!(root.is_dir());
+        assert!(manager.cancel("fd-1"));
+        assert!(!root.exists(), "dossier vide supprimé");
+        assert!(matches!(
+            manager.start_receive_in_folder("fd-1", "fd-1-0".into(), "a.txt".into(), 10, None),
+            Err(GhostHandError::TransferCancelled { .. })
+        ));
+
+        // Un dossier dont l'id est le préfixe d'un fichier isolé n'annule que ses fichiers
+        manager.offer(file("ft-4")).unwrap();
+        manager.accept("ft-4").unwrap();
+        let entry = FolderEntry { path: "b.txt".into(), dir: false, size: 10, mtime: None, mode: None, readonly: false };
+        manager.offer(FileOffer::Folder { id: "ft".into(), name: "docs".into(), entries: vec![entry] }).unwrap();
+        manager.accept("ft").unwrap();
+        manager.start_receive_in_folder("ft", "ft-0".into(), "b.txt".into(), 10, None).unwrap();
+        assert!(manager.cancel("ft"));
+        assert!(matches!(
+            manager.receive_chunk("ft-0", b"01234", 0, None),
+            Err(GhostHandError::TransferCancelled { .. })
+        ));
+        manager.receive_chunk("ft-4", b"01234", 0, None).unwrap();
+        assert!(manager.is_receiving("ft-4"));
+        assert!(!manager.is_receiving("ft-0"));
+
+        // Les envois reprenables d'un dossier sont retrouvés par leur champ `folder`,
+        // pas par le préfixe de leur id
+        let outgoing = |id: &str, folder: Option<&str>| OutgoingTransfer {
+            id: id.into(), name: "a.txt".into(), path: dir.join("a.txt"), size: 10, sha256: String::new(),
+            folder: folder.map(String::from), peer_id: None, modified: None, updated_at: 0,
+        };
+        manager.register_outgoing(&mut outgoing("ft-5", None)).unwrap();
+        manager.register_outgoing(&mut outgoing("fd-2-0", Some("fd-2"))).unwrap();
+        manager.register_outgoing(&mut outgoing("fd-2-1", Some("fd-2"))).unwrap();
+        manager.cancel("ft");
+        assert!(manager.outgoing_path("ft-5").exists(), "envoi isolé conservé");
+        assert!(manager.cancel("fd-2"));
+        assert!(!manager.outgoing_path("fd-2-0").exists());
+        assert!(!manager.outgoing_path("fd-2-1").exists());
 
-        Ok((id, name, size, chunks))
+        // Changer de pair oublie la confiance accordée
+        manager.set_peer(Some("GHD-AUTRE".into()));
+        assert!(!manager.offer(file("ft-3")).unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,257 @@
+//! Transfert de dossiers
+//!
+//! L'émetteur parcourt le dossier et construit un manifeste (chemins relatifs,
+//! tailles, dates de modification, permissions) envoyé dans un
+//! `FolderTransferStart`. Les fichiers suivent comme des transferts ordinaires
+//! rattachés au dossier. Les liens symboliques et fichiers spéciaux sont
+//! ignorés à l'envoi ; chaque chemin du manifeste est revalidé à la réception
+//! (pas de `..`, de chemin absolu ni de séparateur Windows) avant de recréer
+//! l'arborescence dans le dossier de téléchargement.
+
+use crate::error::{GhostHandError, Result};
+use crate::file_transfer::{file_sha256, OutgoingTransfer};
+use crate::protocol::FolderEntry;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Nombre max d'entrées (fichiers et sous-dossiers) d'un manifeste
+pub const MAX_FOLDER_ENTRIES: usize = 10_000;
+/// Profondeur max de l'arborescence
+const MAX_DEPTH: usize = 32;
+/// Longueur max d'un chemin relatif
+const MAX_PATH_LEN: usize = 1024;
+
+/// Valider un chemin relatif du manifeste et le convertir en chemin local
+pub fn relative_path(path: &str) -> Result<PathBuf> {
+    let invalid = || GhostHandError::Validation(format!("Chemin de dossier invalide: {:?}", path));
+    if path.is_empty() || path.len() > MAX_PATH_LEN {
+        return Err(invalid());
+    }
+    let mut local = PathBuf::new();
+    for (depth, component) in path.split('/').enumerate() {
+        let forbidden = component.is_empty()
+            || component == "."
+            || component == ".."
+            || component.chars().any(|c| c == '\\' || c == ':' || c.is_control());
+        if forbidden || depth >= MAX_DEPTH {
+            return Err(invalid());
+        }
+        local.push(component);
+    }
+    Ok(local)
+}
+
+/// Valider un manifeste reçu : chemins sûrs, sans doublon, taille totale bornée
+pub fn validate_manifest(entries: &[FolderEntry], max_total_size: u64) -> Result<()> {
+    if entries.len() > MAX_FOLDER_ENTRIES {
+        return Err(GhostHandError::Validation(format!(
+            "Dossier trop volumineux: {} entrées (max {})", entries.len(), MAX_FOLDER_ENTRIES
+        )));
+    }
+    let mut seen = HashSet::new();
+    let mut total: u64 = 0;
+    for entry in entries {
+        relative_path(&entry.path)?;
+        if !seen.insert(entry.path.as_str()) {
+            return Err(GhostHandError::Validation(format!("Entrée en double: {}", entry.path)));
+        }
+        total = total.saturating_add(entry.size);
+    }
+    if total > max_total_size {
+        return Err(GhostHandError::Validation(format!(
+            "Dossier trop gros: {} bytes (max: {} bytes)", total, max_total_size
+        )));
+    }
+    Ok(())
+}
+
+/// Manifeste d'un dossier à envoyer
+#[derive(Debug, Clone)]
+pub struct FolderManifest {
+    pub root: PathBuf,
+    pub name: String,
+    pub entries: Vec<FolderEntry>,
+}
+
+impl FolderManifest {
+    /// Parcourir `root` ; les liens symboliques et fichiers spéciaux sont ignorés
+    pub fn build(root: &Path, max_total_size: u64) -> Result<Self> {
+        if !std::fs::symlink_metadata(root)?.is_dir() {
+            return Err(GhostHandError::Validation(format!(
+                "{} n'est pas un dossier", root.display()
+            )));
+        }
+        let name = root.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("dossier")
+            .to_string();
+
+        let mut entries = Vec::new();
+        Self::walk(root, "", 0, &mut entries)?;
+        validate_manifest(&entries, max_total_size)?;
+        Ok(Self { root: root.to_path_buf(), name, entries })
+    }
+
+    fn walk(dir: &Path, prefix: &str, depth: usize, entries: &mut Vec<FolderEntry>) -> Result<()> {
+        if depth >= MAX_DEPTH {
+            return Err(GhostHandError::Validation(format!(
+                "Arborescence trop profonde: {}", dir.display()
+            )));
+        }
+        let mut children: Vec<_> = std::fs::read_dir(dir)?.flatten().collect();
+        children.sort_by_key(|c| c.file_name());
+
+        for child in children {
+            let path = child.path();
+            let Some(file_name) = child.file_name().to_str().map(str::to_string) else {
+                warn!("Nom non UTF-8 ignoré: {}", path.display());
+                continue;
+            };
+            let relative = if prefix.is_empty() { file_name } else { format!("{}/{}", prefix, file_name) };
+            if relative_path(&relative).is_err() {
+                warn!("Chemin non transférable ignoré: {}", path.display());
+                continue;
+            }
+            // symlink_metadata : un lien n'est jamais suivi
+            let metadata = std::fs::symlink_metadata(&path)?;
+            let file_type = metadata.file_type();
+            if !file_type.is_dir() && !file_type.is_file() {
+                warn!("Lien symbolique ou fichier spécial ignoré: {}", path.display());
+                continue;
+            }
+            if entries.len() >= MAX_FOLDER_ENTRIES {
+                return Err(GhostHandError::Validation(format!(
+                    "Dossier trop volumineux (max {} entrées)", MAX_FOLDER_ENTRIES
+                )));
+            }
+            entries.push(FolderEntry {
+                path: relative.clone(),
+                dir: file_type.is_dir(),
+                size: if file_type.is_file() { metadata.len() } else { 0 },
+                mtime: metadata.modified().ok()
+                    .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs()),
+                mode: unix_mode(&metadata),
+                readonly: metadata.permissions().readonly(),
+            });
+            if file_type.is_dir() {
+                Self::walk(&path, &relative, depth + 1, entries)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+
+    /// Préparer l'envoi de chaque fichier (SHA-256 inclus) ; les ids dérivent
+    /// de celui du dossier
+    pub fn transfers(&self, folder_id: &str) -> Result<Vec<OutgoingTransfer>> {
+        let mut transfers = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate().filter(|(_, e)| !e.dir) {
+            let path = self.root.join(relative_path(&entry.path)?);
+            // Le fichier a pu être remplacé par un lien depuis le parcours
+            if !std::fs::symlink_metadata(&path)?.is_file() {
+                return Err(GhostHandError::Validation(format!(
+                    "{} n'est plus un fichier régulier", path.display()
+                )));
+            }
+            transfers.push(OutgoingTransfer {
+                id: format!("{}-{}", folder_id, index),
+                name: entry.path.clone(),
+                sha256: file_sha256(&path)?,
+                path,
+                size: entry.size,
+                folder: Some(folder_id.to_string()),
+                peer_id: None,
+                modified: entry.mtime,
+                updated_at: 0,
+            });
+        }
+        Ok(transfers)
+    }
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// Appliquer date de modification et permissions d'une entrée reçue
+/// (jamais de bits setuid/setgid/sticky)
+pub fn apply_metadata(path: &Path, entry: &FolderEntry) {
+    if let Some(mtime) = entry.mtime {
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime);
+        let result = std::fs::File::options()
+            .write(!entry.dir)
+            .read(entry.dir)
+            .open(path)
+            .and_then(|f| f.set_modified(time));
+        if let Err(e) = result {
+            warn!("Date de modification non appliquée à {}: {}", path.display(), e);
+        }
+    }
+
+    let Ok(mut permissions) = std::fs::metadata(path).map(|m| m.permissions()) else {
+        return;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        match entry.mode {
+            Some(mode) => permissions.set_mode(mode & 0o777),
+            None => permissions.set_readonly(entry.readonly),
+        }
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(entry.readonly);
+    if let Err(e) = std::fs::set_permissions(path, permissions) {
+        warn!("Permissions non appliquées à {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_paths_reject_traversal() {
+        assert_eq!(relative_path("logs/app.log").unwrap(), Path::new("logs").join("app.log"));
+        for bad in ["", "/etc/passwd", "../x", "a/../../b", "a//b", "a/./b", "C:/x", "a\\..\\b", "a/b\0"] {
+            assert!(relative_path(bad).is_err(), "{:?} accepté", bad);
+        }
+        let entry = |path: &str| FolderEntry {
+            path: path.to_string(), dir: false, size: 10, mtime: None, mode: None, readonly: false,
+        };
+        assert!(validate_manifest(&[entry("a"), entry("a")], 100).is_err(), "doublon");
+        assert!(validate_manifest(&[entry("a"), entry("b")], 15).is_err(), "trop gros");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_manifest_skips_symlinks() {
+        let root = std::env::temp_dir().join(format!("ghd-folder-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("logs")).unwrap();
+        std::fs::write(root.join("logs/app.log"), b"hello").unwrap();
+        std::os::unix::fs::symlink("/etc/passwd", root.join("passwd")).unwrap();
+
+        let manifest = FolderManifest::build(&root, 1000).unwrap();
+        let paths: Vec<_> = manifest.entries.iter().map(|e| (e.path.as_str(), e.dir)).collect();
+        assert_eq!(paths, vec![("logs", true), ("logs/app.log", false)]);
+        assert_eq!(manifest.total_size(), 5);
+
+        let transfers = manifest.transfers("fd-1").unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!((transfers[0].id.as_str(), transfers[0].name.as_str()), ("fd-1-1", "logs/app.log"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,156 @@
+//! Backends d'injection d'input
+//!
+//! `InputController` ne parle plus directement à enigo : il pilote un
+//! `InputBackend`. L'implémentation enigo est celle utilisée en production ;
+//! `RecordingBackend` enregistre les événements injectés en mémoire, ce qui
+//! permet de tester le mapping et la sécurité sans session graphique.
+
+use crate::error::{GhostHandError, Result};
+use enigo::{Axis, Button, Coordinate, Direction, Enigo, InputError, InputResult, Key, Keyboard, Mouse, Settings};
+use std::sync::{Arc, Mutex};
+
+/// Primitives d'injection clavier/souris utilisées par `InputController`
+pub trait InputBackend: Send {
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()>;
+    /// Touche physique par code natif de la plateforme (voir `keymap::platform_keycode`)
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()>;
+    /// `raw` est-il utilisable ; sinon le mode physique retombe sur les noms logiques US
+    fn supports_raw(&self) -> bool {
+        true
+    }
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()>;
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()>;
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()>;
+    fn text(&mut self, text: &str) -> InputResult<()>;
+}
+
+/// Backend par défaut : injection réelle via enigo
+pub struct EnigoBackend {
+    enigo: Enigo,
+}
+
+impl EnigoBackend {
+    /// Codes natifs injectables : le backend xdo d'enigo (Linux/X11) n'implémente
+    /// pas `raw` et panique (`todo!`) si on l'appelle
+    pub const RAW_KEYCODES: bool = !cfg!(target_os = "linux");
+
+    pub fn new() -> Result<Self> {
+        let enigo = Enigo::new(&Settings::default()).map_err(|e| {
+            GhostHandError::InputControl(format!("Failed to initialize input control: {}", e))
+        })?;
+        Ok(Self { enigo })
+    }
+}
+
+impl InputBackend for EnigoBackend {
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.enigo.key(key, direction)
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        if !Self::RAW_KEYCODES {
+            return Err(InputError::InvalidInput("codes de touche natifs non supportés sur cette plateforme"));
+        }
+        self.enigo.raw(keycode, direction)
+    }
+
+    fn supports_raw(&self) -> bool {
+        Self::RAW_KEYCODES
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        self.enigo.button(button, direction)
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        self.enigo.move_mouse(x, y, coordinate)
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.enigo.scroll(length, axis)
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.enigo.text(text)
+    }
+}
+
+/// Événement injecté, tel qu'observé par un `RecordingBackend`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectedEvent {
+    Key { key: Key, direction: Direction },
+    Raw { keycode: u16, direction: Direction },
+    Button { button: Button, direction: Direction },
+    MoveMouse { x: i32, y: i32, coordinate: Coordinate },
+    Scroll { length: i32, axis: Axis },
+    Text(String),
+}
+
+/// Backend d'enregistrement en mémoire (tests, démonstrations).
+/// Les clones partagent le même journal : garder un clone pour inspecter ce
+/// que le contrôleur a injecté.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingBackend {
+    events: Arc<Mutex<Vec<InjectedEvent>>>,
+    /// Simuler une plateforme sans codes natifs (`supports_raw`)
+    no_raw: bool,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Déclarer (ou non) le support des codes natifs, comme le ferait `EnigoBackend`
+    pub fn with_raw_support(mut self, supported: bool) -> Self {
+        self.no_raw = !supported;
+        self
+    }
+
+    /// Copie des événements enregistrés, dans l'ordre d'injection
+    pub fn events(&self) -> Vec<InjectedEvent> {
+        self.events.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    /// Retirer et renvoyer les événements enregistrés
+    pub fn take_events(&self) -> Vec<InjectedEvent> {
+        self.events.lock().map(|mut e| std::mem::take(&mut *e)).unwrap_or_default()
+    }
+
+    fn record(&self, event: InjectedEvent) -> InputResult<()> {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event);
+        }
+        Ok(())
+    }
+}
+
+impl InputBackend for RecordingBackend {
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.record(InjectedEvent::Key { key, direction })
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        self.record(InjectedEvent::Raw { keycode, direction })
+    }
+
+    fn supports_raw(&self) -> bool {
+        !self.no_raw
+    }
+
+    fn button(&mut self, button: Button, direction: Direction) -> InputResult<()> {
+        self.record(InjectedEvent::Button { button, direction })
+    }
+
+    fn move_mouse(&mut self, x: i32, y: i32, coordinate: Coordinate) -> InputResult<()> {
+        self.record(InjectedEvent::MoveMouse { x, y, coordinate })
+    }
+
+    fn scroll(&mut self, length: i32, axis: Axis) -> InputResult<()> {
+        self.record(InjectedEvent::Scroll { length, axis })
+    }
+
+    fn text(&mut self, text: &str) -> InputResult<()> {
+        self.record(InjectedEvent::Text(text.to_string()))
+    }
+}
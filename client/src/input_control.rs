@@ -1,65 +1,127 @@
 use crate::error::{GhostHandError, Result};
 use crate::audit::{audit_log, AuditEvent, AuditLevel};
-use enigo::{
-    Button, Coordinate, Direction, Enigo, Key, Keyboard, Mouse, Settings,
-};
+use crate::input_backend::{EnigoBackend, InputBackend};
+use crate::input_policy::{normalize_key, InputPolicy, KeySequence, MouseAction, PolicyAction, PolicyDecision};
+use crate::keymap::{hid_usage_name, platform_keycode};
+use crate::touch_input::{PointerAction, PointerKind, TouchContact, TouchMapper, TouchMapping};
+use enigo::{Button, Coordinate, Direction, Key};
+use crate::protocol::{ControlMessage, KeyInputMode, KeyModifiersProto, ScrollUnit};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, warn};
-
-// WHITELIST SÉCURITÉ: Touches système bloquées pour prévenir les actions dangereuses
-// Ces touches sont bloquées indépendamment des modifiers
-const BLOCKED_KEYS: &[&str] = &[
-    // Touches Windows système
-    "meta",       // Touche Windows seule
-    "super",      // Alias pour Meta
-    "windows",    // Alias pour Meta
-    "command",    // Alias pour Meta (macOS)
-];
-
-// Combinaisons de touches bloquées (modifier + touche)
-const BLOCKED_COMBINATIONS: &[(&str, &str)] = &[
-    // Windows dangereuses
-    ("meta", "r"),       // Win+R (Exécuter)
-    ("meta", "x"),       // Win+X (Menu système)
-    ("meta", "l"),       // Win+L (Verrouiller)
-    ("meta", "d"),       // Win+D (Bureau) - Désactivé pour éviter distraction
-    ("ctrl", "alt"),     // Ctrl+Alt (préfixe pour Ctrl+Alt+Del)
-
-    // Shortcuts système critiques
-    ("alt", "f4"),       // Alt+F4 (Fermer application)
-    ("ctrl", "shift"),   // Ctrl+Shift+Esc combo (si suivi de Esc)
-];
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 /// Input control manager for keyboard and mouse events
 pub struct InputController {
-    enigo: Enigo,
+    /// Backend d'injection (enigo en production, enregistrement en test)
+    backend: Box<dyn InputBackend>,
     screen_width: i32,
     screen_height: i32,
+    /// Origine de l'écran sélectionné dans le bureau virtuel (coordonnées normalisées)
+    display_origin: (i32, i32),
+    /// Reliquat de défilement haute résolution (1/120 de cran) non encore injecté
+    scroll_remainder: (i32, i32),
+    /// Politique d'input appliquée aux événements distants
+    policy: InputPolicy,
+    /// Pair à l'origine des événements (pour l'audit)
+    peer_id: Option<String>,
+    /// Touches enfoncées et non encore relâchées (ordre d'appui)
+    held_keys: Vec<HeldKey>,
+    /// Boutons souris enfoncés et non encore relâchés
+    held_buttons: Vec<MouseButton>,
+    /// Layout clavier annoncé par le viewer en mode physique (informatif)
+    viewer_layout: Option<String>,
+    /// Traduction des contacts tactiles/stylet en événements souris
+    touch: TouchMapper,
+    /// Séquences de touches approuvées par l'hôte (`SendKeySequence`)
+    key_sequences: Vec<KeySequence>,
+    /// Repli du mode physique sur les noms logiques déjà signalé dans les logs
+    physical_fallback_logged: bool,
+}
+
+/// Touche maintenue : logique (nom normalisé) ou physique (usage HID)
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HeldKey {
+    Logical(String),
+    Physical(u16),
+}
+
+impl HeldKey {
+    fn name(&self) -> &str {
+        match self {
+            HeldKey::Logical(name) => name,
+            HeldKey::Physical(usage) => hid_usage_name(*usage).unwrap_or("unknown"),
+        }
+    }
 }
 
 /// Represents a mouse event
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MouseEvent {
     Move { x: i32, y: i32 },
     Click { button: MouseButton },
     Press { button: MouseButton },
     Release { button: MouseButton },
     Scroll { delta_x: i32, delta_y: i32 },
+    /// Double-clic injecté d'un bloc (indépendant de la gigue réseau)
+    DoubleClick { button: MouseButton },
+    /// Position normalisée (0.0..=1.0) sur l'écran sélectionné
+    MoveNormalized { x: f64, y: f64 },
+    /// Défilement haute résolution, en 1/120 de cran (convention Windows/HID)
+    ScrollHighRes { delta_x: i32, delta_y: i32 },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// 4e bouton (précédent)
+    Back,
+    /// 5e bouton (suivant)
+    Forward,
 }
 
+impl MouseButton {
+    /// Nom utilisé dans le protocole et les règles de politique
+    pub fn name(self) -> &'static str {
+        match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+            MouseButton::Back => "back",
+            MouseButton::Forward => "forward",
+        }
+    }
+
+    /// Bouton correspondant à un nom du protocole (None si inconnu)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "left" => Some(MouseButton::Left),
+            "right" => Some(MouseButton::Right),
+            "middle" => Some(MouseButton::Middle),
+            "back" => Some(MouseButton::Back),
+            "forward" => Some(MouseButton::Forward),
+            _ => None,
+        }
+    }
+}
+
+/// Unités haute résolution par cran de molette
+pub const SCROLL_UNITS_PER_NOTCH: i32 = 120;
+
 /// Represents a keyboard event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum KeyboardEvent {
     Press { key: String },
     Release { key: String },
     Type { text: String },
+    /// Touche physique (usage USB HID), injectée indépendamment du layout hôte
+    PhysicalPress { usage: u16 },
+    PhysicalRelease { usage: u16 },
 }
 
 /// Keyboard modifiers (Ctrl, Shift, Alt, Meta)
@@ -72,37 +134,9 @@ pub struct KeyModifiers {
 }
 
 impl InputController {
-    /// Vérifier si une touche est bloquée par la whitelist de sécurité
+    /// Vérifier si une touche est bloquée par la politique d'input par défaut
     pub fn is_key_blocked(key: &str, modifiers: &KeyModifiers) -> bool {
-        let key_lower = key.to_lowercase();
-
-        // Bloquer les touches système individuelles
-        if BLOCKED_KEYS.contains(&key_lower.as_str()) {
-            return true;
-        }
-
-        // Bloquer les combinaisons dangereuses
-        for (modifier, blocked_key) in BLOCKED_COMBINATIONS {
-            let key_matches = key_lower == *blocked_key;
-            let modifier_active = match *modifier {
-                "meta" => modifiers.meta,
-                "ctrl" => modifiers.ctrl,
-                "alt" => modifiers.alt,
-                "shift" => modifiers.shift,
-                _ => false,
-            };
-
-            if key_matches && modifier_active {
-                return true;
-            }
-        }
-
-        // Bloquer Ctrl+Alt+Del spécifiquement
-        if modifiers.ctrl && modifiers.alt && (key_lower == "delete" || key_lower == "del") {
-            return true;
-        }
-
-        false
+        !InputPolicy::default().check_key(key, modifiers).is_allowed()
     }
 
     /// Create a new input controller with default resolution
@@ -112,11 +146,96 @@ impl InputController {
 
     /// Create a new input controller with specific screen resolution
     pub fn new_with_resolution(screen_width: i32, screen_height: i32) -> Result<Self> {
-        let enigo = Enigo::new(&Settings::default()).map_err(|e| {
-            GhostHandError::InputControl(format!("Failed to initialize input control: {}", e))
-        })?;
+        let backend = EnigoBackend::new()?;
+        Ok(Self::new_with_backend(Box::new(backend), screen_width, screen_height))
+    }
+
+    /// Create an input controller driving a custom injection backend
+    pub fn new_with_backend(backend: Box<dyn InputBackend>, screen_width: i32, screen_height: i32) -> Self {
+        Self {
+            backend,
+            screen_width,
+            screen_height,
+            display_origin: (0, 0),
+            scroll_remainder: (0, 0),
+            policy: InputPolicy::default(),
+            peer_id: None,
+            held_keys: Vec::new(),
+            held_buttons: Vec::new(),
+            viewer_layout: None,
+            touch: TouchMapper::default(),
+            key_sequences: Vec::new(),
+            physical_fallback_logged: false,
+        }
+    }
 
-        Ok(Self { enigo, screen_width, screen_height })
+    /// Appliquer une politique d'input et identifier le pair distant (audit)
+    pub fn with_policy(mut self, policy: InputPolicy, peer_id: Option<String>) -> Self {
+        self.policy = policy;
+        self.peer_id = peer_id;
+        self
+    }
+
+    /// Configurer la traduction des contacts tactiles et du stylet
+    pub fn with_touch_mapping(mut self, mapping: TouchMapping) -> Self {
+        self.touch = TouchMapper::new(mapping);
+        self
+    }
+
+    /// Séquences de touches que le pair peut déclencher malgré la politique
+    pub fn with_key_sequences(mut self, sequences: Vec<KeySequence>) -> Self {
+        self.key_sequences = sequences;
+        self
+    }
+
+    /// Pair à l'origine des événements (None si inconnu)
+    pub fn peer_id(&self) -> Option<&str> {
+        self.peer_id.as_deref()
+    }
+
+    /// Remplacer la politique d'input en cours de session
+    pub fn set_policy(&mut self, policy: InputPolicy) {
+        self.policy = policy;
+    }
+
+    /// Auditer un refus de la politique d'input et construire l'erreur associée
+    fn deny(&self, decision: &PolicyDecision, input: String) -> GhostHandError {
+        warn!("⚠️  SÉCURITÉ: Input refusé par la règle '{}': {}", decision.rule_name(), input);
+        audit_log(
+            AuditLevel::Security,
+            AuditEvent::InputPolicyDenied {
+                rule: decision.rule_name().to_string(),
+                input: input.clone(),
+                peer_id: self.peer_id.clone(),
+            },
+        );
+        GhostHandError::InputControl(format!(
+            "Input refusé par la politique ({}): {}",
+            decision.rule_name(),
+            input
+        ))
+    }
+
+    /// Vérifier un événement souris contre la politique
+    fn check_mouse_policy(&self, event: &MouseEvent) -> Result<()> {
+        let (action, button) = match event {
+            MouseEvent::Move { .. } => (MouseAction::Move, None),
+            MouseEvent::Click { button } => (MouseAction::Click, Some(*button)),
+            MouseEvent::Press { button } => (MouseAction::Press, Some(*button)),
+            MouseEvent::Release { button } => (MouseAction::Release, Some(*button)),
+            MouseEvent::Scroll { .. } | MouseEvent::ScrollHighRes { .. } => (MouseAction::Scroll, None),
+            MouseEvent::DoubleClick { button } => (MouseAction::Click, Some(*button)),
+            MouseEvent::MoveNormalized { .. } => (MouseAction::Move, None),
+        };
+        let decision = self.policy.check_mouse(action, button);
+        if decision.is_allowed() {
+            return Ok(());
+        }
+        let input = match button {
+            Some(b) => format!("mouse:{:?}:{:?}", action, b),
+            None => format!("mouse:{:?}", action),
+        };
+        Err(self.deny(&decision, input.to_lowercase()))
     }
 
     /// Update the screen resolution dynamically
@@ -126,8 +245,21 @@ impl InputController {
         debug!("InputController resolution updated: {}x{}", width, height);
     }
 
+    /// Position et taille de l'écran sélectionné (cible des coordonnées normalisées)
+    pub fn set_display_bounds(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.display_origin = (x, y);
+        self.set_resolution(width, height);
+    }
+
     /// Handle a mouse event
     pub fn handle_mouse_event(&mut self, event: MouseEvent) -> Result<()> {
+        // Le relâchement d'un bouton maintenu passe toujours, même si la politique
+        // a changé depuis l'appui : sinon le bouton resterait enfoncé sur l'hôte
+        let releases_held = matches!(event, MouseEvent::Release { button } if self.held_buttons.contains(&button));
+        if !releases_held {
+            self.check_mouse_policy(&event)?;
+        }
+
         match event {
             MouseEvent::Move { x, y } => {
                 // Normaliser les coordonnées pour éviter les débordements
@@ -140,7 +272,7 @@ impl InputController {
                 }
 
                 debug!("Mouse move to ({}, {})", clamped_x, clamped_y);
-                self.enigo
+                self.backend
                     .move_mouse(clamped_x, clamped_y, Coordinate::Abs)
                     .map_err(|e| {
                         GhostHandError::InputControl(format!("Failed to move mouse: {}", e))
@@ -148,180 +280,499 @@ impl InputController {
             }
             MouseEvent::Click { button } => {
                 debug!("Mouse click: {:?}", button);
-                let btn = Self::convert_button(button);
-                self.enigo.button(btn, Direction::Click).map_err(|e| {
+                let btn = Self::convert_button(button)?;
+                self.backend.button(btn, Direction::Click).map_err(|e| {
                     GhostHandError::InputControl(format!("Failed to click mouse: {}", e))
                 })?;
             }
             MouseEvent::Press { button } => {
                 debug!("Mouse press: {:?}", button);
-                let btn = Self::convert_button(button);
-                self.enigo.button(btn, Direction::Press).map_err(|e| {
+                let btn = Self::convert_button(button)?;
+                self.backend.button(btn, Direction::Press).map_err(|e| {
                     GhostHandError::InputControl(format!("Failed to press mouse: {}", e))
                 })?;
+                if !self.held_buttons.contains(&button) {
+                    self.held_buttons.push(button);
+                }
             }
             MouseEvent::Release { button } => {
                 debug!("Mouse release: {:?}", button);
-                let btn = Self::convert_button(button);
-                self.enigo.button(btn, Direction::Release).map_err(|e| {
+                let btn = Self::convert_button(button)?;
+                self.held_buttons.retain(|b| *b != button);
+                self.backend.button(btn, Direction::Release).map_err(|e| {
                     GhostHandError::InputControl(format!("Failed to release mouse: {}", e))
                 })?;
             }
             MouseEvent::Scroll { delta_x, delta_y } => {
                 debug!("Mouse scroll: dx={}, dy={}", delta_x, delta_y);
-                if delta_y != 0 {
-                    self.enigo.scroll(delta_y, enigo::Axis::Vertical).map_err(|e| {
-                        GhostHandError::InputControl(format!("Failed to scroll: {}", e))
+                self.scroll_lines(delta_x, delta_y)?;
+            }
+            MouseEvent::DoubleClick { button } => {
+                debug!("Mouse double click: {:?}", button);
+                let btn = Self::convert_button(button)?;
+                for _ in 0..2 {
+                    self.backend.button(btn, Direction::Click).map_err(|e| {
+                        GhostHandError::InputControl(format!("Failed to double click mouse: {}", e))
                     })?;
                 }
-                if delta_x != 0 {
-                    self.enigo.scroll(delta_x, enigo::Axis::Horizontal).map_err(|e| {
-                        GhostHandError::InputControl(format!("Failed to scroll: {}", e))
-                    })?;
+            }
+            MouseEvent::MoveNormalized { x, y } => {
+                if !x.is_finite() || !y.is_finite() {
+                    return Err(GhostHandError::InputControl(format!(
+                        "Coordonnées normalisées invalides: ({}, {})",
+                        x, y
+                    )));
                 }
+                // Relatif à l'écran sélectionné, indépendamment de la résolution streamée
+                let (origin_x, origin_y) = self.display_origin;
+                let px = (x.clamp(0.0, 1.0) * f64::from(self.screen_width - 1)).round() as i32;
+                let py = (y.clamp(0.0, 1.0) * f64::from(self.screen_height - 1)).round() as i32;
+                debug!("Mouse move (normalisé {:.4}, {:.4}) → ({}, {})", x, y, origin_x + px, origin_y + py);
+                self.backend
+                    .move_mouse(origin_x + px, origin_y + py, Coordinate::Abs)
+                    .map_err(|e| {
+                        GhostHandError::InputControl(format!("Failed to move mouse: {}", e))
+                    })?;
+            }
+            MouseEvent::ScrollHighRes { delta_x, delta_y } => {
+                // Cumuler les fractions de cran (trackpads, molettes libres) et
+                // n'injecter que les crans entiers
+                let total_x = self.scroll_remainder.0.saturating_add(delta_x);
+                let total_y = self.scroll_remainder.1.saturating_add(delta_y);
+                self.scroll_remainder = (total_x % SCROLL_UNITS_PER_NOTCH, total_y % SCROLL_UNITS_PER_NOTCH);
+                let (lines_x, lines_y) = (total_x / SCROLL_UNITS_PER_NOTCH, total_y / SCROLL_UNITS_PER_NOTCH);
+                debug!("Mouse scroll haute résolution: dx={}, dy={} → {} / {} crans", delta_x, delta_y, lines_x, lines_y);
+                self.scroll_lines(lines_x, lines_y)?;
             }
         }
         Ok(())
     }
 
-    /// Longueur maximale d'un texte injecté via `Type` (anti-abus / DoS)
-    const MAX_TYPE_LEN: usize = 8192;
+    fn scroll_lines(&mut self, delta_x: i32, delta_y: i32) -> Result<()> {
+        if delta_y != 0 {
+            self.backend.scroll(delta_y, enigo::Axis::Vertical).map_err(|e| {
+                GhostHandError::InputControl(format!("Failed to scroll: {}", e))
+            })?;
+        }
+        if delta_x != 0 {
+            self.backend.scroll(delta_x, enigo::Axis::Horizontal).map_err(|e| {
+                GhostHandError::InputControl(format!("Failed to scroll: {}", e))
+            })?;
+        }
+        Ok(())
+    }
 
-    /// Handle a keyboard event with optional modifiers
-    pub fn handle_keyboard_event(&mut self, event: KeyboardEvent, modifiers: KeyModifiers) -> Result<()> {
-        // SÉCURITÉ (F5): `Type` contournait toute la whitelist. On borne la taille et on
-        // rejette les octets nuls / caractères de contrôle non imprimables (hors \r\n\t)
-        // pour empêcher l'injection de séquences de contrôle via un pair malveillant.
-        if let KeyboardEvent::Type { ref text } = event {
-            if text.len() > Self::MAX_TYPE_LEN {
-                warn!("⚠️  SÉCURITÉ: Type rejeté (texte trop long: {} octets)", text.len());
-                return Err(GhostHandError::InputControl(
-                    "Texte injecté trop long".to_string(),
-                ));
+    /// Traiter un contact tactile ou stylet : chaque action produite passe par
+    /// `handle_mouse_event` (politique et audit identiques à la souris)
+    pub fn handle_touch_event(&mut self, kind: PointerKind, contact: TouchContact) -> Result<()> {
+        if !contact.x.is_finite() || !contact.y.is_finite() {
+            return Err(GhostHandError::InputControl(format!(
+                "Coordonnées de contact invalides: ({}, {})",
+                contact.x, contact.y
+            )));
+        }
+        let screen = (self.screen_width, self.screen_height);
+        for action in self.touch.map(kind, contact, screen) {
+            match action {
+                PointerAction::Mouse(event) => self.handle_mouse_event(event)?,
+                PointerAction::Zoom { notches } => self.zoom(notches)?,
             }
-            if text.chars().any(|c| c.is_control() && c != '\r' && c != '\n' && c != '\t') {
-                warn!("⚠️  SÉCURITÉ: Type rejeté (caractères de contrôle interdits)");
+        }
+        Ok(())
+    }
+
+    /// Zoom par pinch : molette avec Ctrl maintenu (sauf si Ctrl l'est déjà)
+    fn zoom(&mut self, notches: i32) -> Result<()> {
+        if self.held_modifiers().ctrl {
+            return self.handle_mouse_event(MouseEvent::Scroll { delta_x: 0, delta_y: notches });
+        }
+        self.handle_keyboard_event(KeyboardEvent::Press { key: "ctrl".to_string() }, KeyModifiers::default())?;
+        let scrolled = self.handle_mouse_event(MouseEvent::Scroll { delta_x: 0, delta_y: notches });
+        let released = self.handle_keyboard_event(
+            KeyboardEvent::Release { key: "ctrl".to_string() },
+            KeyModifiers::default(),
+        );
+        scrolled.and(released)
+    }
+
+    /// Injecter une séquence approuvée par l'hôte, d'un bloc : l'input maintenu est
+    /// d'abord relâché, puis les touches sont enfoncées dans l'ordre et relâchées
+    /// dans l'ordre inverse. Contourne la politique d'input, d'où l'audit systématique.
+    pub fn send_key_sequence(&mut self, name: &str) -> Result<()> {
+        let Some(sequence) = self.key_sequences.iter().find(|s| s.name == name).cloned() else {
+            let decision = PolicyDecision {
+                action: PolicyAction::Deny,
+                rule: Some("key-sequence-allowlist".to_string()),
+            };
+            let shown: String = name.chars().take(64).collect();
+            return Err(self.deny(&decision, format!("sequence:{}", shown)));
+        };
+        let keys = sequence
+            .keys
+            .iter()
+            .map(|k| {
+                Self::parse_key(k).ok_or_else(|| {
+                    GhostHandError::InputControl(format!("Séquence '{}': touche inconnue '{}'", name, k))
+                })
+            })
+            .collect::<Result<Vec<Key>>>()?;
+        if keys.is_empty() {
+            return Err(GhostHandError::InputControl(format!("Séquence '{}' vide", name)));
+        }
+
+        self.release_all()?;
+        let mut result = Ok(());
+        let mut pressed = 0;
+        for key in &keys {
+            if let Err(e) = self.backend.key(*key, Direction::Press) {
+                result = Err(GhostHandError::InputControl(format!("Failed to press key: {}", e)));
+                break;
+            }
+            pressed += 1;
+        }
+        // Toujours relâcher ce qui a été enfoncé, même après un échec
+        for key in keys[..pressed].iter().rev() {
+            if let Err(e) = self.backend.key(*key, Direction::Release) {
+                result = Err(GhostHandError::InputControl(format!("Failed to release key: {}", e)));
+            }
+        }
+
+        // Audit uniquement d'une séquence réellement injectée
+        match &result {
+            Ok(()) => {
+                warn!("🔐 Séquence de touches approuvée injectée: {}", name);
                 audit_log(
                     AuditLevel::Security,
-                    AuditEvent::SecurityError {
-                        error_code: "BLOCKED_TYPE".to_string(),
-                        description: "Injection de texte avec caractères de contrôle bloquée".to_string(),
-                        peer_id: None,
+                    AuditEvent::KeySequenceInjected {
+                        name: name.to_string(),
+                        peer_id: self.peer_id.clone(),
                     },
                 );
-                return Err(GhostHandError::InputControl(
-                    "Caractères de contrôle interdits dans le texte injecté".to_string(),
-                ));
             }
+            Err(e) => warn!("Séquence de touches '{}' non injectée: {}", name, e),
         }
+        result
+    }
 
-        // SÉCURITÉ: Vérifier la whitelist avant d'exécuter
-        if let KeyboardEvent::Press { ref key } | KeyboardEvent::Release { ref key } = event {
-            if Self::is_key_blocked(key, &modifiers) {
-                warn!("⚠️  SÉCURITÉ: Touche bloquée par whitelist: {} (modifiers: {:?})", key, modifiers);
+    /// Longueur maximale d'un texte injecté via `Type` (anti-abus / DoS)
+    pub const MAX_TYPE_LEN: usize = 8192;
 
-                // AUDIT: Logger la tentative bloquée
-                audit_log(
-                    AuditLevel::Security,
-                    AuditEvent::SecurityError {
-                        error_code: "BLOCKED_KEY".to_string(),
-                        description: format!(
-                            "Tentative d'utilisation d'une touche système bloquée: {} (ctrl={}, alt={}, shift={}, meta={})",
-                            key, modifiers.ctrl, modifiers.alt, modifiers.shift, modifiers.meta
-                        ),
-                        peer_id: None,
-                    },
-                );
+    /// Vérifier qu'un texte peut être injecté sur l'hôte, avec les modifiers
+    /// actuellement maintenus (sans jamais journaliser son contenu)
+    pub fn validate_text(&self, text: &str) -> Result<()> {
+        self.check_text(text, &self.held_modifiers())
+    }
 
-                return Err(GhostHandError::InputControl(
-                    format!("Touche système bloquée pour sécurité: {}", key)
-                ));
-            }
+    fn check_text(&self, text: &str, modifiers: &KeyModifiers) -> Result<()> {
+        // SÉCURITÉ (F5): `Type` contournait toute la whitelist. On borne la taille et on
+        // rejette les octets nuls / caractères de contrôle non imprimables (hors \r\n\t)
+        // pour empêcher l'injection de séquences de contrôle via un pair malveillant.
+        if text.len() > Self::MAX_TYPE_LEN {
+            warn!("⚠️  SÉCURITÉ: Type rejeté (texte trop long: {} octets)", text.len());
+            return Err(GhostHandError::InputControl(
+                "Texte injecté trop long".to_string(),
+            ));
+        }
+        if text.chars().any(|c| c.is_control() && c != '\r' && c != '\n' && c != '\t') {
+            warn!("⚠️  SÉCURITÉ: Type rejeté (caractères de contrôle interdits)");
+            audit_log(
+                AuditLevel::Security,
+                AuditEvent::SecurityError {
+                    error_code: "BLOCKED_TYPE".to_string(),
+                    description: "Injection de texte avec caractères de contrôle bloquée".to_string(),
+                    peer_id: self.peer_id.clone(),
+                },
+            );
+            return Err(GhostHandError::InputControl(
+                "Caractères de contrôle interdits dans le texte injecté".to_string(),
+            ));
         }
 
-        // Appliquer les modifiers AVANT la touche principale
-        if modifiers.ctrl {
-            self.enigo.key(Key::Control, Direction::Press).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to press Ctrl: {}", e))
-            })?;
+        let input = format!("text:{} chars", text.chars().count());
+        let decision = self.policy.check_text();
+        if !decision.is_allowed() {
+            return Err(self.deny(&decision, input));
         }
-        if modifiers.shift {
-            self.enigo.key(Key::Shift, Direction::Press).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to press Shift: {}", e))
-            })?;
+
+        // Avec un modifier maintenu, chaque caractère tapé devient un raccourci
+        // (Win+R, Alt+F4...) : on l'évalue comme tel
+        if modifiers.ctrl || modifiers.alt || modifiers.meta || modifiers.shift {
+            for c in text.chars() {
+                let decision = self.policy.check_key(&c.to_string(), modifiers);
+                if !decision.is_allowed() {
+                    return Err(self.deny(&decision, input));
+                }
+            }
         }
-        if modifiers.alt {
-            self.enigo.key(Key::Alt, Direction::Press).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to press Alt: {}", e))
-            })?;
+        Ok(())
+    }
+
+    /// Handle a keyboard event with optional modifiers
+    pub fn handle_keyboard_event(&mut self, event: KeyboardEvent, modifiers: KeyModifiers) -> Result<()> {
+        // SÉCURITÉ: la politique s'évalue sur l'état réel du clavier hôte pendant la
+        // touche : modifiers déjà maintenus + modifiers que l'on s'apprête à synthétiser.
+        // Un pair ne peut donc pas masquer un Ctrl maintenu en omettant `modifiers`.
+        let event = self.physical_fallback(event);
+        let held = self.held_modifiers();
+        let effective = KeyModifiers {
+            ctrl: held.ctrl || modifiers.ctrl,
+            shift: held.shift || modifiers.shift,
+            alt: held.alt || modifiers.alt,
+            meta: held.meta || modifiers.meta,
+        };
+        if let KeyboardEvent::Type { ref text } = event {
+            self.check_text(text, &effective)?;
         }
-        if modifiers.meta {
-            self.enigo.key(Key::Meta, Direction::Press).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to press Meta: {}", e))
+        // Nom logique de la touche principale (position US pour une touche physique)
+        let main_key = match &event {
+            KeyboardEvent::Press { key } | KeyboardEvent::Release { key } => Some(normalize_key(key)),
+            KeyboardEvent::PhysicalPress { usage } | KeyboardEvent::PhysicalRelease { usage } => {
+                match hid_usage_name(*usage) {
+                    Some(name) => Some(name.to_string()),
+                    None => {
+                        warn!("Usage HID inconnu: {:#06x}", usage);
+                        return Err(GhostHandError::InputControl(format!(
+                            "Usage HID inconnu: {:#06x}",
+                            usage
+                        )));
+                    }
+                }
+            }
+            KeyboardEvent::Type { .. } => None,
+        };
+        // Le relâchement d'une touche maintenue n'est pas soumis à la politique (un
+        // Alt relâché pendant que Ctrl est tenu resterait sinon enfoncé sur l'hôte)
+        // et ne synthétise aucun modifier
+        let releases_held = self.releases_held_key(&event);
+        if let Some(key) = main_key.as_ref().filter(|_| !releases_held) {
+            let decision = self.policy.check_key(key, &effective);
+            if !decision.is_allowed() {
+                return Err(self.deny(
+                    &decision,
+                    format!(
+                        "key:{} (ctrl={}, alt={}, shift={}, meta={})",
+                        key, effective.ctrl, effective.alt, effective.shift, effective.meta
+                    ),
+                ));
+            }
+        }
+
+        // Appliquer AVANT la touche principale les modifiers demandés qui ne sont pas
+        // déjà maintenus (ceux-là restent sous le contrôle de leurs propres KeyPress),
+        // ni la touche principale elle-même (KeyPress "Control" avec ctrl=true).
+        let synthesized: Vec<(Key, &str)> = [
+            (modifiers.ctrl && !held.ctrl, "ctrl", Key::Control, "Ctrl"),
+            (modifiers.shift && !held.shift, "shift", Key::Shift, "Shift"),
+            (modifiers.alt && !held.alt, "alt", Key::Alt, "Alt"),
+            (modifiers.meta && !held.meta, "meta", Key::Meta, "Meta"),
+        ]
+        .into_iter()
+        .filter(|(needed, name, _, _)| *needed && !releases_held && main_key.as_deref() != Some(*name))
+        .map(|(_, _, key, label)| (key, label))
+        .collect();
+        for (key, label) in &synthesized {
+            self.backend.key(*key, Direction::Press).map_err(|e| {
+                GhostHandError::InputControl(format!("Failed to press {}: {}", label, e))
             })?;
         }
 
         // Exécuter la touche principale
-        match event {
+        let result = match event {
             KeyboardEvent::Press { key } => {
                 debug!("Key press: {} (modifiers: {:?})", key, modifiers);
-                if let Some(k) = Self::parse_key(&key) {
-                    self.enigo.key(k, Direction::Press).map_err(|e| {
-                        GhostHandError::InputControl(format!("Failed to press key: {}", e))
-                    })?;
-                } else {
-                    warn!("Unknown key: {}", key);
+                match Self::parse_key(&key) {
+                    Some(k) => self.backend.key(k, Direction::Press)
+                        .map(|_| self.mark_key_held(HeldKey::Logical(normalize_key(&key))))
+                        .map_err(|e| GhostHandError::InputControl(format!("Failed to press key: {}", e))),
+                    None => {
+                        warn!("Unknown key: {}", key);
+                        Ok(())
+                    }
                 }
             }
             KeyboardEvent::Release { key } => {
                 debug!("Key release: {}", key);
-                if let Some(k) = Self::parse_key(&key) {
-                    self.enigo.key(k, Direction::Release).map_err(|e| {
+                self.mark_key_released(&HeldKey::Logical(normalize_key(&key)));
+                match Self::parse_key(&key) {
+                    Some(k) => self.backend.key(k, Direction::Release).map_err(|e| {
                         GhostHandError::InputControl(format!("Failed to release key: {}", e))
-                    })?;
-                } else {
-                    warn!("Unknown key: {}", key);
+                    }),
+                    None => {
+                        warn!("Unknown key: {}", key);
+                        Ok(())
+                    }
                 }
             }
             KeyboardEvent::Type { text } => {
-                debug!("Type text: {}", text);
-                self.enigo.text(&text).map_err(|e| {
+                debug!("Type text: {} caractère(s)", text.chars().count());
+                self.backend.text(&text).map_err(|e| {
                     GhostHandError::InputControl(format!("Failed to type text: {}", e))
-                })?;
+                })
             }
-        }
+            KeyboardEvent::PhysicalPress { usage } => {
+                debug!("Physical key press: {:#06x} (modifiers: {:?})", usage, modifiers);
+                match platform_keycode(usage) {
+                    Some(code) => self.backend.raw(code, Direction::Press)
+                        .map(|_| self.mark_key_held(HeldKey::Physical(usage)))
+                        .map_err(|e| GhostHandError::InputControl(format!("Failed to press key: {}", e))),
+                    None => Ok(()),
+                }
+            }
+            KeyboardEvent::PhysicalRelease { usage } => {
+                debug!("Physical key release: {:#06x}", usage);
+                self.mark_key_released(&HeldKey::Physical(usage));
+                match platform_keycode(usage) {
+                    Some(code) => self.backend.raw(code, Direction::Release).map_err(|e| {
+                        GhostHandError::InputControl(format!("Failed to release key: {}", e))
+                    }),
+                    None => Ok(()),
+                }
+            }
+        };
 
-        // Relâcher les modifiers APRÈS la touche principale (dans l'ordre inverse)
-        if modifiers.meta {
-            self.enigo.key(Key::Meta, Direction::Release).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to release Meta: {}", e))
+        // Relâcher les modifiers synthétisés APRÈS la touche principale (ordre inverse),
+        // y compris si elle a échoué — sinon ils resteraient enfoncés sur l'hôte
+        for (key, label) in synthesized.iter().rev() {
+            self.backend.key(*key, Direction::Release).map_err(|e| {
+                GhostHandError::InputControl(format!("Failed to release {}: {}", label, e))
             })?;
         }
-        if modifiers.alt {
-            self.enigo.key(Key::Alt, Direction::Release).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to release Alt: {}", e))
-            })?;
+
+        result
+    }
+
+    /// Sans codes natifs (xdo sous Linux), une touche physique est injectée sous
+    /// son nom logique US : la position n'est plus garantie sur un layout non US,
+    /// mais le clavier du viewer reste utilisable
+    fn physical_fallback(&mut self, event: KeyboardEvent) -> KeyboardEvent {
+        if self.backend.supports_raw() {
+            return event;
         }
-        if modifiers.shift {
-            self.enigo.key(Key::Shift, Direction::Release).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to release Shift: {}", e))
-            })?;
+        let (usage, press) = match event {
+            KeyboardEvent::PhysicalPress { usage } => (usage, true),
+            KeyboardEvent::PhysicalRelease { usage } => (usage, false),
+            other => return other,
+        };
+        // Usage inconnu : rejeté plus loin comme avec des codes natifs
+        let Some(name) = hid_usage_name(usage) else {
+            return if press { KeyboardEvent::PhysicalPress { usage } } else { KeyboardEvent::PhysicalRelease { usage } };
+        };
+        if !self.physical_fallback_logged {
+            warn!("Codes de touche natifs non supportés sur cet hôte : mode physique injecté selon le layout US");
+            self.physical_fallback_logged = true;
         }
-        if modifiers.ctrl {
-            self.enigo.key(Key::Control, Direction::Release).map_err(|e| {
-                GhostHandError::InputControl(format!("Failed to release Ctrl: {}", e))
-            })?;
+        let key = name.to_string();
+        if press { KeyboardEvent::Press { key } } else { KeyboardEvent::Release { key } }
+    }
+
+    /// L'événement relâche-t-il une touche maintenue sur l'hôte
+    pub fn releases_held_key(&self, event: &KeyboardEvent) -> bool {
+        match event {
+            KeyboardEvent::Release { key } => self.held_keys.contains(&HeldKey::Logical(normalize_key(key))),
+            KeyboardEvent::PhysicalRelease { usage } => self.held_keys.contains(&HeldKey::Physical(*usage)),
+            _ => false,
         }
+    }
 
-        Ok(())
+    /// Contact tactile ou stylet en cours sur l'hôte
+    pub fn is_touch_active(&self, kind: PointerKind, id: u32) -> bool {
+        self.touch.is_active(kind, id)
+    }
+
+    /// Touches actuellement maintenues sur l'hôte (noms normalisés, ordre d'appui)
+    pub fn held_keys(&self) -> Vec<String> {
+        self.held_keys.iter().map(|k| k.name().to_string()).collect()
+    }
+
+    /// Mémoriser le layout clavier annoncé par le viewer (mode physique)
+    pub fn set_viewer_layout(&mut self, layout: Option<String>) {
+        if layout.is_some() && layout != self.viewer_layout {
+            info!("Layout clavier du viewer: {:?}", layout);
+            self.viewer_layout = layout;
+        }
+    }
+
+    /// Boutons souris actuellement maintenus sur l'hôte
+    pub fn held_buttons(&self) -> &[MouseButton] {
+        &self.held_buttons
+    }
+
+    /// Modifiers effectivement maintenus (d'après les touches enfoncées)
+    pub fn held_modifiers(&self) -> KeyModifiers {
+        let held = |name: &str| self.held_keys.iter().any(|k| k.name() == name);
+        KeyModifiers {
+            ctrl: held("ctrl"),
+            shift: held("shift"),
+            alt: held("alt"),
+            meta: held("meta"),
+        }
+    }
+
+    fn mark_key_held(&mut self, key: HeldKey) {
+        if !self.held_keys.contains(&key) {
+            self.held_keys.push(key);
+        }
+    }
+
+    fn mark_key_released(&mut self, key: &HeldKey) {
+        self.held_keys.retain(|k| k != key);
+    }
+
+    /// Relâcher toutes les touches et boutons encore maintenus (fin de session,
+    /// erreur de transport, passage en lecture seule). Chaque relâchement est tenté
+    /// même si un précédent échoue ; la dernière erreur est renvoyée.
+    pub fn release_all(&mut self) -> Result<()> {
+        self.touch.reset();
+        if self.held_keys.is_empty() && self.held_buttons.is_empty() {
+            return Ok(());
+        }
+        info!(
+            "Relâchement de l'input maintenu: {} touche(s), {} bouton(s)",
+            self.held_keys.len(),
+            self.held_buttons.len()
+        );
+
+        let mut result = Ok(());
+        for button in std::mem::take(&mut self.held_buttons) {
+            let released = Self::convert_button(button).and_then(|btn| {
+                self.backend.button(btn, Direction::Release).map_err(|e| {
+                    GhostHandError::InputControl(format!("Failed to release mouse: {}", e))
+                })
+            });
+            if let Err(e) = released {
+                warn!("Échec relâchement bouton {:?}: {}", button, e);
+                result = Err(e);
+            }
+        }
+        for key in std::mem::take(&mut self.held_keys).into_iter().rev() {
+            let released = match &key {
+                HeldKey::Logical(name) => Self::parse_key(name).map(|k| self.backend.key(k, Direction::Release)),
+                HeldKey::Physical(usage) => platform_keycode(*usage).map(|c| self.backend.raw(c, Direction::Release)),
+            };
+            if let Some(Err(e)) = released {
+                warn!("Échec relâchement touche {}: {}", key.name(), e);
+                result = Err(GhostHandError::InputControl(format!("Failed to release key: {}", e)));
+            }
+        }
+        result
     }
 
     /// Convert MouseButton to enigo Button
-    fn convert_button(button: MouseButton) -> Button {
+    fn convert_button(button: MouseButton) -> Result<Button> {
         match button {
-            MouseButton::Left => Button::Left,
-            MouseButton::Right => Button::Right,
-            MouseButton::Middle => Button::Middle,
+            MouseButton::Left => Ok(Button::Left),
+            MouseButton::Right => Ok(Button::Right),
+            MouseButton::Middle => Ok(Button::Middle),
+            #[cfg(not(target_os = "macos"))]
+            MouseButton::Back => Ok(Button::Back),
+            #[cfg(not(target_os = "macos"))]
+            MouseButton::Forward => Ok(Button::Forward),
+            // enigo ne sait pas injecter les boutons 4/5 sous macOS
+            #[cfg(target_os = "macos")]
+            MouseButton::Back | MouseButton::Forward => Err(GhostHandError::InputControl(format!(
+                "Bouton {} non supporté sur cette plateforme",
+                button.name()
+            ))),
         }
     }
 
@@ -404,42 +855,286 @@ impl InputController {
     }
 }
 
-/// Input event listener (for capturing local input)
+impl Drop for InputController {
+    /// Filet de sécurité : ne jamais laisser de touche ou bouton enfoncé sur l'hôte
+    fn drop(&mut self) {
+        let _ = self.release_all();
+    }
+}
+
+/// Événement d'input local brut (côté viewer), tel que produit par une `InputSource`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RawInputEvent {
+    KeyDown { key: String },
+    KeyUp { key: String },
+    MouseMove { x: i32, y: i32 },
+    /// Position normalisée (0.0..=1.0) dans la vue du flux distant
+    MouseMoveNormalized { x: f64, y: f64 },
+    ButtonDown { button: MouseButton },
+    ButtonUp { button: MouseButton },
+    DoubleClick { button: MouseButton },
+    Wheel {
+        delta: i32,
+        #[serde(default)]
+        delta_x: i32,
+        #[serde(default)]
+        unit: ScrollUnit,
+    },
+    /// La fenêtre du viewer a gagné/perdu le focus
+    FocusChanged { focused: bool },
+}
+
+/// Source d'événements d'input locaux (fenêtre Tauri, script de test...)
+#[async_trait]
+pub trait InputSource: Send {
+    /// Prochain événement, None quand la source est épuisée/fermée
+    async fn next_event(&mut self) -> Option<RawInputEvent>;
+}
+
+/// Source alimentée par un canal (événements de la fenêtre viewer relayés par l'UI)
+pub struct ChannelInputSource {
+    rx: mpsc::UnboundedReceiver<RawInputEvent>,
+}
+
+impl ChannelInputSource {
+    /// Créer la source et l'émetteur à donner au producteur d'événements
+    pub fn new() -> (mpsc::UnboundedSender<RawInputEvent>, Self) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (tx, Self { rx })
+    }
+}
+
+#[async_trait]
+impl InputSource for ChannelInputSource {
+    async fn next_event(&mut self) -> Option<RawInputEvent> {
+        self.rx.recv().await
+    }
+}
+
+/// Source scriptée : rejoue une liste d'événements fixée à l'avance (tests)
+pub struct ScriptedInputSource {
+    events: VecDeque<RawInputEvent>,
+}
+
+impl ScriptedInputSource {
+    pub fn new(events: Vec<RawInputEvent>) -> Self {
+        Self { events: events.into() }
+    }
+}
+
+#[async_trait]
+impl InputSource for ScriptedInputSource {
+    async fn next_event(&mut self) -> Option<RawInputEvent> {
+        self.events.pop_front()
+    }
+}
+
+/// Traduction des événements locaux en messages de contrôle.
+/// Ne laisse passer que les événements reçus pendant que la fenêtre a le focus,
+/// et suit l'état des modifiers pour les joindre à chaque `KeyPress`.
+#[derive(Debug, Default)]
+pub struct InputTranslator {
+    focused: bool,
+    modifiers: KeyModifiers,
+    /// Touches enfoncées localement (pour les relâcher à distance à la perte de focus)
+    held_keys: Vec<String>,
+    held_buttons: Vec<MouseButton>,
+}
+
+impl InputTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Traduire un événement brut en zéro, un ou plusieurs messages
+    pub fn translate(&mut self, event: RawInputEvent) -> Vec<ControlMessage> {
+        match event {
+            RawInputEvent::FocusChanged { focused } => {
+                self.focused = focused;
+                if focused {
+                    Vec::new()
+                } else {
+                    // Perte de focus : les relâchements n'arriveront jamais à la fenêtre,
+                    // on les synthétise pour ne rien laisser enfoncé sur l'hôte
+                    self.release_all()
+                }
+            }
+            _ if !self.focused => Vec::new(),
+            RawInputEvent::KeyDown { key } => {
+                self.set_modifier(&key, true);
+                let name = normalize_key(&key);
+                if !self.held_keys.contains(&name) {
+                    self.held_keys.push(name);
+                }
+                vec![self.key_message(key, true)]
+            }
+            RawInputEvent::KeyUp { key } => {
+                self.set_modifier(&key, false);
+                let name = normalize_key(&key);
+                self.held_keys.retain(|k| *k != name);
+                vec![self.key_message(key, false)]
+            }
+            RawInputEvent::MouseMove { x, y } => vec![ControlMessage::MouseMove { x, y }],
+            RawInputEvent::MouseMoveNormalized { x, y } => {
+                vec![ControlMessage::MouseMoveNormalized { x, y }]
+            }
+            RawInputEvent::ButtonDown { button } => {
+                if !self.held_buttons.contains(&button) {
+                    self.held_buttons.push(button);
+                }
+                vec![Self::button_message(button, true)]
+            }
+            RawInputEvent::ButtonUp { button } => {
+                self.held_buttons.retain(|b| *b != button);
+                vec![Self::button_message(button, false)]
+            }
+            RawInputEvent::DoubleClick { button } => vec![ControlMessage::MouseDoubleClick {
+                button: button.name().to_string(),
+            }],
+            RawInputEvent::Wheel { delta, delta_x, unit } => {
+                vec![ControlMessage::MouseScroll { delta, delta_x, unit }]
+            }
+        }
+    }
+
+    /// Modifiers actuellement maintenus localement
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+
+    fn release_all(&mut self) -> Vec<ControlMessage> {
+        let mut messages: Vec<ControlMessage> = std::mem::take(&mut self.held_buttons)
+            .into_iter()
+            .map(|b| Self::button_message(b, false))
+            .collect();
+        for key in std::mem::take(&mut self.held_keys).into_iter().rev() {
+            self.set_modifier(&key, false);
+            messages.push(self.key_message(key, false));
+        }
+        messages
+    }
+
+    fn set_modifier(&mut self, key: &str, pressed: bool) {
+        match normalize_key(key).as_str() {
+            "ctrl" => self.modifiers.ctrl = pressed,
+            "shift" => self.modifiers.shift = pressed,
+            "alt" => self.modifiers.alt = pressed,
+            "meta" => self.modifiers.meta = pressed,
+            _ => {}
+        }
+    }
+
+    fn key_message(&self, key: String, pressed: bool) -> ControlMessage {
+        ControlMessage::KeyPress {
+            key,
+            pressed,
+            modifiers: Some(KeyModifiersProto {
+                ctrl: self.modifiers.ctrl,
+                shift: self.modifiers.shift,
+                alt: self.modifiers.alt,
+                meta: self.modifiers.meta,
+            }),
+            mode: KeyInputMode::Logical,
+            hid_usage: None,
+            layout: None,
+        }
+    }
+
+    fn button_message(button: MouseButton, pressed: bool) -> ControlMessage {
+        ControlMessage::MouseClick {
+            button: button.name().to_string(),
+            pressed,
+        }
+    }
+}
+
+/// Regroupement des `MouseMove` côté émetteur : seule la dernière position
+/// en attente part à chaque tick, au lieu d'un message par événement pointeur
+/// (évite l'accumulation de retard sur les liens lents).
+#[derive(Debug, Default)]
+pub struct MouseMoveCoalescer {
+    pending: std::sync::Mutex<Option<ControlMessage>>,
+    /// Nombre de positions écrasées avant envoi (statistiques)
+    coalesced: AtomicU64,
+    /// Réveille la tâche d'envoi quand une position est mise en attente
+    queued: tokio::sync::Notify,
+}
+
+impl MouseMoveCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistrer une nouvelle position (remplace la précédente non envoyée)
+    pub fn push(&self, x: i32, y: i32) {
+        self.replace(ControlMessage::MouseMove { x, y });
+    }
+
+    /// Enregistrer une position normalisée (0.0..=1.0) sur l'écran distant
+    pub fn push_normalized(&self, x: f64, y: f64) {
+        self.replace(ControlMessage::MouseMoveNormalized { x, y });
+    }
+
+    fn replace(&self, msg: ControlMessage) {
+        if let Ok(mut pending) = self.pending.lock() {
+            if pending.replace(msg).is_some() {
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.queued.notify_one();
+    }
+
+    /// Attendre qu'une position soit mise en attente (retour immédiat si une
+    /// position a été enregistrée depuis le dernier réveil)
+    pub async fn wait(&self) {
+        self.queued.notified().await;
+    }
+
+    /// Retirer la position en attente, sous forme de message prêt à envoyer
+    pub fn take(&self) -> Option<ControlMessage> {
+        self.pending.lock().ok().and_then(|mut pending| pending.take())
+    }
+
+    /// Nombre total de positions regroupées depuis la création
+    pub fn coalesced_count(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+}
+
+/// Capture de l'input local (côté viewer) : lit une `InputSource` et émet les
+/// `ControlMessage` correspondants vers le callback.
 pub struct InputListener {
-    // This would use rdev to listen to global input events
-    // Useful for the host side to capture and send events
+    source: Box<dyn InputSource>,
 }
 
 impl InputListener {
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    pub fn new(source: Box<dyn InputSource>) -> Self {
+        Self { source }
     }
 
-    /// Start listening to input events
-    pub async fn start<F>(&self, _callback: F) -> Result<()>
+    /// Démarrer l'écoute ; la tâche se termine quand la source est épuisée
+    pub fn start<F>(self, callback: F) -> JoinHandle<()>
     where
-        F: Fn(InputEvent) + Send + 'static,
+        F: Fn(ControlMessage) + Send + 'static,
     {
-        // In a real implementation, we'd use rdev::listen
-        // For now, this is a placeholder
+        let mut source = self.source;
         tokio::spawn(async move {
-            // rdev::listen implementation would go here
-            // This would capture all mouse and keyboard events
-        });
-
-        Ok(())
+            let mut translator = InputTranslator::new();
+            while let Some(event) = source.next_event().await {
+                for msg in translator.translate(event) {
+                    callback(msg);
+                }
+            }
+            debug!("InputListener: source d'input fermée");
+        })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum InputEvent {
-    Mouse(MouseEvent),
-    Keyboard(KeyboardEvent),
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input_backend::{InjectedEvent, RecordingBackend};
 
     #[test]
     fn test_input_controller_creation() {
@@ -506,4 +1201,372 @@ mod tests {
         modifiers2.alt = true;
         assert!(!InputController::is_key_blocked("tab", &modifiers2));
     }
+
+    fn recording_controller() -> (InputController, RecordingBackend) {
+        let backend = RecordingBackend::new();
+        let controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080);
+        (controller, backend)
+    }
+
+    fn key(key: Key, direction: Direction) -> InjectedEvent {
+        InjectedEvent::Key { key, direction }
+    }
+
+    #[test]
+    fn test_mouse_move_is_clamped_to_screen() {
+        let (mut controller, backend) = recording_controller();
+        controller.handle_mouse_event(MouseEvent::Move { x: -50, y: 5000 }).unwrap();
+        assert_eq!(
+            backend.take_events(),
+            vec![InjectedEvent::MoveMouse { x: 0, y: 1079, coordinate: Coordinate::Abs }]
+        );
+    }
+
+    #[test]
+    fn test_high_res_scroll_accumulates_fractions() {
+        let (mut controller, backend) = recording_controller();
+        for _ in 0..3 {
+            controller.handle_mouse_event(MouseEvent::ScrollHighRes { delta_x: 0, delta_y: 50 }).unwrap();
+        }
+        controller.handle_mouse_event(MouseEvent::ScrollHighRes { delta_x: -240, delta_y: 0 }).unwrap();
+
+        assert_eq!(
+            backend.events(),
+            vec![
+                InjectedEvent::Scroll { length: 1, axis: enigo::Axis::Vertical },
+                InjectedEvent::Scroll { length: -2, axis: enigo::Axis::Horizontal },
+            ]
+        );
+        assert!(controller.handle_mouse_event(MouseEvent::MoveNormalized { x: f64::NAN, y: 0.0 }).is_err());
+    }
+
+    #[test]
+    fn test_touch_goes_through_mouse_policy() {
+        use crate::input_policy::{InputMatcher, InputRule, PolicyAction};
+        use crate::protocol::TouchPhase;
+
+        let (controller, backend) = recording_controller();
+        let policy = InputPolicy {
+            default_action: PolicyAction::Allow,
+            rules: vec![InputRule {
+                name: "no-scroll".to_string(),
+                action: PolicyAction::Deny,
+                matcher: InputMatcher::Mouse { event: MouseAction::Scroll, button: None },
+            }],
+        };
+        let mapping = TouchMapping { two_finger_scroll: false, ..TouchMapping::default() };
+        let mut controller = controller.with_policy(policy, None).with_touch_mapping(mapping);
+        let contact = |id, phase, x| TouchContact { id, phase, x, y: 0.5, pressure: 1.0 };
+
+        controller.handle_touch_event(PointerKind::Touch, contact(1, TouchPhase::Start, 0.4)).unwrap();
+        assert_eq!(controller.held_buttons(), &[MouseButton::Left]);
+        controller.handle_touch_event(PointerKind::Touch, contact(2, TouchPhase::Start, 0.6)).unwrap();
+        assert!(controller.held_buttons().is_empty());
+        backend.take_events();
+
+        // Pinch : la molette est refusée par la règle, Ctrl est tout de même relâché
+        let pinch = controller.handle_touch_event(PointerKind::Touch, contact(2, TouchPhase::Move, 1.0));
+        assert!(pinch.is_err());
+        assert_eq!(
+            backend.events(),
+            vec![key(Key::Control, Direction::Press), key(Key::Control, Direction::Release)]
+        );
+        assert!(controller.held_keys().is_empty());
+    }
+
+    #[test]
+    fn test_key_sequence_requires_host_approval() {
+        let (controller, backend) = recording_controller();
+        let mut controller = controller.with_key_sequences(vec![KeySequence {
+            name: "ctrl-alt-del".to_string(),
+            keys: vec!["ctrl".to_string(), "alt".to_string(), "delete".to_string()],
+        }]);
+
+        // Bloqué par la politique en tant que chord, autorisé en tant que séquence nommée
+        assert!(InputController::is_key_blocked("delete", &KeyModifiers { ctrl: true, alt: true, ..Default::default() }));
+        controller.send_key_sequence("ctrl-alt-del").unwrap();
+        assert_eq!(
+            backend.take_events(),
+            vec![
+                key(Key::Control, Direction::Press),
+                key(Key::Alt, Direction::Press),
+                key(Key::Delete, Direction::Press),
+                key(Key::Delete, Direction::Release),
+                key(Key::Alt, Direction::Release),
+                key(Key::Control, Direction::Release),
+            ]
+        );
+
+        assert!(controller.send_key_sequence("ctrl-shift-esc").is_err());
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn test_blocked_chord_injects_nothing() {
+        let (mut controller, backend) = recording_controller();
+        let meta = KeyModifiers { meta: true, ..Default::default() };
+        assert!(controller
+            .handle_keyboard_event(KeyboardEvent::Press { key: "r".to_string() }, meta)
+            .is_err());
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn test_held_modifier_is_used_for_policy() {
+        let (mut controller, backend) = recording_controller();
+        let none = KeyModifiers::default();
+        controller.handle_keyboard_event(KeyboardEvent::Press { key: "Alt".to_string() }, none).unwrap();
+        // Le pair omet `modifiers`, mais Alt est réellement maintenu sur l'hôte
+        assert!(controller
+            .handle_keyboard_event(KeyboardEvent::Press { key: "f4".to_string() }, none)
+            .is_err());
+        assert_eq!(backend.take_events(), vec![key(Key::Alt, Direction::Press)]);
+    }
+
+    #[test]
+    fn test_modifiers_are_synthesized_around_key() {
+        let (mut controller, backend) = recording_controller();
+        let ctrl = KeyModifiers { ctrl: true, ..Default::default() };
+        controller.handle_keyboard_event(KeyboardEvent::Press { key: "c".to_string() }, ctrl).unwrap();
+        assert_eq!(
+            backend.take_events(),
+            vec![
+                key(Key::Control, Direction::Press),
+                key(Key::Unicode('c'), Direction::Press),
+                key(Key::Control, Direction::Release),
+            ]
+        );
+        assert_eq!(controller.held_keys(), ["c".to_string()]);
+    }
+
+    #[test]
+    fn test_held_key_release_bypasses_policy() {
+        let none = KeyModifiers::default();
+        for (first, second, first_key, second_key) in [
+            ("alt", "ctrl", Key::Alt, Key::Control),
+            ("shift", "ctrl", Key::Shift, Key::Control),
+        ] {
+            let (mut controller, backend) = recording_controller();
+            controller.handle_keyboard_event(KeyboardEvent::Press { key: first.to_string() }, none).unwrap();
+            controller.handle_keyboard_event(KeyboardEvent::Press { key: second.to_string() }, none).unwrap();
+            // Relâcher la première touche pendant que Ctrl est tenu : l'appui
+            // aurait été refusé (ctrl-alt-prefix / ctrl-shift-prefix), pas le relâchement
+            controller.handle_keyboard_event(KeyboardEvent::Release { key: first.to_string() }, none).unwrap();
+            controller.handle_keyboard_event(KeyboardEvent::Release { key: second.to_string() }, none).unwrap();
+            assert_eq!(
+                backend.take_events(),
+                vec![
+                    key(first_key, Direction::Press),
+                    key(second_key, Direction::Press),
+                    key(first_key, Direction::Release),
+                    key(second_key, Direction::Release),
+                ]
+            );
+            assert!(controller.held_keys().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_held_button_release_bypasses_policy() {
+        use crate::input_policy::{InputMatcher, InputRule, PolicyAction};
+
+        let (mut controller, backend) = recording_controller();
+        controller.handle_mouse_event(MouseEvent::Press { button: MouseButton::Left }).unwrap();
+        // Règle ajoutée pendant que le bouton est maintenu
+        let policy = InputPolicy {
+            default_action: PolicyAction::Allow,
+            rules: vec![InputRule {
+                name: "no-left".to_string(),
+                action: PolicyAction::Deny,
+                matcher: InputMatcher::Mouse { event: MouseAction::Release, button: Some("left".to_string()) },
+            }],
+        };
+        let mut controller = controller.with_policy(policy, None);
+        controller.handle_mouse_event(MouseEvent::Release { button: MouseButton::Left }).unwrap();
+        assert_eq!(
+            backend.take_events(),
+            vec![
+                InjectedEvent::Button { button: Button::Left, direction: Direction::Press },
+                InjectedEvent::Button { button: Button::Left, direction: Direction::Release },
+            ]
+        );
+        assert!(controller.held_buttons().is_empty());
+        // Un relâchement sans appui préalable reste soumis à la politique
+        assert!(controller.handle_mouse_event(MouseEvent::Release { button: MouseButton::Left }).is_err());
+    }
+
+    #[test]
+    fn test_release_all_releases_held_input() {
+        let (mut controller, backend) = recording_controller();
+        let none = KeyModifiers::default();
+        controller.handle_keyboard_event(KeyboardEvent::Press { key: "alt".to_string() }, none).unwrap();
+        controller.handle_keyboard_event(KeyboardEvent::Press { key: "shift".to_string() }, none).unwrap();
+        controller.handle_mouse_event(MouseEvent::Press { button: MouseButton::Left }).unwrap();
+        backend.take_events();
+
+        controller.release_all().unwrap();
+        assert_eq!(
+            backend.take_events(),
+            vec![
+                InjectedEvent::Button { button: Button::Left, direction: Direction::Release },
+                key(Key::Shift, Direction::Release),
+                key(Key::Alt, Direction::Release),
+            ]
+        );
+        assert!(controller.held_keys().is_empty());
+        assert!(controller.held_buttons().is_empty());
+
+        // Rien à relâcher une seconde fois
+        controller.release_all().unwrap();
+        assert!(backend.events().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_physical_mode_falls_back_to_logical_keys_without_native_keycodes() {
+        use crate::input_backend::EnigoBackend;
+
+        const { assert!(!EnigoBackend::RAW_KEYCODES) };
+        let backend = RecordingBackend::new().with_raw_support(EnigoBackend::RAW_KEYCODES);
+        let mut controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080);
+        let none = KeyModifiers::default();
+        // Usage 0x14 : injecté comme la touche US "q", jamais comme code natif
+        controller.handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0x14 }, none).unwrap();
+        assert_eq!(controller.held_keys(), ["q".to_string()]);
+        controller.handle_keyboard_event(KeyboardEvent::PhysicalRelease { usage: 0x14 }, none).unwrap();
+        assert!(controller.held_keys().is_empty());
+        assert_eq!(backend.take_events(), vec![
+            InjectedEvent::Key { key: Key::Unicode('q'), direction: Direction::Press },
+            InjectedEvent::Key { key: Key::Unicode('q'), direction: Direction::Release },
+        ]);
+        assert!(controller
+            .handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0x9999 }, none)
+            .is_err());
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn test_physical_keys_use_platform_keycodes() {
+        let (mut controller, backend) = recording_controller();
+        let none = KeyModifiers::default();
+        // Usage 0x14 : touche "A" d'un AZERTY, "Q" d'un QWERTY
+        controller.handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0x14 }, none).unwrap();
+        let code = platform_keycode(0x14).unwrap();
+        assert_eq!(backend.take_events(), vec![InjectedEvent::Raw { keycode: code, direction: Direction::Press }]);
+        assert_eq!(controller.held_keys(), ["q".to_string()]);
+
+        controller.release_all().unwrap();
+        assert_eq!(backend.take_events(), vec![InjectedEvent::Raw { keycode: code, direction: Direction::Release }]);
+
+        assert!(controller
+            .handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0x9999 }, none)
+            .is_err());
+    }
+
+    #[test]
+    fn test_physical_chords_are_checked_by_policy() {
+        let (mut controller, backend) = recording_controller();
+        let none = KeyModifiers::default();
+        controller.handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0xE2 }, none).unwrap();
+        // Alt gauche physique maintenu + F4
+        assert!(controller
+            .handle_keyboard_event(KeyboardEvent::PhysicalPress { usage: 0x3D }, none)
+            .is_err());
+        assert_eq!(backend.events().len(), 1);
+    }
+
+    #[test]
+    fn test_coalescer_keeps_latest_position() {
+        let coalescer = MouseMoveCoalescer::new();
+        assert!(coalescer.take().is_none());
+
+        for x in 0..10 {
+            coalescer.push(x, x * 2);
+        }
+        assert!(matches!(coalescer.take(), Some(ControlMessage::MouseMove { x: 9, y: 18 })));
+        assert!(coalescer.take().is_none());
+        assert_eq!(coalescer.coalesced_count(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_coalescer_wakes_sender_only_when_move_is_queued() {
+        let coalescer = std::sync::Arc::new(MouseMoveCoalescer::new());
+        let idle = tokio::time::timeout(std::time::Duration::from_millis(50), coalescer.wait()).await;
+        assert!(idle.is_err(), "aucun réveil sans mouvement");
+
+        let waiter = tokio::spawn({
+            let coalescer = coalescer.clone();
+            async move { coalescer.wait().await }
+        });
+        coalescer.push(1, 2);
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(coalescer.take().is_some());
+    }
+
+    fn key_down(key: &str) -> RawInputEvent {
+        RawInputEvent::KeyDown { key: key.to_string() }
+    }
+
+    #[test]
+    fn test_translator_ignores_input_without_focus() {
+        let mut translator = InputTranslator::new();
+        assert!(translator.translate(key_down("a")).is_empty());
+        assert!(translator.translate(RawInputEvent::MouseMove { x: 1, y: 1 }).is_empty());
+
+        translator.translate(RawInputEvent::FocusChanged { focused: true });
+        assert_eq!(translator.translate(RawInputEvent::MouseMove { x: 1, y: 1 }).len(), 1);
+    }
+
+    #[test]
+    fn test_translator_tracks_modifiers() {
+        let mut translator = InputTranslator::new();
+        translator.translate(RawInputEvent::FocusChanged { focused: true });
+        translator.translate(key_down("Control"));
+
+        match translator.translate(key_down("c")).pop() {
+            Some(ControlMessage::KeyPress { key, pressed, modifiers: Some(m), .. }) => {
+                assert_eq!(key, "c");
+                assert!(pressed);
+                assert!(m.ctrl && !m.shift && !m.alt && !m.meta);
+            }
+            other => panic!("message inattendu: {:?}", other),
+        }
+
+        translator.translate(RawInputEvent::KeyUp { key: "Control".to_string() });
+        assert!(!translator.modifiers().ctrl);
+    }
+
+    #[test]
+    fn test_translator_releases_held_input_on_focus_loss() {
+        let mut translator = InputTranslator::new();
+        translator.translate(RawInputEvent::FocusChanged { focused: true });
+        translator.translate(key_down("shift"));
+        translator.translate(RawInputEvent::ButtonDown { button: MouseButton::Left });
+
+        let released = translator.translate(RawInputEvent::FocusChanged { focused: false });
+        assert_eq!(released.len(), 2);
+        assert!(matches!(&released[0], ControlMessage::MouseClick { button, pressed: false } if button == "left"));
+        assert!(matches!(&released[1], ControlMessage::KeyPress { key, pressed: false, .. } if key == "shift"));
+        assert!(!translator.modifiers().shift);
+    }
+
+    #[tokio::test]
+    async fn test_listener_runs_scripted_source() {
+        let source = ScriptedInputSource::new(vec![
+            RawInputEvent::FocusChanged { focused: true },
+            RawInputEvent::MouseMove { x: 10, y: 20 },
+            RawInputEvent::Wheel { delta: -3, delta_x: 0, unit: ScrollUnit::Line },
+        ]);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        InputListener::new(Box::new(source))
+            .start(move |msg| {
+                let _ = tx.send(msg);
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(rx.recv().await, Some(ControlMessage::MouseMove { x: 10, y: 20 })));
+        assert!(matches!(rx.recv().await, Some(ControlMessage::MouseScroll { delta: -3, .. })));
+        assert!(rx.recv().await.is_none());
+    }
 }
@@ -0,0 +1,504 @@
+//! Macros d'input (côté viewer)
+//!
+//! Enregistrement des `ControlMessage` d'input sortants avec leur minutage
+//! relatif, sous forme de fichier JSON portable, puis rejeu sur le `Transport`
+//! courant avec contrôle de vitesse et interruption (touche d'arrêt).
+//! Les macros sont conservées dans les préférences du `Storage`.
+
+use crate::error::{GhostHandError, Result};
+use crate::input_policy::normalize_key;
+use crate::network::Transport;
+use crate::protocol::ControlMessage;
+use crate::storage::Storage;
+use crate::streaming::{real_session_key, SessionKeyHandle};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+/// Version du format de fichier
+pub const MACRO_FORMAT_VERSION: u32 = 1;
+/// Nombre maximal d'étapes d'une macro
+pub const MAX_MACRO_STEPS: usize = 10_000;
+/// Délai maximal entre deux étapes (au-delà, le délai est tronqué)
+pub const MAX_STEP_DELAY_MS: u64 = 60_000;
+/// Touche locale interrompant un rejeu en cours
+pub const MACRO_ABORT_KEY: &str = "Escape";
+
+/// Préfixe des clés de préférences contenant une macro
+const STORAGE_PREFIX: &str = "input_macro:";
+
+/// Étape d'une macro : message envoyé `delay_ms` après l'étape précédente
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub delay_ms: u64,
+    pub message: ControlMessage,
+}
+
+/// Macro d'input (format de fichier portable)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputMacro {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    pub name: String,
+    /// Date de création (Unix milliseconds)
+    #[serde(default)]
+    pub created_at: u64,
+    pub steps: Vec<MacroStep>,
+}
+
+fn default_version() -> u32 {
+    MACRO_FORMAT_VERSION
+}
+
+/// Messages pouvant figurer dans une macro : input distant uniquement, hors
+/// presse-papiers (son contenu n'a pas à être conservé dans un fichier)
+fn is_recordable(msg: &ControlMessage) -> bool {
+    msg.remote_input_kind().is_some() && !matches!(msg, ControlMessage::ClipboardSync { .. })
+}
+
+impl InputMacro {
+    /// Durée totale à vitesse normale
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.steps.iter().map(|s| s.delay_ms).sum())
+    }
+
+    /// Vérifier une macro venant d'un fichier ou du stockage : seul de l'input
+    /// distant peut être rejoué (jamais d'échange de clés, de transfert...)
+    pub fn validate(&self) -> Result<()> {
+        if self.version > MACRO_FORMAT_VERSION {
+            return Err(GhostHandError::Validation(format!(
+                "Macro '{}': version {} non supportée",
+                self.name, self.version
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(GhostHandError::Validation("Macro sans nom".to_string()));
+        }
+        if self.steps.len() > MAX_MACRO_STEPS {
+            return Err(GhostHandError::Validation(format!(
+                "Macro '{}': {} étapes (max {})",
+                self.name,
+                self.steps.len(),
+                MAX_MACRO_STEPS
+            )));
+        }
+        if let Some(step) = self.steps.iter().find(|s| !is_recordable(&s.message)) {
+            return Err(GhostHandError::Validation(format!(
+                "Macro '{}': message non rejouable {:?}",
+                self.name, step.message
+            )));
+        }
+        Ok(())
+    }
+
+    /// Charger une macro depuis un fichier JSON (validée)
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let input_macro: InputMacro = serde_json::from_str(&json)?;
+        input_macro.validate()?;
+        Ok(input_macro)
+    }
+
+    /// Écrire la macro dans un fichier JSON
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Enregistrer une macro dans les préférences du stockage (remplace une macro du même nom)
+pub fn store_macro(storage: &mut Storage, input_macro: &InputMacro) -> Result<()> {
+    input_macro.validate()?;
+    storage.set_preference(
+        format!("{}{}", STORAGE_PREFIX, input_macro.name),
+        serde_json::to_string(input_macro)?,
+    );
+    Ok(())
+}
+
+/// Lire une macro stockée (None si inconnue)
+pub fn load_stored_macro(storage: &Storage, name: &str) -> Result<Option<InputMacro>> {
+    let Some(json) = storage.get_preference(&format!("{}{}", STORAGE_PREFIX, name)) else {
+        return Ok(None);
+    };
+    let input_macro: InputMacro = serde_json::from_str(json)?;
+    input_macro.validate()?;
+    Ok(Some(input_macro))
+}
+
+/// Noms des macros stockées, triés
+pub fn stored_macro_names(storage: &Storage) -> Vec<String> {
+    let mut names: Vec<String> = storage
+        .preference_keys()
+        .filter_map(|k| k.strip_prefix(STORAGE_PREFIX).map(str::to_string))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Supprimer une macro stockée
+pub fn remove_stored_macro(storage: &mut Storage, name: &str) -> bool {
+    storage.remove_preference(&format!("{}{}", STORAGE_PREFIX, name)).is_some()
+}
+
+#[derive(Debug)]
+struct Recording {
+    name: String,
+    last: Instant,
+    steps: Vec<MacroStep>,
+}
+
+/// Enregistreur partagé : alimenté par chaque envoi d'input du viewer
+#[derive(Debug, Default)]
+pub struct MacroRecorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+impl MacroRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Démarrer un enregistrement (remplace un enregistrement en cours)
+    pub fn start(&self, name: &str) {
+        if let Ok(mut recording) = self.recording.lock() {
+            *recording = Some(Recording {
+                name: name.to_string(),
+                last: Instant::now(),
+                steps: Vec::new(),
+            });
+            info!("Enregistrement de macro démarré: {}", name);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.lock().map(|r| r.is_some()).unwrap_or(false)
+    }
+
+    /// Ajouter un message sortant (ignoré hors enregistrement ou s'il n'est pas rejouable)
+    pub fn record(&self, msg: &ControlMessage) {
+        if !is_recordable(msg) {
+            return;
+        }
+        let Ok(mut guard) = self.recording.lock() else { return };
+        let Some(recording) = guard.as_mut() else { return };
+        if recording.steps.len() >= MAX_MACRO_STEPS {
+            return;
+        }
+        let now = Instant::now();
+        let delay_ms = (now.duration_since(recording.last).as_millis() as u64).min(MAX_STEP_DELAY_MS);
+        recording.last = now;
+        recording.steps.push(MacroStep { delay_ms, message: msg.clone() });
+    }
+
+    /// Arrêter l'enregistrement et renvoyer la macro (None si aucun enregistrement)
+    pub fn stop(&self) -> Option<InputMacro> {
+        let recording = self.recording.lock().ok()?.take()?;
+        let mut steps = recording.steps;
+        // Le délai avant la première action n'a pas de sens au rejeu
+        if let Some(first) = steps.first_mut() {
+            first.delay_ms = 0;
+        }
+        info!("Enregistrement de macro terminé: {} ({} étapes)", recording.name, steps.len());
+        Some(InputMacro {
+            version: MACRO_FORMAT_VERSION,
+            name: recording.name,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            steps,
+        })
+    }
+}
+
+/// Poignée d'interruption d'un rejeu (partageable avec la capture clavier)
+#[derive(Debug, Clone, Default)]
+pub struct MacroAbortHandle {
+    aborted: Arc<AtomicBool>,
+}
+
+impl MacroAbortHandle {
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+}
+
+/// Issue d'un rejeu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackOutcome {
+    Completed { steps: usize },
+    Aborted { steps: usize },
+}
+
+/// Rejeu d'une macro
+#[derive(Debug, Clone)]
+pub struct MacroPlayer {
+    speed: f64,
+    abort: MacroAbortHandle,
+}
+
+impl Default for MacroPlayer {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            abort: MacroAbortHandle::default(),
+        }
+    }
+}
+
+impl MacroPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Facteur de vitesse (2.0 = deux fois plus vite), borné à 0.1..=10
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = if speed.is_finite() { speed.clamp(0.1, 10.0) } else { 1.0 };
+        self
+    }
+
+    pub fn abort_handle(&self) -> MacroAbortHandle {
+        self.abort.clone()
+    }
+
+    /// La touche locale est-elle la touche d'arrêt du rejeu ?
+    pub fn is_abort_hotkey(key: &str) -> bool {
+        normalize_key(key) == normalize_key(MACRO_ABORT_KEY)
+    }
+
+    /// Rejouer la macro via `send`. En cas d'interruption ou d'erreur d'envoi,
+    /// les touches et boutons laissés enfoncés par la macro sont relâchés.
+    pub async fn play<F, Fut>(&self, input_macro: &InputMacro, mut send: F) -> Result<PlaybackOutcome>
+    where
+        F: FnMut(ControlMessage) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        input_macro.validate()?;
+        info!("Rejeu de la macro '{}' (x{})", input_macro.name, self.speed);
+        let mut held: Vec<ControlMessage> = Vec::new();
+        let mut sent = 0;
+
+        for step in &input_macro.steps {
+            if !self.wait(step.delay_ms).await {
+                break;
+            }
+            track_held(&mut held, &step.message);
+            if let Err(e) = send(step.message.clone()).await {
+                warn!("Rejeu de macro interrompu par une erreur d'envoi: {}", e);
+                release_held(&mut held, &mut send).await;
+                return Err(e);
+            }
+            sent += 1;
+        }
+
+        if self.abort.is_aborted() {
+            info!("Rejeu de la macro '{}' interrompu après {} étapes", input_macro.name, sent);
+            release_held(&mut held, &mut send).await;
+            return Ok(PlaybackOutcome::Aborted { steps: sent });
+        }
+        // Macro incomplète (enregistrement arrêté pendant un appui) : ne rien laisser enfoncé
+        release_held(&mut held, &mut send).await;
+        Ok(PlaybackOutcome::Completed { steps: sent })
+    }
+
+    /// Rejouer sur un transport, chiffré avec la clé de session E2E si elle est prête
+    pub async fn play_over(
+        &self,
+        input_macro: &InputMacro,
+        transport: &Transport,
+        session_key: &SessionKeyHandle,
+    ) -> Result<PlaybackOutcome> {
+        self.play(input_macro, |msg| async move {
+            let bytes = msg.to_bytes()?;
+            let payload = match real_session_key(&*session_key.lock().await) {
+                Some(cipher) => cipher.seal(&bytes)?,
+                None => bytes,
+            };
+            transport.send_data(&payload).await
+        })
+        .await
+    }
+
+    /// Attendre le délai d'une étape ; false si le rejeu a été interrompu
+    async fn wait(&self, delay_ms: u64) -> bool {
+        let mut remaining = Duration::from_millis(delay_ms).div_f64(self.speed);
+        // Tranches courtes pour réagir rapidement à la touche d'arrêt
+        let slice = Duration::from_millis(20);
+        while !remaining.is_zero() {
+            if self.abort.is_aborted() {
+                return false;
+            }
+            let chunk = remaining.min(slice);
+            sleep(chunk).await;
+            remaining -= chunk;
+        }
+        !self.abort.is_aborted()
+    }
+}
+
+/// Suivre les appuis en attente de relâchement (message de relâchement associé)
+fn track_held(held: &mut Vec<ControlMessage>, msg: &ControlMessage) {
+    match msg {
+        ControlMessage::KeyPress { key, pressed, mode, hid_usage, layout, .. } => {
+            let same = |m: &ControlMessage| {
+                matches!(m, ControlMessage::KeyPress { key: k, hid_usage: u, .. } if k == key && u == hid_usage)
+            };
+            held.retain(|m| !same(m));
+            if *pressed {
+                held.push(ControlMessage::KeyPress {
+                    key: key.clone(),
+                    pressed: false,
+                    modifiers: None,
+                    mode: *mode,
+                    hid_usage: *hid_usage,
+                    layout: layout.clone(),
+                });
+            }
+        }
+        ControlMessage::MouseClick { button, pressed } => {
+            held.retain(|m| !matches!(m, ControlMessage::MouseClick { button: b, .. } if b == button));
+            if *pressed {
+                held.push(ControlMessage::MouseClick { button: button.clone(), pressed: false });
+            }
+        }
+        _ => {}
+    }
+}
+
+async fn release_held<F, Fut>(held: &mut Vec<ControlMessage>, send: &mut F)
+where
+    F: FnMut(ControlMessage) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    for msg in held.drain(..).rev() {
+        debug!("Macro : relâchement de {:?}", msg);
+        if let Err(e) = send(msg).await {
+            warn!("Macro : relâchement impossible: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ClipboardMime, KeyInputMode};
+
+    fn key(key: &str, pressed: bool) -> ControlMessage {
+        ControlMessage::KeyPress {
+            key: key.to_string(),
+            pressed,
+            modifiers: None,
+            mode: KeyInputMode::Logical,
+            hid_usage: None,
+            layout: None,
+        }
+    }
+
+    type Sent = Arc<Mutex<Vec<ControlMessage>>>;
+
+    fn collect() -> (Sent, impl FnMut(ControlMessage) -> std::future::Ready<Result<()>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        (sent, move |msg| {
+            sink.lock().unwrap().push(msg);
+            std::future::ready(Ok(()))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let recorder = MacroRecorder::new();
+        recorder.record(&key("a", true)); // hors enregistrement : ignoré
+        recorder.start("login");
+        recorder.record(&ControlMessage::MouseMove { x: 10, y: 20 });
+        recorder.record(&ControlMessage::Ping); // pas un input
+        recorder.record(&ControlMessage::ClipboardSync {
+            content: "secret".to_string(),
+            mime: ClipboardMime::TextPlain,
+            alt_text: None,
+        });
+        recorder.record(&key("a", true));
+        recorder.record(&key("a", false));
+        let input_macro = recorder.stop().unwrap();
+        assert!(!recorder.is_recording());
+        assert_eq!(input_macro.steps.len(), 3);
+        assert_eq!(input_macro.steps[0].delay_ms, 0);
+
+        // Aller-retour par le format fichier
+        let json = serde_json::to_string(&input_macro).unwrap();
+        let loaded: InputMacro = serde_json::from_str(&json).unwrap();
+
+        let (sent, send) = collect();
+        let outcome = MacroPlayer::new().with_speed(10.0).play(&loaded, send).await.unwrap();
+        assert_eq!(outcome, PlaybackOutcome::Completed { steps: 3 });
+        assert_eq!(sent.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_abort_releases_held_input() {
+        let input_macro = InputMacro {
+            version: MACRO_FORMAT_VERSION,
+            name: "drag".to_string(),
+            created_at: 0,
+            steps: vec![
+                MacroStep { delay_ms: 0, message: ControlMessage::MouseClick { button: "left".to_string(), pressed: true } },
+                MacroStep { delay_ms: 0, message: key("shift", true) },
+                MacroStep { delay_ms: 5_000, message: ControlMessage::MouseMove { x: 500, y: 500 } },
+            ],
+        };
+        let player = MacroPlayer::new();
+        let abort = player.abort_handle();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(50)).await;
+            abort.abort();
+        });
+
+        let (sent, send) = collect();
+        let outcome = player.play(&input_macro, send).await.unwrap();
+        assert_eq!(outcome, PlaybackOutcome::Aborted { steps: 2 });
+        let sent = sent.lock().unwrap();
+        assert!(matches!(&sent[2], ControlMessage::KeyPress { key, pressed: false, .. } if key == "shift"));
+        assert!(matches!(&sent[3], ControlMessage::MouseClick { pressed: false, .. }));
+        assert!(MacroPlayer::is_abort_hotkey("Esc"));
+    }
+
+    #[test]
+    fn test_macros_are_kept_in_storage_preferences() {
+        let dir = std::env::temp_dir().join("ghosthand_macro_store_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut storage = Storage::new(&dir, None).unwrap();
+        let input_macro = InputMacro {
+            version: MACRO_FORMAT_VERSION,
+            name: "reboot".to_string(),
+            created_at: 0,
+            steps: vec![MacroStep { delay_ms: 0, message: key("f5", true) }],
+        };
+
+        store_macro(&mut storage, &input_macro).unwrap();
+        storage.set_preference("theme".to_string(), "dark".to_string());
+        assert_eq!(stored_macro_names(&storage), vec!["reboot".to_string()]);
+        assert_eq!(load_stored_macro(&storage, "reboot").unwrap().unwrap().steps.len(), 1);
+        assert!(remove_stored_macro(&mut storage, "reboot"));
+        assert!(load_stored_macro(&storage, "reboot").unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validation_rejects_non_input_messages() {
+        let json = r#"{"name": "evil", "steps": [
+            {"delay_ms": 0, "message": {"type": "KeyExchangeInit", "public_key": [1, 2, 3]}}
+        ]}"#;
+        let input_macro: InputMacro = serde_json::from_str(json).unwrap();
+        assert!(input_macro.validate().is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Moteur de politique d'input (côté hôte)
+//!
+//! Remplace les listes figées de touches bloquées par des règles allow/deny
+//! chargées depuis la configuration. Les règles sont évaluées dans l'ordre :
+//! la première règle correspondante l'emporte, sinon `default_action` s'applique.
+//! Des règles propres à un pair peuvent être stockées sur son `KnownPeer` et
+//! sont alors évaluées avant les règles globales.
+
+use crate::input_control::{KeyModifiers, MouseButton};
+use crate::storage::KnownPeer;
+use serde::{Deserialize, Serialize};
+
+/// Décision d'une règle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
+}
+
+/// Action souris ciblée par une règle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseAction {
+    Move,
+    Click,
+    Press,
+    Release,
+    Scroll,
+}
+
+/// Condition de déclenchement d'une règle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputMatcher {
+    /// Touche seule, quels que soient les modifiers
+    Key { key: String },
+    /// Combinaison : tous les `modifiers` actifs + `key` (None = n'importe quelle touche)
+    Chord {
+        modifiers: Vec<String>,
+        #[serde(default)]
+        key: Option<String>,
+    },
+    /// Action souris, éventuellement limitée à un bouton
+    Mouse {
+        event: MouseAction,
+        #[serde(default)]
+        button: Option<String>,
+    },
+    /// Injection de texte (`TypeText`), quel que soit son contenu
+    Text,
+}
+
+/// Règle nommée (le nom est reporté dans l'audit en cas de refus)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputRule {
+    pub name: String,
+    pub action: PolicyAction,
+    #[serde(flatten)]
+    pub matcher: InputMatcher,
+}
+
+/// Séquence de touches nommée, approuvée par l'hôte (`SendKeySequence`).
+/// Injectée telle quelle même si la politique bloque la combinaison (Ctrl+Alt+Suppr,
+/// Ctrl+Maj+Échap...) : seules les séquences listées dans la configuration existent.
+/// Sous Windows, Ctrl+Alt+Suppr injecté n'ouvre l'écran sécurisé que si la
+/// stratégie SoftwareSASGeneration l'autorise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySequence {
+    pub name: String,
+    /// Touches enfoncées dans l'ordre, puis relâchées dans l'ordre inverse
+    pub keys: Vec<String>,
+}
+
+/// Résultat de l'évaluation d'un événement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    pub action: PolicyAction,
+    /// Nom de la règle correspondante (None = action par défaut)
+    pub rule: Option<String>,
+}
+
+impl PolicyDecision {
+    pub fn is_allowed(&self) -> bool {
+        self.action == PolicyAction::Allow
+    }
+
+    /// Nom de la règle pour l'audit (`"default"` si aucune règle n'a correspondu)
+    pub fn rule_name(&self) -> &str {
+        self.rule.as_deref().unwrap_or("default")
+    }
+}
+
+/// Politique d'input complète
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputPolicy {
+    /// Action appliquée quand aucune règle ne correspond
+    pub default_action: PolicyAction,
+    /// Règles évaluées dans l'ordre (première correspondance gagnante)
+    pub rules: Vec<InputRule>,
+}
+
+impl Default for InputPolicy {
+    /// Politique par défaut : reprend l'ancienne whitelist de sécurité
+    /// (Meta seule, Win+R/X/L/D, Alt+F4, préfixes Ctrl+Alt / Ctrl+Shift, Ctrl+Alt+Del).
+    fn default() -> Self {
+        let deny = |name: &str, matcher: InputMatcher| InputRule {
+            name: name.to_string(),
+            action: PolicyAction::Deny,
+            matcher,
+        };
+        let chord = |modifiers: &[&str], key: &str| InputMatcher::Chord {
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            key: Some(key.to_string()),
+        };
+
+        Self {
+            default_action: PolicyAction::Allow,
+            rules: vec![
+                deny("meta-key", InputMatcher::Key { key: "meta".to_string() }),
+                deny("win-run", chord(&["meta"], "r")),
+                deny("win-system-menu", chord(&["meta"], "x")),
+                deny("win-lock", chord(&["meta"], "l")),
+                deny("win-desktop", chord(&["meta"], "d")),
+                deny("ctrl-alt-prefix", chord(&["ctrl"], "alt")),
+                deny("alt-f4", chord(&["alt"], "f4")),
+                deny("ctrl-shift-prefix", chord(&["ctrl"], "shift")),
+                deny("ctrl-alt-del", chord(&["ctrl", "alt"], "delete")),
+            ],
+        }
+    }
+}
+
+/// Normaliser un nom de touche (minuscules + alias usuels)
+pub(crate) fn normalize_key(key: &str) -> String {
+    let lower = key.to_lowercase();
+    match lower.as_str() {
+        "control" => "ctrl".to_string(),
+        "super" | "windows" | "command" | "os" => "meta".to_string(),
+        "del" => "delete".to_string(),
+        "esc" => "escape".to_string(),
+        "return" => "enter".to_string(),
+        _ => lower,
+    }
+}
+
+fn modifier_active(name: &str, modifiers: &KeyModifiers) -> bool {
+    match normalize_key(name).as_str() {
+        "ctrl" => modifiers.ctrl,
+        "alt" => modifiers.alt,
+        "shift" => modifiers.shift,
+        "meta" => modifiers.meta,
+        _ => false,
+    }
+}
+
+impl InputPolicy {
+    /// Politique effective pour un pair : ses règles propres passent avant les règles globales
+    pub fn scoped_for_peer(&self, peer: Option<&KnownPeer>) -> InputPolicy {
+        let mut scoped = self.clone();
+        if let Some(peer) = peer {
+            if !peer.input_rules.is_empty() {
+                let mut rules = peer.input_rules.clone();
+                rules.extend(scoped.rules);
+                scoped.rules = rules;
+            }
+        }
+        scoped
+    }
+
+    /// Évaluer un événement clavier (touche + modifiers effectivement actifs)
+    pub fn check_key(&self, key: &str, modifiers: &KeyModifiers) -> PolicyDecision {
+        let key = normalize_key(key);
+        self.evaluate(|matcher| match matcher {
+            InputMatcher::Key { key: k } => normalize_key(k) == key,
+            InputMatcher::Chord { modifiers: mods, key: k } => {
+                let key_matches = k.as_ref().is_none_or(|k| normalize_key(k) == key);
+                key_matches && !mods.is_empty() && mods.iter().all(|m| modifier_active(m, modifiers))
+            }
+            InputMatcher::Mouse { .. } | InputMatcher::Text => false,
+        })
+    }
+
+    /// Évaluer une injection de texte
+    pub fn check_text(&self) -> PolicyDecision {
+        self.evaluate(|matcher| matches!(matcher, InputMatcher::Text))
+    }
+
+    /// Évaluer un événement souris
+    pub fn check_mouse(&self, action: MouseAction, button: Option<MouseButton>) -> PolicyDecision {
+        self.evaluate(|matcher| match matcher {
+            InputMatcher::Mouse { event, button: b } => {
+                *event == action
+                    && b.as_ref().is_none_or(|b| {
+                        button.is_some_and(|btn| b.eq_ignore_ascii_case(btn.name()))
+                    })
+            }
+            _ => false,
+        })
+    }
+
+    fn evaluate<F>(&self, matches: F) -> PolicyDecision
+    where
+        F: Fn(&InputMatcher) -> bool,
+    {
+        self.rules
+            .iter()
+            .find(|rule| matches(&rule.matcher))
+            .map(|rule| PolicyDecision {
+                action: rule.action,
+                rule: Some(rule.name.clone()),
+            })
+            .unwrap_or(PolicyDecision {
+                action: self.default_action,
+                rule: None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mods(ctrl: bool, shift: bool, alt: bool, meta: bool) -> KeyModifiers {
+        KeyModifiers { ctrl, shift, alt, meta }
+    }
+
+    #[test]
+    fn test_default_policy_matches_legacy_blocklist() {
+        let policy = InputPolicy::default();
+        let none = KeyModifiers::default();
+
+        assert!(!policy.check_key("super", &none).is_allowed());
+        assert!(!policy.check_key("r", &mods(false, false, false, true)).is_allowed());
+        assert!(!policy.check_key("del", &mods(true, false, true, false)).is_allowed());
+        assert!(!policy.check_key("f4", &mods(false, false, true, false)).is_allowed());
+
+        assert!(policy.check_key("c", &mods(true, false, false, false)).is_allowed());
+        assert!(policy.check_key("tab", &mods(false, false, true, false)).is_allowed());
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let mut policy = InputPolicy::default();
+        policy.rules.insert(0, InputRule {
+            name: "admin-ctrl-alt-del".to_string(),
+            action: PolicyAction::Allow,
+            matcher: InputMatcher::Chord {
+                modifiers: vec!["ctrl".to_string(), "alt".to_string()],
+                key: Some("delete".to_string()),
+            },
+        });
+
+        let decision = policy.check_key("delete", &mods(true, false, true, false));
+        assert!(decision.is_allowed());
+        assert_eq!(decision.rule_name(), "admin-ctrl-alt-del");
+    }
+
+    #[test]
+    fn test_mouse_rules_and_default_action() {
+        let policy = InputPolicy {
+            default_action: PolicyAction::Deny,
+            rules: vec![InputRule {
+                name: "move-only".to_string(),
+                action: PolicyAction::Allow,
+                matcher: InputMatcher::Mouse { event: MouseAction::Move, button: None },
+            }],
+        };
+
+        assert!(policy.check_mouse(MouseAction::Move, None).is_allowed());
+        let denied = policy.check_mouse(MouseAction::Press, Some(MouseButton::Right));
+        assert!(!denied.is_allowed());
+        assert_eq!(denied.rule_name(), "default");
+    }
+
+    #[test]
+    fn test_peer_rules_take_precedence() {
+        let policy = InputPolicy::default();
+        let peer = KnownPeer {
+            peer_id: "GHD-ADMIN".to_string(),
+            display_name: None,
+            last_seen: 0,
+            favorite: false,
+            connection_count: 0,
+            notes: None,
+            input_rules: vec![InputRule {
+                name: "allow-meta".to_string(),
+                action: PolicyAction::Allow,
+                matcher: InputMatcher::Key { key: "meta".to_string() },
+            }],
+            auto_accept_files: false,
+        };
+
+        let scoped = policy.scoped_for_peer(Some(&peer));
+        assert!(scoped.check_key("meta", &KeyModifiers::default()).is_allowed());
+        assert!(!policy.check_key("meta", &KeyModifiers::default()).is_allowed());
+    }
+
+    #[test]
+    fn test_policy_deserializes_from_config() {
+        let json = r#"{
+            "rules": [
+                { "name": "no-f4", "action": "deny", "kind": "key", "key": "F4" },
+                { "name": "no-right-click", "action": "deny", "kind": "mouse", "event": "press", "button": "right" },
+                { "name": "no-paste", "action": "deny", "kind": "text" }
+            ]
+        }"#;
+        let policy: InputPolicy = serde_json::from_str(json).unwrap();
+        assert_eq!(policy.default_action, PolicyAction::Allow);
+        assert!(!policy.check_key("f4", &KeyModifiers::default()).is_allowed());
+        assert!(!policy.check_mouse(MouseAction::Press, Some(MouseButton::Right)).is_allowed());
+        assert!(policy.check_mouse(MouseAction::Press, Some(MouseButton::Left)).is_allowed());
+        assert_eq!(policy.check_text().rule_name(), "no-paste");
+    }
+}
@@ -0,0 +1,176 @@
+//! Table des touches physiques (usages USB HID, page 0x07)
+//!
+//! Le mode physique transmet la position de la touche plutôt que le caractère :
+//! un viewer AZERTY qui appuie sur la touche "A" envoie l'usage 0x14 (position Q
+//! en QWERTY), et l'hôte injecte cette même position quel que soit son layout.
+//! Chaque usage est associé à son `KeyboardEvent.code` DOM (côté viewer), à un nom
+//! logique US (pour la politique d'input) et aux codes natifs de chaque plateforme.
+
+/// Bit signalant à enigo (Windows) un scancode étendu (préfixe E0)
+const EXT: u16 = 0xE000;
+
+struct KeyEntry {
+    usage: u16,
+    /// Nom logique (layout US), évalué par la politique d'input
+    name: &'static str,
+    /// `KeyboardEvent.code` côté navigateur
+    dom_code: &'static str,
+    /// Code evdev Linux (le keycode X11 vaut evdev + 8)
+    evdev: u16,
+    /// Scancode PS/2 set 1 (Windows), `EXT` pour les touches étendues
+    set1: u16,
+    /// Virtual keycode macOS (kVK_*)
+    mac: u16,
+}
+
+macro_rules! keys {
+    ($(($usage:expr, $name:expr, $dom:expr, $evdev:expr, $set1:expr, $mac:expr)),* $(,)?) => {
+        &[$(KeyEntry { usage: $usage, name: $name, dom_code: $dom, evdev: $evdev, set1: $set1, mac: $mac }),*]
+    };
+}
+
+static KEYS: &[KeyEntry] = keys![
+    // Lettres
+    (0x04, "a", "KeyA", 30, 0x1E, 0x00),
+    (0x05, "b", "KeyB", 48, 0x30, 0x0B),
+    (0x06, "c", "KeyC", 46, 0x2E, 0x08),
+    (0x07, "d", "KeyD", 32, 0x20, 0x02),
+    (0x08, "e", "KeyE", 18, 0x12, 0x0E),
+    (0x09, "f", "KeyF", 33, 0x21, 0x03),
+    (0x0A, "g", "KeyG", 34, 0x22, 0x05),
+    (0x0B, "h", "KeyH", 35, 0x23, 0x04),
+    (0x0C, "i", "KeyI", 23, 0x17, 0x22),
+    (0x0D, "j", "KeyJ", 36, 0x24, 0x26),
+    (0x0E, "k", "KeyK", 37, 0x25, 0x28),
+    (0x0F, "l", "KeyL", 38, 0x26, 0x25),
+    (0x10, "m", "KeyM", 50, 0x32, 0x2E),
+    (0x11, "n", "KeyN", 49, 0x31, 0x2D),
+    (0x12, "o", "KeyO", 24, 0x18, 0x1F),
+    (0x13, "p", "KeyP", 25, 0x19, 0x23),
+    (0x14, "q", "KeyQ", 16, 0x10, 0x0C),
+    (0x15, "r", "KeyR", 19, 0x13, 0x0F),
+    (0x16, "s", "KeyS", 31, 0x1F, 0x01),
+    (0x17, "t", "KeyT", 20, 0x14, 0x11),
+    (0x18, "u", "KeyU", 22, 0x16, 0x20),
+    (0x19, "v", "KeyV", 47, 0x2F, 0x09),
+    (0x1A, "w", "KeyW", 17, 0x11, 0x0D),
+    (0x1B, "x", "KeyX", 45, 0x2D, 0x07),
+    (0x1C, "y", "KeyY", 21, 0x15, 0x10),
+    (0x1D, "z", "KeyZ", 44, 0x2C, 0x06),
+    // Chiffres (rangée du haut)
+    (0x1E, "1", "Digit1", 2, 0x02, 0x12),
+    (0x1F, "2", "Digit2", 3, 0x03, 0x13),
+    (0x20, "3", "Digit3", 4, 0x04, 0x14),
+    (0x21, "4", "Digit4", 5, 0x05, 0x15),
+    (0x22, "5", "Digit5", 6, 0x06, 0x17),
+    (0x23, "6", "Digit6", 7, 0x07, 0x16),
+    (0x24, "7", "Digit7", 8, 0x08, 0x1A),
+    (0x25, "8", "Digit8", 9, 0x09, 0x1C),
+    (0x26, "9", "Digit9", 10, 0x0A, 0x19),
+    (0x27, "0", "Digit0", 11, 0x0B, 0x1D),
+    // Contrôle et ponctuation
+    (0x28, "enter", "Enter", 28, 0x1C, 0x24),
+    (0x29, "escape", "Escape", 1, 0x01, 0x35),
+    (0x2A, "backspace", "Backspace", 14, 0x0E, 0x33),
+    (0x2B, "tab", "Tab", 15, 0x0F, 0x30),
+    (0x2C, "space", "Space", 57, 0x39, 0x31),
+    (0x2D, "-", "Minus", 12, 0x0C, 0x1B),
+    (0x2E, "=", "Equal", 13, 0x0D, 0x18),
+    (0x2F, "[", "BracketLeft", 26, 0x1A, 0x21),
+    (0x30, "]", "BracketRight", 27, 0x1B, 0x1E),
+    (0x31, "\\", "Backslash", 43, 0x2B, 0x2A),
+    (0x33, ";", "Semicolon", 39, 0x27, 0x29),
+    (0x34, "'", "Quote", 40, 0x28, 0x27),
+    (0x35, "`", "Backquote", 41, 0x29, 0x32),
+    (0x36, ",", "Comma", 51, 0x33, 0x2B),
+    (0x37, ".", "Period", 52, 0x34, 0x2F),
+    (0x38, "/", "Slash", 53, 0x35, 0x2C),
+    (0x39, "capslock", "CapsLock", 58, 0x3A, 0x39),
+    // Touches de fonction
+    (0x3A, "f1", "F1", 59, 0x3B, 0x7A),
+    (0x3B, "f2", "F2", 60, 0x3C, 0x78),
+    (0x3C, "f3", "F3", 61, 0x3D, 0x63),
+    (0x3D, "f4", "F4", 62, 0x3E, 0x76),
+    (0x3E, "f5", "F5", 63, 0x3F, 0x60),
+    (0x3F, "f6", "F6", 64, 0x40, 0x61),
+    (0x40, "f7", "F7", 65, 0x41, 0x62),
+    (0x41, "f8", "F8", 66, 0x42, 0x64),
+    (0x42, "f9", "F9", 67, 0x43, 0x65),
+    (0x43, "f10", "F10", 68, 0x44, 0x6D),
+    (0x44, "f11", "F11", 87, 0x57, 0x67),
+    (0x45, "f12", "F12", 88, 0x58, 0x6F),
+    // Navigation
+    (0x49, "insert", "Insert", 110, EXT | 0x52, 0x72),
+    (0x4A, "home", "Home", 102, EXT | 0x47, 0x73),
+    (0x4B, "pageup", "PageUp", 104, EXT | 0x49, 0x74),
+    (0x4C, "delete", "Delete", 111, EXT | 0x53, 0x75),
+    (0x4D, "end", "End", 107, EXT | 0x4F, 0x77),
+    (0x4E, "pagedown", "PageDown", 109, EXT | 0x51, 0x79),
+    (0x4F, "right", "ArrowRight", 106, EXT | 0x4D, 0x7C),
+    (0x50, "left", "ArrowLeft", 105, EXT | 0x4B, 0x7B),
+    (0x51, "down", "ArrowDown", 108, EXT | 0x50, 0x7D),
+    (0x52, "up", "ArrowUp", 103, EXT | 0x48, 0x7E),
+    // Touche ISO à gauche de Z (< > sur AZERTY/QWERTZ)
+    (0x64, "<", "IntlBackslash", 86, 0x56, 0x0A),
+    // Modifiers (gauche puis droite)
+    (0xE0, "ctrl", "ControlLeft", 29, 0x1D, 0x3B),
+    (0xE1, "shift", "ShiftLeft", 42, 0x2A, 0x38),
+    (0xE2, "alt", "AltLeft", 56, 0x38, 0x3A),
+    (0xE3, "meta", "MetaLeft", 125, EXT | 0x5B, 0x37),
+    (0xE4, "ctrl", "ControlRight", 97, EXT | 0x1D, 0x3E),
+    (0xE5, "shift", "ShiftRight", 54, 0x36, 0x3C),
+    (0xE6, "alt", "AltRight", 100, EXT | 0x38, 0x3D),
+    (0xE7, "meta", "MetaRight", 126, EXT | 0x5C, 0x36),
+];
+
+fn entry(usage: u16) -> Option<&'static KeyEntry> {
+    KEYS.iter().find(|k| k.usage == usage)
+}
+
+/// Nom logique (layout US) d'un usage HID, utilisé par la politique d'input
+pub fn hid_usage_name(usage: u16) -> Option<&'static str> {
+    entry(usage).map(|k| k.name)
+}
+
+/// Usage HID correspondant à un `KeyboardEvent.code` DOM ("KeyA", "ShiftLeft"...)
+pub fn hid_usage_from_dom_code(code: &str) -> Option<u16> {
+    KEYS.iter().find(|k| k.dom_code == code).map(|k| k.usage)
+}
+
+/// Code natif de la plateforme courante pour `Keyboard::raw` d'enigo
+pub fn platform_keycode(usage: u16) -> Option<u16> {
+    let k = entry(usage)?;
+    if cfg!(target_os = "windows") {
+        Some(k.set1)
+    } else if cfg!(target_os = "macos") {
+        Some(k.mac)
+    } else {
+        // X11 : keycode = evdev + 8
+        Some(k.evdev + 8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usages_are_unique() {
+        for (i, a) in KEYS.iter().enumerate() {
+            assert!(KEYS[i + 1..].iter().all(|b| b.usage != a.usage), "usage dupliqué {:#x}", a.usage);
+            assert!(KEYS[i + 1..].iter().all(|b| b.dom_code != a.dom_code), "code dupliqué {}", a.dom_code);
+        }
+    }
+
+    #[test]
+    fn test_dom_code_round_trip() {
+        // La touche "A" d'un AZERTY a le code DOM "KeyQ" : même position physique
+        assert_eq!(hid_usage_from_dom_code("KeyQ"), Some(0x14));
+        assert_eq!(hid_usage_name(0x14), Some("q"));
+        assert_eq!(hid_usage_name(hid_usage_from_dom_code("F4").unwrap()), Some("f4"));
+        assert_eq!(hid_usage_name(0xE7), Some("meta"));
+        assert!(hid_usage_from_dom_code("Unknown").is_none());
+        assert!(platform_keycode(0x04).is_some());
+        assert!(platform_keycode(0xFFFF).is_none());
+    }
+}
@@ -5,16 +5,27 @@
 pub mod adaptive_bitrate;
 pub mod audit;
 pub mod clipboard;
+pub mod clipboard_filter;
+pub mod clipboard_watcher;
 pub mod config;
 pub mod crypto;
 pub mod error;
+pub mod file_browser;
+pub mod file_sender;
 pub mod file_transfer;
+pub mod folder_transfer;
+pub mod input_backend;
 pub mod input_control;
+pub mod input_macro;
+pub mod input_policy;
+pub mod keymap;
 pub mod network;
 pub mod protocol;
 pub mod screen_capture;
 pub mod storage;
 pub mod streaming;
+pub mod touch_input;
+pub mod transfer_queue;
 pub mod validation;
 pub mod video_encoder;
 
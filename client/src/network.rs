@@ -385,13 +385,114 @@ impl Drop for SignalingClient {
     }
 }
 
+/// Taille max d'un message du data channel (60KB - safe limit, WebRTC max ~256KB) ;
+/// au-delà, le message est fragmenté
+pub const MAX_CHUNK_SIZE: usize = 60 * 1024;
+/// Taille max d'un message réassemblé (protège contre une annonce de longueur démesurée)
+const MAX_REASSEMBLED_SIZE: usize = 32 * 1024 * 1024;
+
+/// Émetteur de messages fragmentés, partagé par tous les clones d'un transport.
+/// Le récepteur ne réassemble qu'un message à la fois : les fragments d'un
+/// message partent donc d'un bloc, sans fragments ni messages d'un autre envoi
+/// entre eux (transferts concurrents, vidéo, presse-papiers...).
+#[derive(Clone, Default)]
+pub struct FragmentingSender {
+    lock: Arc<Mutex<()>>,
+}
+
+impl FragmentingSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Envoyer `data` via `send`, fragmenté si besoin
+    pub async fn send<F, Fut>(&self, data: &[u8], mut send: F) -> Result<()>
+    where
+        F: FnMut(Bytes) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let messages = Self::fragments(data);
+        let _guard = self.lock.lock().await;
+        for msg in messages {
+            send(msg).await?;
+        }
+        Ok(())
+    }
+
+    /// Découper un message : tel quel s'il tient dans `MAX_CHUNK_SIZE`, sinon
+    /// un en-tête `[0xFF][0x01][total_len: u32 LE]` suivi de `[0xFF][0x02][data...]`
+    pub fn fragments(data: &[u8]) -> Vec<Bytes> {
+        if data.len() <= MAX_CHUNK_SIZE {
+            return vec![Bytes::from(data.to_vec())];
+        }
+        let mut messages = Vec::with_capacity(1 + data.len().div_ceil(MAX_CHUNK_SIZE));
+        let mut header = Vec::with_capacity(6);
+        header.push(0xFF);
+        header.push(0x01);
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        messages.push(Bytes::from(header));
+        for chunk in data.chunks(MAX_CHUNK_SIZE) {
+            let mut chunk_msg = Vec::with_capacity(2 + chunk.len());
+            chunk_msg.push(0xFF);
+            chunk_msg.push(0x02);
+            chunk_msg.extend_from_slice(chunk);
+            messages.push(Bytes::from(chunk_msg));
+        }
+        messages
+    }
+}
+
+/// Réassemblage des messages fragmentés par `FragmentingSender`, à appliquer
+/// AVANT le déchiffrement : la fragmentation porte sur la trame déjà scellée
+#[derive(Debug, Default)]
+pub struct FrameReassembler {
+    pending: Option<(usize, Vec<u8>)>,
+}
+
+impl FrameReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Message complet, ou None tant qu'un message fragmenté est incomplet
+    /// (les fragments orphelins ou mal formés sont écartés)
+    pub fn push(&mut self, raw: Vec<u8>) -> Option<Vec<u8>> {
+        if raw.len() < 2 || raw[0] != 0xFF {
+            return Some(raw);
+        }
+        match raw[1] {
+            0x01 if raw.len() >= 6 => {
+                let total_len = u32::from_le_bytes([raw[2], raw[3], raw[4], raw[5]]) as usize;
+                if total_len > MAX_REASSEMBLED_SIZE {
+                    warn!("Message fragmenté trop volumineux annoncé ({} octets), ignoré", total_len);
+                    self.pending = None;
+                    return None;
+                }
+                self.pending = Some((total_len, Vec::with_capacity(total_len)));
+                None
+            }
+            0x02 => {
+                let (expected_len, buffer) = self.pending.as_mut()?;
+                buffer.extend_from_slice(&raw[2..]);
+                if buffer.len() >= *expected_len {
+                    self.pending.take().map(|(_, buffer)| buffer)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
 /// WebRTC peer connection manager
 #[derive(Clone)]
 pub struct WebRTCConnection {
     pub peer_connection: Arc<webrtc::peer_connection::RTCPeerConnection>,
     data_channel: Arc<RwLock<Option<Arc<webrtc::data_channel::RTCDataChannel>>>>,
+    sender: FragmentingSender,
     #[allow(dead_code)]
-    config: Config,
+    config: Arc<Config>,
 }
 
 impl WebRTCConnection {
@@ -451,7 +552,8 @@ impl WebRTCConnection {
         Ok(Self {
             peer_connection,
             data_channel: Arc::new(RwLock::new(None)),
-            config,
+            sender: FragmentingSender::new(),
+            config: Arc::new(config),
         })
     }
 
@@ -571,50 +673,26 @@ impl WebRTCConnection {
         Ok(())
     }
 
-    /// Send data over the data channel
-    /// Taille max d'un chunk WebRTC (60KB - safe limit, WebRTC max ~256KB)
-    const MAX_CHUNK_SIZE: usize = 60 * 1024;
-
+    /// Send data over the data channel (fragmenté au-delà de `MAX_CHUNK_SIZE`)
     pub async fn send_data(&self, data: &[u8]) -> Result<()> {
-        let dc_lock = self.data_channel.read().await;
-
-        if let Some(dc) = dc_lock.as_ref() {
-            if data.len() <= Self::MAX_CHUNK_SIZE {
-                // Message petit : envoi direct
-                dc.send(&Bytes::from(data.to_vec()))
+        let dc = self.data_channel.read().await.clone()
+            .ok_or_else(|| GhostHandError::WebRTC("Data channel non disponible".into()))?;
+        self.sender.send(data, |msg| {
+            let dc = dc.clone();
+            async move {
+                dc.send(&msg)
                     .await
-                    .map_err(|e| GhostHandError::WebRTC(format!("Erreur d'envoi de données: {}", e)))?;
-            } else {
-                // Message trop gros : fragmenter
-                // PERF: Pré-construire tous les buffers puis les envoyer en batch
-                let mut messages: Vec<Bytes> = Vec::new();
-
-                // Header: [0xFF][0x01][total_len: u32 LE]
-                let mut header = Vec::with_capacity(6);
-                header.push(0xFF);
-                header.push(0x01);
-                header.extend_from_slice(&(data.len() as u32).to_le_bytes());
-                messages.push(Bytes::from(header));
-
-                // Chunks de données: [0xFF][0x02][data...]
-                for chunk in data.chunks(Self::MAX_CHUNK_SIZE) {
-                    let mut chunk_msg = Vec::with_capacity(2 + chunk.len());
-                    chunk_msg.push(0xFF);
-                    chunk_msg.push(0x02);
-                    chunk_msg.extend_from_slice(chunk);
-                    messages.push(Bytes::from(chunk_msg));
-                }
-
-                // Envoyer tous les messages d'un coup (un seul verrouillage du DC)
-                for msg in messages {
-                    dc.send(&msg)
-                        .await
-                        .map_err(|e| GhostHandError::WebRTC(format!("Erreur envoi chunk: {}", e)))?;
-                }
+                    .map(|_| ())
+                    .map_err(|e| GhostHandError::WebRTC(format!("Erreur d'envoi de données: {}", e)))
             }
-            Ok(())
-        } else {
-            Err(GhostHandError::WebRTC("Data channel non disponible".into()))
+        }).await
+    }
+
+    /// Octets en attente d'envoi dans le tampon du data channel
+    pub async fn buffered_amount(&self) -> usize {
+        match self.data_channel.read().await.as_ref() {
+            Some(dc) => dc.buffered_amount().await,
+            None => 0,
         }
     }
 
@@ -758,6 +836,15 @@ impl Transport {
         }
     }
 
+    /// Octets en attente d'envoi côté transport. Le relais n'expose pas son
+    /// tampon : seuls les acquittements du pair le régulent.
+    pub async fn buffered_amount(&self) -> usize {
+        match self {
+            Transport::WebRTC(w) => w.buffered_amount().await,
+            Transport::Relay(_) => 0,
+        }
+    }
+
     /// Retourner l'incoming_tx pour le relay (None si WebRTC)
     pub fn relay_incoming_tx(&self) -> Option<mpsc::UnboundedSender<Vec<u8>>> {
         match self {
@@ -1394,4 +1481,66 @@ mod tests {
         assert_eq!(session.device_id, device_id);
         assert_eq!(session.config.server_url, config.server_url);
     }
+
+    #[test]
+    fn test_fragments_roundtrip() {
+        let small = vec![7u8; 100];
+        assert_eq!(FragmentingSender::fragments(&small).len(), 1);
+
+        let large: Vec<u8> = (0..MAX_CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+        let messages = FragmentingSender::fragments(&large);
+        assert_eq!(messages.len(), 4);
+
+        let mut reassembler = FrameReassembler::new();
+        let mut out = None;
+        for msg in messages {
+            assert!(out.is_none());
+            out = reassembler.push(msg.to_vec());
+        }
+        assert_eq!(out.unwrap(), large);
+    }
+
+    #[test]
+    fn test_reassembler_rejects_oversized_announce() {
+        let mut reassembler = FrameReassembler::new();
+        let mut header = vec![0xFF, 0x01];
+        header.extend_from_slice(&((MAX_REASSEMBLED_SIZE + 1) as u32).to_le_bytes());
+        assert!(reassembler.push(header).is_none());
+        // Fragment orphelin : écarté
+        assert!(reassembler.push(vec![0xFF, 0x02, 1, 2, 3]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fragmented_sends_do_not_interleave() {
+        let sender = FragmentingSender::new();
+        let sent = Arc::new(std::sync::Mutex::new(Vec::<Bytes>::new()));
+        let a = vec![0xAAu8; MAX_CHUNK_SIZE * 3];
+        let b = vec![0xBBu8; MAX_CHUNK_SIZE * 3];
+
+        let send = |data: Vec<u8>| {
+            let sender = sender.clone();
+            let sent = sent.clone();
+            tokio::spawn(async move {
+                sender.send(&data, |msg| {
+                    let sent = sent.clone();
+                    async move {
+                        sent.lock().unwrap().push(msg);
+                        tokio::task::yield_now().await;
+                        Ok(())
+                    }
+                }).await
+            })
+        };
+        let (ra, rb) = tokio::join!(send(a.clone()), send(b.clone()));
+        ra.unwrap().unwrap();
+        rb.unwrap().unwrap();
+
+        let mut reassembler = FrameReassembler::new();
+        let complete: Vec<Vec<u8>> = sent.lock().unwrap().iter()
+            .filter_map(|msg| reassembler.push(msg.to_vec()))
+            .collect();
+        assert_eq!(complete.len(), 2);
+        assert!(complete.contains(&a));
+        assert!(complete.contains(&b));
+    }
 }
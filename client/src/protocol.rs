@@ -11,6 +11,60 @@ pub struct DisplayInfoProto {
     pub is_primary: bool,
 }
 
+/// Entrée du manifeste d'un dossier envoyé (`FolderTransferStart`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderEntry {
+    /// Chemin relatif à la racine du dossier, composants séparés par `/`
+    pub path: String,
+    /// Sous-dossier (sinon fichier)
+    #[serde(default)]
+    pub dir: bool,
+    #[serde(default)]
+    pub size: u64,
+    /// Date de modification (secondes Unix)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    /// Permissions Unix (bits `rwx` uniquement)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    #[serde(default)]
+    pub readonly: bool,
+}
+
+/// Entrée d'un dossier distant listé par `ListDirectory`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    #[serde(default)]
+    pub dir: bool,
+    #[serde(default)]
+    pub size: u64,
+    /// Date de modification (secondes Unix)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u64>,
+    /// Modifiable par le viewer (racine en lecture-écriture)
+    #[serde(default)]
+    pub writable: bool,
+}
+
+/// Résultat d'une requête de navigation distante (`FileBrowserResponse`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FileBrowserResult {
+    /// Contenu d'un dossier (ou liste des racines si `root` est vide)
+    Listing {
+        entries: Vec<RemoteEntry>,
+        /// Liste tronquée à `MAX_LISTING_ENTRIES`
+        #[serde(default)]
+        truncated: bool,
+    },
+    /// `ReadFile` accepté : le fichier suit comme un transfert ordinaire
+    Transfer { transfer_id: String },
+    /// Opération d'écriture effectuée
+    Done,
+    Error { message: String },
+}
+
 /// Modifiers clavier transmis avec les événements KeyPress
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct KeyModifiersProto {
@@ -24,6 +78,80 @@ pub struct KeyModifiersProto {
     pub meta: bool,
 }
 
+/// Mode d'interprétation d'un `KeyPress`, choisi par le viewer pour la session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyInputMode {
+    /// Caractère/nom de touche (`key`), interprété avec le layout de l'hôte
+    #[default]
+    Logical,
+    /// Position physique (`hid_usage`), indépendante des layouts. Un hôte Linux
+    /// (enigo/xdo, sans codes natifs) injecte le nom logique US de la touche.
+    Physical,
+}
+
+/// Phase d'un contact tactile ou stylet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TouchPhase {
+    Start,
+    Move,
+    End,
+    /// Contact interrompu par le système (pas de relâchement à la position courante)
+    Cancel,
+}
+
+/// Type MIME d'un contenu de presse-papiers synchronisé
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClipboardMime {
+    /// Texte brut (comportement historique)
+    #[default]
+    #[serde(rename = "text/plain")]
+    TextPlain,
+    #[serde(rename = "text/html")]
+    TextHtml,
+    /// Image PNG, transmise en base64 dans `content`
+    #[serde(rename = "image/png")]
+    ImagePng,
+}
+
+impl ClipboardMime {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipboardMime::TextPlain => "text/plain",
+            ClipboardMime::TextHtml => "text/html",
+            ClipboardMime::ImagePng => "image/png",
+        }
+    }
+}
+
+/// Unité des deltas d'un `MouseScroll`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollUnit {
+    /// Crans de molette (comportement historique)
+    #[default]
+    Line,
+    /// Pixels (`WheelEvent.deltaMode` = 0 dans un navigateur)
+    Pixel,
+    /// 1/120 de cran (molettes libres, trackpads)
+    HighRes,
+}
+
+/// Pixels de défilement navigateur correspondant à un cran de molette
+pub const SCROLL_PIXELS_PER_LINE: i32 = 100;
+
+impl ScrollUnit {
+    /// Convertir un delta exprimé dans cette unité en 1/120 de cran
+    pub fn to_high_res(self, delta: i32) -> i32 {
+        match self {
+            ScrollUnit::Line => delta.saturating_mul(120),
+            ScrollUnit::Pixel => delta.saturating_mul(120) / SCROLL_PIXELS_PER_LINE,
+            ScrollUnit::HighRes => delta,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ControlMessage {
@@ -49,23 +177,85 @@ pub enum ControlMessage {
         x: i32,
         y: i32,
     },
+    /// Position normalisée (0.0..=1.0) sur l'écran sélectionné de l'hôte,
+    /// indépendante de la résolution du flux (downscale `SetResolution`)
+    MouseMoveNormalized {
+        x: f64,
+        y: f64,
+    },
     MouseClick {
         button: String,
         pressed: bool,
     },
+    /// Double-clic, injecté d'un bloc par l'hôte
+    MouseDoubleClick {
+        button: String,
+    },
     MouseScroll {
+        /// Défilement vertical (positif = vers le bas)
         delta: i32,
+        /// Défilement horizontal (positif = vers la droite)
+        #[serde(default)]
+        delta_x: i32,
+        #[serde(default)]
+        unit: ScrollUnit,
     },
     KeyPress {
         key: String,
         pressed: bool,
         #[serde(default)]
         modifiers: Option<KeyModifiersProto>,
+        #[serde(default)]
+        mode: KeyInputMode,
+        /// Usage USB HID (page 0x07) de la touche, requis en mode physique
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hid_usage: Option<u16>,
+        /// Layout clavier du viewer ("fr-FR", "de-DE"...), à titre indicatif
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        layout: Option<String>,
+    },
+    /// Contact tactile (un message par doigt), position normalisée sur l'écran sélectionné
+    Touch {
+        id: u32,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        pressure: f32,
+    },
+    /// Stylet ; un `Move` hors contact (après `End`) correspond au survol
+    Pen {
+        #[serde(default)]
+        id: u32,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        pressure: f32,
+    },
+    /// Séquence de touches nommée, définie et approuvée par l'hôte (Ctrl+Alt+Suppr...)
+    SendKeySequence {
+        name: String,
+    },
+    /// Saisie d'un texte complet (collage, Unicode, texte composé par IME)
+    TypeText {
+        text: String,
+        /// Délai entre deux fragments en ms (0 = saisie d'un seul bloc)
+        #[serde(default)]
+        pacing_ms: u32,
     },
 
     // Clipboard sync
+    /// Contenu du presse-papiers ; les trames volumineuses (images) passent par
+    /// la fragmentation du transport, dans la limite de `MAX_CLIPBOARD_BYTES`
     ClipboardSync {
+        /// Texte, HTML ou PNG encodé en base64 selon `mime`
         content: String,
+        #[serde(default)]
+        mime: ClipboardMime,
+        /// Texte de repli d'un contenu HTML (applications sans support HTML)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alt_text: Option<String>,
     },
 
     // File transfer
@@ -73,14 +263,95 @@ pub enum ControlMessage {
         id: String,
         name: String,
         size: u64,
+        /// SHA-256 (hex) du fichier entier, s'il est connu dès le départ
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+        /// Dossier (`FolderTransferStart`) auquel appartient le fichier ; `name`
+        /// est alors son chemin relatif dans le manifeste
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        folder: Option<String>,
     },
     FileTransferChunk {
         id: String,
         data: Vec<u8>,
         offset: u64,
+        /// SHA-256 (hex) du chunk, vérifié avant écriture
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
     },
     FileTransferComplete {
         id: String,
+        /// SHA-256 (hex) du fichier entier, si non annoncé dans `FileTransferStart`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sha256: Option<String>,
+    },
+    /// Annonce d'un dossier : le manifeste complet précède les fichiers, envoyés
+    /// ensuite comme des transferts ordinaires rattachés au dossier
+    FolderTransferStart {
+        id: String,
+        name: String,
+        entries: Vec<FolderEntry>,
+    },
+    /// Envoyé par le destinataire après reconnexion : plages `[début, fin)` encore
+    /// manquantes d'un transfert interrompu. Liste vide = seul le Complete manque.
+    FileTransferResume {
+        id: String,
+        missing: Vec<(u64, u64)>,
+    },
+    /// Réponse du destinataire à un `FileTransferStart` ou `FolderTransferStart` :
+    /// l'émetteur n'envoie aucun chunk avant l'acceptation
+    FileTransferResponse {
+        id: String,
+        accepted: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// Annulation par l'un ou l'autre pair (l'id d'un dossier annule tous ses fichiers)
+    FileTransferCancel {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// Acquittement d'un chunk écrit sur disque (contrôle de flux de l'émetteur)
+    FileTransferAck {
+        id: String,
+        offset: u64,
+        /// Octets reçus au total pour ce transfert
+        received: u64,
+    },
+
+    // Remote file browser (viewer → hôte), confiné aux racines configurées par l'hôte
+    /// Lister un dossier ; `root` vide = liste des racines
+    ListDirectory {
+        request_id: u64,
+        #[serde(default)]
+        root: String,
+        /// Chemin relatif à la racine, composants séparés par `/` (vide = racine)
+        #[serde(default)]
+        path: String,
+    },
+    /// Télécharger un fichier : l'hôte répond par `Transfer` puis l'envoie
+    ReadFile {
+        request_id: u64,
+        root: String,
+        path: String,
+    },
+    /// Créer un dossier (racine en lecture-écriture)
+    MakeDirectory {
+        request_id: u64,
+        root: String,
+        path: String,
+    },
+    /// Supprimer un fichier ou un dossier vide (racine en lecture-écriture)
+    DeletePath {
+        request_id: u64,
+        root: String,
+        path: String,
+    },
+    /// Réponse de l'hôte à une requête de navigation
+    FileBrowserResponse {
+        request_id: u64,
+        result: FileBrowserResult,
     },
 
     // Chat
@@ -102,6 +373,12 @@ pub enum ControlMessage {
         displays: Vec<DisplayInfoProto>,
     },
 
+    // Session permissions (hôte → viewer)
+    /// L'hôte a changé la permission de la session (true = lecture seule)
+    PermissionChanged {
+        view_only: bool,
+    },
+
     // E2E Key exchange (X25519 ECDH — au-dessus de DTLS-SRTP)
     KeyExchangeInit {
         public_key: Vec<u8>,
@@ -119,6 +396,23 @@ pub enum ControlMessage {
 }
 
 impl ControlMessage {
+    /// Type d'input distant porté par ce message (souris, clavier, écriture du
+    /// presse-papiers). None pour les messages qui n'agissent pas sur l'hôte.
+    pub fn remote_input_kind(&self) -> Option<&'static str> {
+        match self {
+            ControlMessage::MouseMove { .. } | ControlMessage::MouseMoveNormalized { .. } => Some("mouse_move"),
+            ControlMessage::MouseClick { .. } | ControlMessage::MouseDoubleClick { .. } => Some("mouse_click"),
+            ControlMessage::MouseScroll { .. } => Some("mouse_scroll"),
+            ControlMessage::Touch { .. } => Some("touch"),
+            ControlMessage::Pen { .. } => Some("pen"),
+            ControlMessage::KeyPress { .. } => Some("key_press"),
+            ControlMessage::TypeText { .. } => Some("type_text"),
+            ControlMessage::SendKeySequence { .. } => Some("key_sequence"),
+            ControlMessage::ClipboardSync { .. } => Some("clipboard_sync"),
+            _ => None,
+        }
+    }
+
     /// Sérialiser le message en bytes pour envoi via WebRTC
     /// Utilise un format binaire optimisé pour VideoFrame, JSON pour les autres
     pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
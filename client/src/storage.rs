@@ -3,6 +3,7 @@
 //! Ce module gère la sauvegarde et le chargement des données utilisateur
 //! telles que l'historique des connexions, les pairs favoris, et les préférences.
 
+use crate::input_policy::InputRule;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
@@ -49,6 +50,12 @@ pub struct KnownPeer {
     /// Notes personnelles
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+    /// Règles d'input propres à ce pair (évaluées avant la politique globale)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_rules: Vec<InputRule>,
+    /// Fichiers envoyés par ce pair acceptés sans confirmation
+    #[serde(default)]
+    pub auto_accept_files: bool,
 }
 
 /// Structure principale de stockage
@@ -260,6 +267,16 @@ impl Storage {
         }
     }
 
+    /// Accepter (ou non) sans confirmation les fichiers envoyés par un pair
+    pub fn set_peer_auto_accept_files(&mut self, peer_id: &str, auto_accept: bool) -> bool {
+        if let Some(peer) = self.data.known_peers.get_mut(peer_id) {
+            peer.auto_accept_files = auto_accept;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Définir une préférence utilisateur
     pub fn set_preference(&mut self, key: String, value: String) {
         self.data.user_preferences.insert(key, value);
@@ -270,6 +287,11 @@ impl Storage {
         self.data.user_preferences.get(key)
     }
 
+    /// Clés des préférences enregistrées
+    pub fn preference_keys(&self) -> impl Iterator<Item = &String> {
+        self.data.user_preferences.keys()
+    }
+
     /// Supprimer une préférence utilisateur
     pub fn remove_preference(&mut self, key: &str) -> Option<String> {
         self.data.user_preferences.remove(key)
@@ -392,6 +414,8 @@ mod tests {
             favorite: false,
             connection_count: 1,
             notes: None,
+            input_rules: Vec::new(),
+            auto_accept_files: false,
         });
 
         // Récupérer
@@ -402,6 +426,10 @@ mod tests {
         storage.set_peer_favorite("PEER-123", true);
         assert_eq!(storage.get_favorite_peers().len(), 1);
 
+        assert!(storage.set_peer_auto_accept_files("PEER-123", true));
+        assert!(storage.get_known_peer("PEER-123").unwrap().auto_accept_files);
+        assert!(!storage.set_peer_auto_accept_files("INCONNU", true));
+
         let _ = fs::remove_dir_all(&temp_dir);
     }
 }
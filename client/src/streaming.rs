@@ -3,12 +3,18 @@
 //! Ce module gère la boucle de capture, encodage et transmission vidéo.
 
 use crate::adaptive_bitrate::AdaptiveBitrateController;
-use crate::crypto::{open_frame, seal_frame, ENCRYPTED_MAGIC};
+use crate::audit::{audit_log, AuditEvent, AuditLevel};
+use crate::clipboard::{ClipboardContent, ClipboardManager};
+use crate::clipboard_filter::{ClipboardDirection, ClipboardFilter};
+use crate::clipboard_watcher::{ClipboardPolicyHandle, ClipboardRole};
+use crate::crypto::{FrameCipher, SessionKeyState, ENCRYPTED_MAGIC};
 use crate::error::{GhostHandError, Result};
 use crate::input_control::{InputController, MouseButton, MouseEvent as InputMouseEvent, KeyboardEvent as InputKeyboardEvent, KeyModifiers};
-use crate::network::Transport;
-use crate::protocol::ControlMessage;
+use crate::network::{FrameReassembler, Transport};
+use crate::protocol::{ControlMessage, KeyInputMode, ScrollUnit, TouchPhase};
 use crate::screen_capture::ScreenCapturer;
+use crate::touch_input::{PointerKind, TouchContact};
+use crate::validation::ClientRateLimiter;
 use crate::video_encoder::VideoEncoder;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
@@ -18,15 +24,12 @@ use tracing::{debug, info, warn};
 
 /// Poignée partagée vers la clé de session E2E. Mise à jour en direct dès que le
 /// handshake X25519 se termine — le streamer/récepteur la relisent à chaque trame.
-/// Peut contenir un sentinel `b"PENDING:..."` tant que la clé n'est pas dérivée.
-pub type SessionKeyHandle = Arc<Mutex<Option<Vec<u8>>>>;
-
-/// Extraire la vraie clé de session (ignore le sentinel PENDING de handshake).
-fn real_session_key(guard: &Option<Vec<u8>>) -> Option<Vec<u8>> {
-    guard
-        .as_ref()
-        .filter(|k| !k.starts_with(b"PENDING:"))
-        .cloned()
+/// Contient l'état `Pending` tant que la clé n'est pas dérivée.
+pub type SessionKeyHandle = Arc<Mutex<Option<SessionKeyState>>>;
+
+/// Extraire le chiffrement de session actif (ignore l'état Pending du handshake).
+pub(crate) fn real_session_key(guard: &Option<SessionKeyState>) -> Option<Arc<FrameCipher>> {
+    guard.as_ref().and_then(SessionKeyState::cipher)
 }
 
 fn stream_diag(msg: &str) {
@@ -130,7 +133,7 @@ impl Streamer {
                     None => None,
                 };
                 let payload = match real_key {
-                    Some(key) => match seal_frame(&key, &bytes) {
+                    Some(cipher) => match cipher.seal(&bytes) {
                         Ok(env) => env,
                         Err(e) => {
                             stream_diag(&format!("SENDER: erreur chiffrement, trame ignorée: {}", e));
@@ -320,36 +323,13 @@ impl Receiver {
         let msg_cb = Arc::new(message_callback);
         let receiver_handle = self.key_handle.clone();
         tokio::spawn(async move {
-            let mut reassembly: Option<(usize, Vec<u8>)> = None;
+            let mut reassembler = FrameReassembler::new();
 
             while let Some(raw_data) = rx.recv().await {
                 // 1. Réassembler AVANT de déchiffrer : la fragmentation (0xFF) s'applique
                 //    sur la trame déjà scellée. Un fragment isolé ne peut pas être déchiffré.
-                let frame: Vec<u8> = if raw_data.len() >= 2 && raw_data[0] == 0xFF {
-                    match raw_data[1] {
-                        0x01 if raw_data.len() >= 6 => {
-                            let total_len = u32::from_le_bytes([raw_data[2], raw_data[3], raw_data[4], raw_data[5]]) as usize;
-                            reassembly = Some((total_len, Vec::with_capacity(total_len)));
-                            continue;
-                        }
-                        0x02 => {
-                            if let Some((expected_len, ref mut buffer)) = reassembly {
-                                buffer.extend_from_slice(&raw_data[2..]);
-                                if buffer.len() >= expected_len {
-                                    let complete = std::mem::take(buffer);
-                                    reassembly = None;
-                                    complete
-                                } else {
-                                    continue;
-                                }
-                            } else {
-                                continue;
-                            }
-                        }
-                        _ => continue,
-                    }
-                } else {
-                    raw_data
+                let Some(frame) = reassembler.push(raw_data) else {
+                    continue;
                 };
 
                 // 2. Déchiffrer si trame scellée (0xE2). Le flux vidéo est TOUJOURS scellé
@@ -360,7 +340,7 @@ impl Receiver {
                 };
                 let data = if frame.first() == Some(&ENCRYPTED_MAGIC) {
                     match real_key {
-                        Some(ref k) => match open_frame(k, &frame) {
+                        Some(ref cipher) => match cipher.open(&frame) {
                             Ok(plain) => plain,
                             Err(e) => {
                                 stream_diag(&format!("RECEIVER: erreur déchiffrement: {}", e));
@@ -394,38 +374,227 @@ impl Receiver {
     }
 }
 
+/// Décodage des messages reçus par l'hôte : réassemblage des fragments, puis
+/// ouverture des trames scellées. Une fois la clé E2E active, tout message en
+/// clair est rejeté (empêche l'injection d'input par un relais malveillant).
+#[derive(Debug, Default)]
+pub struct HostFrameDecoder {
+    reassembler: FrameReassembler,
+}
+
+impl HostFrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Message prêt à parser, ou None (fragment d'un message incomplet, trame
+    /// illisible, scellée sans clé ou en clair alors que la clé est active)
+    pub fn decode(&mut self, raw: Vec<u8>, cipher: Option<&FrameCipher>) -> Option<Vec<u8>> {
+        // Réassembler AVANT de déchiffrer : la fragmentation porte sur la trame scellée
+        let frame = self.reassembler.push(raw)?;
+        if frame.first() == Some(&ENCRYPTED_MAGIC) {
+            match cipher?.open(&frame) {
+                Ok(plain) => Some(plain),
+                Err(e) => {
+                    warn!("Déchiffrement échoué: {}", e);
+                    None
+                }
+            }
+        } else if cipher.is_some() {
+            warn!("Message de contrôle en clair rejeté (clé E2E active)");
+            None
+        } else {
+            // Phase handshake : KeyExchangeAccept circule en clair
+            Some(frame)
+        }
+    }
+}
+
+/// Taille d'un fragment de `TypeText` quand la saisie est cadencée (en caractères)
+const TYPE_TEXT_CHUNK_CHARS: usize = 16;
+/// Délai maximal accepté entre deux fragments de `TypeText`
+const MAX_TYPE_TEXT_PACING_MS: u32 = 500;
+
+/// Nombre max de messages d'input acceptés par pair et par fenêtre
+const INPUT_RATE_LIMIT: usize = 600;
+/// Fenêtre du rate limit d'input
+const INPUT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
 /// InputHandler : gestion des commandes input reçues
 pub struct InputHandler {
     controller: Arc<Mutex<InputController>>,
+    /// Session en lecture seule : tout input distant est écarté (partagé, modifiable en direct)
+    view_only: Arc<AtomicBool>,
+    /// Rate limit des messages d'input, par pair
+    rate_limiter: ClientRateLimiter,
+    /// Clé du pair pour le rate limit et l'audit
+    peer_key: String,
+    /// Dernier signalement de flood (un seul audit par fenêtre)
+    last_flood_report: std::sync::Mutex<Option<std::time::Instant>>,
+    /// Presse-papiers local, écrit par les `ClipboardSync` du viewer
+    clipboard: ClipboardManager,
+    /// Sens de synchronisation autorisés pour la session
+    clipboard_policy: ClipboardPolicyHandle,
+    /// Filtre (taille, secrets) appliqué aux contenus reçus du viewer
+    clipboard_filter: Arc<ClipboardFilter>,
+    /// Saisie cadencée en cours (`TypeText` avec `pacing_ms`), annulable
+    typing: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl InputHandler {
     /// Créer un nouveau InputHandler avec résolution par défaut
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            controller: Arc::new(Mutex::new(InputController::new()?)),
-        })
+        Ok(Self::from_controller(InputController::new()?))
     }
 
     /// Créer un nouveau InputHandler avec une résolution spécifique
     pub fn new_with_resolution(width: i32, height: i32) -> Result<Self> {
-        Ok(Self {
-            controller: Arc::new(Mutex::new(InputController::new_with_resolution(width, height)?)),
-        })
+        Ok(Self::from_controller(InputController::new_with_resolution(width, height)?))
+    }
+
+    /// Créer un InputHandler à partir d'un contrôleur déjà configuré (politique, pair)
+    pub fn from_controller(controller: InputController) -> Self {
+        let peer_key = controller.peer_id().unwrap_or("anonymous").to_string();
+        Self {
+            controller: Arc::new(Mutex::new(controller)),
+            view_only: Arc::new(AtomicBool::new(false)),
+            rate_limiter: ClientRateLimiter::new(INPUT_RATE_LIMIT, INPUT_RATE_WINDOW),
+            peer_key,
+            last_flood_report: std::sync::Mutex::new(None),
+            clipboard: ClipboardManager::new(),
+            clipboard_policy: ClipboardPolicyHandle::default(),
+            clipboard_filter: Arc::new(ClipboardFilter::default()),
+            typing: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Remplacer le filtre du presse-papiers (règles de la configuration)
+    pub fn with_clipboard_filter(mut self, filter: Arc<ClipboardFilter>) -> Self {
+        self.clipboard_filter = filter;
+        self
+    }
+
+    /// Partager le presse-papiers (cache anti-écho du watcher) et la politique de la session
+    pub fn with_clipboard(mut self, manager: ClipboardManager, policy: ClipboardPolicyHandle) -> Self {
+        self.clipboard = manager;
+        self.clipboard_policy = policy;
+        self
+    }
+
+    /// Ajuster le rate limit d'input (messages max par fenêtre)
+    pub fn with_rate_limit(mut self, max_messages: usize, window: Duration) -> Self {
+        self.rate_limiter = ClientRateLimiter::new(max_messages, window);
+        self
+    }
+
+    /// Vérifier le rate limit du pair ; un flood est signalé une fois par fenêtre
+    fn check_rate_limit(&self, kind: &str) -> bool {
+        if self.rate_limiter.check(&self.peer_key).is_ok() {
+            return true;
+        }
+
+        let now = std::time::Instant::now();
+        let should_report = match self.last_flood_report.lock() {
+            Ok(mut last) => {
+                let report = last.is_none_or(|t| now.duration_since(t) >= INPUT_RATE_WINDOW);
+                if report {
+                    *last = Some(now);
+                }
+                report
+            }
+            Err(_) => false,
+        };
+        if should_report {
+            warn!("⚠️  SÉCURITÉ: flood d'input du pair {} ({}), messages écartés", self.peer_key, kind);
+            audit_log(
+                AuditLevel::Security,
+                AuditEvent::SuspiciousActivity {
+                    description: format!("Flood d'input ({}) : rate limit dépassé", kind),
+                    peer_id: Some(self.peer_key.clone()),
+                },
+            );
+        }
+        false
+    }
+
+    /// Partager le drapeau de lecture seule (permet de basculer la permission en cours de session)
+    pub fn with_view_only(mut self, view_only: Arc<AtomicBool>) -> Self {
+        self.view_only = view_only;
+        self
+    }
+
+    /// Basculer entre lecture seule et contrôle total
+    pub fn set_view_only(&self, view_only: bool) {
+        self.view_only.store(view_only, Ordering::SeqCst);
+    }
+
+    /// La session est-elle en lecture seule ?
+    pub fn is_view_only(&self) -> bool {
+        self.view_only.load(Ordering::SeqCst)
+    }
+
+    /// Relâcher toutes les touches et boutons encore maintenus sur l'hôte
+    /// (interrompt aussi une saisie cadencée en cours)
+    pub async fn release_all(&self) -> Result<()> {
+        self.cancel_typing();
+        self.controller.lock().await.release_all()
+    }
+
+    /// Interrompre la saisie cadencée en cours, s'il y en a une
+    fn cancel_typing(&self) {
+        if let Some(task) = self.typing.lock().ok().and_then(|mut t| t.take()) {
+            task.abort();
+        }
+    }
+
+    /// Suivre l'écran sélectionné (SelectDisplay) pour les coordonnées normalisées
+    pub async fn set_display_bounds(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.controller.lock().await.set_display_bounds(x, y, width, height);
     }
 
     /// Traiter un message de contrôle reçu
     pub async fn handle_message(&self, msg: ControlMessage) -> Result<()> {
+        // Rate limit par pair : un flood est écarté avant toute autre vérification,
+        // sauf le relâchement d'un input maintenu (borné par ce qui est enfoncé)
+        if let Some(kind) = msg.remote_input_kind() {
+            if !self.check_rate_limit(kind) && !self.releases_held_input(&msg).await {
+                return Ok(());
+            }
+        }
+
+        // Lecture seule : souris, clavier et écriture du presse-papiers sont écartés
+        if self.is_view_only() {
+            if let Some(kind) = msg.remote_input_kind() {
+                self.cancel_typing();
+                let mut controller = self.controller.lock().await;
+                // La permission a pu basculer en plein drag / Ctrl maintenu
+                if let Err(e) = controller.release_all() {
+                    warn!("Lecture seule : relâchement de l'input maintenu incomplet: {}", e);
+                }
+                let peer_id = controller.peer_id().map(str::to_string);
+                drop(controller);
+                debug!("Lecture seule : input distant écarté ({})", kind);
+                audit_log(
+                    AuditLevel::Warning,
+                    AuditEvent::ViewOnlyInputDropped {
+                        input: kind.to_string(),
+                        peer_id,
+                    },
+                );
+                return Ok(());
+            }
+        }
+
         match msg {
             ControlMessage::MouseMove { x, y } => {
                 self.controller.lock().await.handle_mouse_event(InputMouseEvent::Move { x, y })?;
             }
+            ControlMessage::MouseMoveNormalized { x, y } => {
+                self.controller.lock().await.handle_mouse_event(InputMouseEvent::MoveNormalized { x, y })?;
+            }
             ControlMessage::MouseClick { button, pressed } => {
-                let btn = match button.as_str() {
-                    "left" => MouseButton::Left,
-                    "right" => MouseButton::Right,
-                    "middle" => MouseButton::Middle,
-                    _ => return Ok(()), // Ignorer les boutons inconnus
+                let Some(btn) = MouseButton::from_name(&button) else {
+                    return Ok(()); // Ignorer les boutons inconnus
                 };
                 if pressed {
                     self.controller.lock().await.handle_mouse_event(InputMouseEvent::Press { button: btn })?;
@@ -433,13 +602,32 @@ impl InputHandler {
                     self.controller.lock().await.handle_mouse_event(InputMouseEvent::Release { button: btn })?;
                 }
             }
-            ControlMessage::MouseScroll { delta } => {
-                self.controller.lock().await.handle_mouse_event(InputMouseEvent::Scroll {
-                    delta_x: 0,
-                    delta_y: delta,
-                })?;
+            ControlMessage::MouseDoubleClick { button } => {
+                let Some(btn) = MouseButton::from_name(&button) else {
+                    return Ok(());
+                };
+                self.controller.lock().await.handle_mouse_event(InputMouseEvent::DoubleClick { button: btn })?;
+            }
+            ControlMessage::Touch { id, phase, x, y, pressure } => {
+                let contact = TouchContact { id, phase, x, y, pressure };
+                self.controller.lock().await.handle_touch_event(PointerKind::Touch, contact)?;
             }
-            ControlMessage::KeyPress { key, pressed, modifiers } => {
+            ControlMessage::Pen { id, phase, x, y, pressure } => {
+                let contact = TouchContact { id, phase, x, y, pressure };
+                self.controller.lock().await.handle_touch_event(PointerKind::Pen, contact)?;
+            }
+            ControlMessage::MouseScroll { delta, delta_x, unit } => {
+                let event = match unit {
+                    ScrollUnit::Line => InputMouseEvent::Scroll { delta_x, delta_y: delta },
+                    // Pixels et fractions de cran : cumulés par le contrôleur
+                    _ => InputMouseEvent::ScrollHighRes {
+                        delta_x: unit.to_high_res(delta_x),
+                        delta_y: unit.to_high_res(delta),
+                    },
+                };
+                self.controller.lock().await.handle_mouse_event(event)?;
+            }
+            ControlMessage::KeyPress { key, pressed, modifiers, mode, hid_usage, layout } => {
                 // Convertir les modifiers du protocole vers input_control
                 let key_modifiers = if let Some(m) = modifiers {
                     KeyModifiers {
@@ -451,11 +639,49 @@ impl InputHandler {
                 } else {
                     KeyModifiers::default()
                 };
-                if pressed {
-                    self.controller.lock().await.handle_keyboard_event(InputKeyboardEvent::Press { key }, key_modifiers)?;
-                } else {
-                    self.controller.lock().await.handle_keyboard_event(InputKeyboardEvent::Release { key }, key_modifiers)?;
+                let event = match (mode, hid_usage) {
+                    (KeyInputMode::Physical, Some(usage)) => {
+                        self.controller.lock().await.set_viewer_layout(layout);
+                        if pressed {
+                            InputKeyboardEvent::PhysicalPress { usage }
+                        } else {
+                            InputKeyboardEvent::PhysicalRelease { usage }
+                        }
+                    }
+                    (KeyInputMode::Physical, None) => {
+                        return Err(GhostHandError::InputControl(
+                            "KeyPress physique sans usage HID".to_string(),
+                        ));
+                    }
+                    (KeyInputMode::Logical, _) if pressed => InputKeyboardEvent::Press { key },
+                    (KeyInputMode::Logical, _) => InputKeyboardEvent::Release { key },
+                };
+                self.controller.lock().await.handle_keyboard_event(event, key_modifiers)?;
+            }
+            ControlMessage::SendKeySequence { name } => {
+                self.controller.lock().await.send_key_sequence(&name)?;
+            }
+            ControlMessage::TypeText { text, pacing_ms } => {
+                self.type_text(text, pacing_ms).await?;
+            }
+            ControlMessage::ClipboardSync { content, mime, alt_text } => {
+                let policy = self.clipboard_policy.lock().map(|p| *p).ok();
+                if !policy.is_some_and(|p| p.allows_incoming(ClipboardRole::Host)) {
+                    debug!("Presse-papiers du viewer écarté (politique {:?})", policy);
+                    return Ok(());
                 }
+                let content = ClipboardContent::from_sync(content, mime, alt_text)?;
+                let peer_id = self.controller.lock().await.peer_id().map(str::to_string);
+                let Some(content) =
+                    self.clipboard_filter.check(content, ClipboardDirection::ViewerToHost, peer_id.as_deref())
+                else {
+                    return Ok(());
+                };
+                // Décodage PNG et accès au presse-papiers système : hors du runtime async
+                let clipboard = self.clipboard.clone();
+                tokio::task::spawn_blocking(move || clipboard.set_content(&content))
+                    .await
+                    .map_err(|e| GhostHandError::Internal(format!("Tâche presse-papiers: {}", e)))??;
             }
             _ => {
                 debug!("Message non-input reçu: {:?}", msg);
@@ -464,6 +690,93 @@ impl InputHandler {
         Ok(())
     }
 
+    /// Le message relâche-t-il une touche, un bouton ou un contact maintenu sur l'hôte
+    async fn releases_held_input(&self, msg: &ControlMessage) -> bool {
+        let controller = self.controller.lock().await;
+        match msg {
+            ControlMessage::KeyPress { key, pressed: false, mode, hid_usage, .. } => {
+                let event = match (mode, hid_usage) {
+                    (KeyInputMode::Physical, Some(usage)) => InputKeyboardEvent::PhysicalRelease { usage: *usage },
+                    (KeyInputMode::Physical, None) => return false,
+                    (KeyInputMode::Logical, _) => InputKeyboardEvent::Release { key: key.clone() },
+                };
+                controller.releases_held_key(&event)
+            }
+            ControlMessage::MouseClick { button, pressed: false } => {
+                MouseButton::from_name(button).is_some_and(|b| controller.held_buttons().contains(&b))
+            }
+            ControlMessage::Touch { id, phase: TouchPhase::End | TouchPhase::Cancel, .. } => {
+                controller.is_touch_active(PointerKind::Touch, *id)
+            }
+            ControlMessage::Pen { id, phase: TouchPhase::End | TouchPhase::Cancel, .. } => {
+                controller.is_touch_active(PointerKind::Pen, *id)
+            }
+            _ => false,
+        }
+    }
+
+    /// Saisir un texte sur l'hôte, d'un bloc ou par fragments cadencés.
+    /// Le texte entier est validé (taille, politique) avant toute injection.
+    /// La saisie cadencée tourne dans une tâche dédiée (la boucle de messages
+    /// n'attend pas) ; un nouveau `TypeText` remplace celle en cours.
+    async fn type_text(&self, text: String, pacing_ms: u32) -> Result<()> {
+        self.cancel_typing();
+        let peer_id = {
+            let controller = self.controller.lock().await;
+            controller.validate_text(&text)?;
+            controller.peer_id().map(str::to_string)
+        };
+        let chars: Vec<char> = text.chars().collect();
+        audit_log(
+            AuditLevel::Info,
+            AuditEvent::TextInjected {
+                peer_id,
+                chars: chars.len(),
+            },
+        );
+
+        if pacing_ms == 0 {
+            return self.controller.lock().await
+                .handle_keyboard_event(InputKeyboardEvent::Type { text }, KeyModifiers::default());
+        }
+
+        let pacing = Duration::from_millis(pacing_ms.min(MAX_TYPE_TEXT_PACING_MS) as u64);
+        let controller = self.controller.clone();
+        let view_only = self.view_only.clone();
+        let task = tokio::spawn(async move {
+            for (i, chunk) in chars.chunks(TYPE_TEXT_CHUNK_CHARS).enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(pacing).await;
+                }
+                // La permission a pu passer en lecture seule pendant la saisie
+                if view_only.load(Ordering::SeqCst) {
+                    debug!("Lecture seule : saisie de texte interrompue");
+                    break;
+                }
+                if let Err(e) = controller.lock().await.handle_keyboard_event(
+                    InputKeyboardEvent::Type { text: chunk.iter().collect() },
+                    KeyModifiers::default(),
+                ) {
+                    warn!("Saisie de texte interrompue: {}", e);
+                    break;
+                }
+            }
+        });
+        if let Ok(mut typing) = self.typing.lock() {
+            *typing = Some(task);
+        }
+        Ok(())
+    }
+
+    /// Attendre la fin de la saisie cadencée en cours (tests)
+    #[cfg(test)]
+    async fn wait_typing(&self) {
+        let task = self.typing.lock().ok().and_then(|mut t| t.take());
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+
     /// Setup le handler sur une connexion WebRTC existante
     pub async fn attach_to_webrtc(self: Arc<Self>, webrtc: Arc<Mutex<Transport>>) -> Result<()> {
         info!("Attachement du InputHandler au WebRTC");
@@ -494,6 +807,10 @@ impl InputHandler {
                     }
                 }
             }
+            // Canal fermé (transport perdu) : ne rien laisser enfoncé sur l'hôte
+            if let Err(e) = handler.release_all().await {
+                warn!("Erreur relâchement input en fin de session: {}", e);
+            }
         });
 
         Ok(())
@@ -502,9 +819,349 @@ impl InputHandler {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::input_backend::{InjectedEvent, RecordingBackend};
+    use enigo::{Button, Coordinate, Direction};
+
     #[tokio::test]
     async fn test_streamer_creation() {
         // Test basique de création
         // Note: nécessite des mocks pour les vrais tests
     }
+
+    fn recording_handler() -> (InputHandler, RecordingBackend) {
+        let backend = RecordingBackend::new();
+        let controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080);
+        (InputHandler::from_controller(controller), backend)
+    }
+
+    #[tokio::test]
+    async fn test_control_messages_drive_backend() {
+        let (handler, backend) = recording_handler();
+        handler.handle_message(ControlMessage::MouseMove { x: 100, y: 200 }).await.unwrap();
+        handler
+            .handle_message(ControlMessage::MouseClick { button: "left".to_string(), pressed: true })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            backend.events(),
+            vec![
+                InjectedEvent::MoveMouse { x: 100, y: 200, coordinate: Coordinate::Abs },
+                InjectedEvent::Button { button: Button::Left, direction: Direction::Press },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extended_pointer_messages() {
+        let (handler, backend) = recording_handler();
+        // Second écran 1280x720 à droite de l'écran principal
+        handler.set_display_bounds(1920, 0, 1280, 720).await;
+        handler.handle_message(ControlMessage::MouseMoveNormalized { x: 0.5, y: 1.0 }).await.unwrap();
+        handler
+            .handle_message(ControlMessage::MouseDoubleClick { button: "back".to_string() })
+            .await
+            .unwrap();
+        // 60 px horizontaux = 0.6 cran : rien tant qu'un cran entier n'est pas atteint
+        let scroll = ControlMessage::MouseScroll { delta: 0, delta_x: 60, unit: ScrollUnit::Pixel };
+        handler.handle_message(scroll.clone()).await.unwrap();
+        handler.handle_message(scroll).await.unwrap();
+
+        assert_eq!(
+            backend.events(),
+            vec![
+                InjectedEvent::MoveMouse { x: 1920 + 640, y: 719, coordinate: Coordinate::Abs },
+                InjectedEvent::Button { button: Button::Back, direction: Direction::Click },
+                InjectedEvent::Button { button: Button::Back, direction: Direction::Click },
+                InjectedEvent::Scroll { length: 1, axis: enigo::Axis::Horizontal },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_view_only_drops_input_and_releases_held() {
+        let (handler, backend) = recording_handler();
+        handler
+            .handle_message(ControlMessage::MouseClick { button: "left".to_string(), pressed: true })
+            .await
+            .unwrap();
+        backend.take_events();
+
+        handler.set_view_only(true);
+        handler.handle_message(ControlMessage::MouseMove { x: 10, y: 10 }).await.unwrap();
+        handler
+            .handle_message(ControlMessage::KeyPress {
+                key: "a".to_string(),
+                pressed: true,
+                modifiers: None,
+                mode: KeyInputMode::Logical,
+                hid_usage: None,
+                layout: None,
+            })
+            .await
+            .unwrap();
+
+        // Seul le relâchement du bouton maintenu atteint l'hôte
+        assert_eq!(
+            backend.events(),
+            vec![InjectedEvent::Button { button: Button::Left, direction: Direction::Release }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_type_text_is_chunked_and_unicode_safe() {
+        let (handler, backend) = recording_handler();
+        let text = "Mot de passe: é日本語🔑 ".repeat(2);
+        handler
+            .handle_message(ControlMessage::TypeText { text: text.clone(), pacing_ms: 1 })
+            .await
+            .unwrap();
+        handler.wait_typing().await;
+
+        let typed: Vec<String> = backend
+            .events()
+            .into_iter()
+            .map(|e| match e {
+                InjectedEvent::Text(t) => t,
+                other => panic!("événement inattendu: {:?}", other),
+            })
+            .collect();
+        assert!(typed.len() > 1);
+        assert!(typed.iter().all(|t| t.chars().count() <= TYPE_TEXT_CHUNK_CHARS));
+        assert_eq!(typed.concat(), text);
+    }
+
+    #[tokio::test]
+    async fn test_paced_type_text_does_not_block_and_is_cancellable() {
+        let (handler, backend) = recording_handler();
+
+        // La boucle de messages rend la main sans attendre les ~5 s de saisie
+        let start = std::time::Instant::now();
+        handler
+            .handle_message(ControlMessage::TypeText { text: "a".repeat(TYPE_TEXT_CHUNK_CHARS * 100), pacing_ms: 50 })
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // Un nouveau TypeText remplace la saisie en cours
+        handler
+            .handle_message(ControlMessage::TypeText { text: "ok".to_string(), pacing_ms: 1 })
+            .await
+            .unwrap();
+        handler.wait_typing().await;
+        let typed = backend.take_events();
+        assert!(typed.len() <= 2);
+        assert_eq!(typed.last(), Some(&InjectedEvent::Text("ok".to_string())));
+
+        // Fin de session : la saisie est interrompue
+        handler
+            .handle_message(ControlMessage::TypeText { text: "b".repeat(64), pacing_ms: 200 })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handler.release_all().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(backend.take_events(), vec![InjectedEvent::Text("b".repeat(TYPE_TEXT_CHUNK_CHARS))]);
+
+        // Passage en lecture seule : la saisie s'arrête au fragment suivant
+        handler
+            .handle_message(ControlMessage::TypeText { text: "c".repeat(64), pacing_ms: 200 })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handler.set_view_only(true);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(backend.events(), vec![InjectedEvent::Text("c".repeat(TYPE_TEXT_CHUNK_CHARS))]);
+    }
+
+    #[tokio::test]
+    async fn test_type_text_respects_policy_and_held_modifiers() {
+        use crate::input_policy::{InputMatcher, InputPolicy, InputRule, PolicyAction};
+
+        // Texte interdit par la politique
+        let mut no_text = InputPolicy::default();
+        no_text.rules.push(InputRule {
+            name: "no-text".to_string(),
+            action: PolicyAction::Deny,
+            matcher: InputMatcher::Text,
+        });
+        let backend = RecordingBackend::new();
+        let controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080)
+            .with_policy(no_text, None);
+        let handler = InputHandler::from_controller(controller);
+        assert!(handler
+            .handle_message(ControlMessage::TypeText { text: "hello".to_string(), pacing_ms: 0 })
+            .await
+            .is_err());
+        assert!(backend.events().is_empty());
+
+        // Alt maintenu : chaque caractère tapé est évalué comme un raccourci Alt+caractère
+        let mut policy = InputPolicy::default();
+        policy.rules.push(InputRule {
+            name: "alt-x".to_string(),
+            action: PolicyAction::Deny,
+            matcher: InputMatcher::Chord { modifiers: vec!["alt".to_string()], key: Some("x".to_string()) },
+        });
+        let (handler, backend) = recording_handler();
+        handler.controller.lock().await.set_policy(policy);
+        handler
+            .handle_message(ControlMessage::KeyPress {
+                key: "alt".to_string(),
+                pressed: true,
+                modifiers: None,
+                mode: KeyInputMode::Logical,
+                hid_usage: None,
+                layout: None,
+            })
+            .await
+            .unwrap();
+        backend.take_events();
+        assert!(handler
+            .handle_message(ControlMessage::TypeText { text: "xyz".to_string(), pacing_ms: 0 })
+            .await
+            .is_err());
+        assert!(backend.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_input_flood_is_rate_limited() {
+        let backend = RecordingBackend::new();
+        let controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080)
+            .with_policy(Default::default(), Some("GHD-FLOOD".to_string()));
+        let handler = InputHandler::from_controller(controller).with_rate_limit(5, Duration::from_secs(60));
+
+        for x in 0..50 {
+            handler.handle_message(ControlMessage::MouseMove { x, y: 0 }).await.unwrap();
+        }
+        // Les messages de pilotage ne sont pas comptés
+        handler.handle_message(ControlMessage::Ping).await.unwrap();
+
+        assert_eq!(backend.events().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_held_input_is_released_despite_rate_limit() {
+        let backend = RecordingBackend::new();
+        let controller = InputController::new_with_backend(Box::new(backend.clone()), 1920, 1080);
+        let handler = InputHandler::from_controller(controller).with_rate_limit(3, Duration::from_secs(60));
+        let key = |key: &str, pressed| ControlMessage::KeyPress {
+            key: key.to_string(),
+            pressed,
+            modifiers: None,
+            mode: KeyInputMode::Logical,
+            hid_usage: None,
+            layout: None,
+        };
+        let click = |pressed| ControlMessage::MouseClick { button: "left".to_string(), pressed };
+
+        handler.handle_message(key("shift", true)).await.unwrap();
+        handler.handle_message(click(true)).await.unwrap();
+        for x in 0..50 {
+            handler.handle_message(ControlMessage::MouseMove { x, y: 0 }).await.unwrap();
+        }
+        backend.take_events();
+
+        // Limite atteinte : les relâchements passent, un appui ou un relâchement
+        // de ce qui n'est pas maintenu reste écarté
+        handler.handle_message(key("a", false)).await.unwrap();
+        handler.handle_message(key("shift", false)).await.unwrap();
+        handler.handle_message(click(false)).await.unwrap();
+        handler.handle_message(click(false)).await.unwrap();
+        handler.handle_message(key("b", true)).await.unwrap();
+
+        assert_eq!(
+            backend.events(),
+            vec![
+                InjectedEvent::Key { key: enigo::Key::Shift, direction: Direction::Release },
+                InjectedEvent::Button { button: Button::Left, direction: Direction::Release },
+            ]
+        );
+        let controller = handler.controller.lock().await;
+        assert!(controller.held_keys().is_empty());
+        assert!(controller.held_buttons().is_empty());
+    }
+
+    /// Sceller `msg` côté viewer, le fragmenter comme `send_data`, puis le faire
+    /// passer par le décodeur de l'hôte
+    fn through_host_path(msg: &ControlMessage) -> ControlMessage {
+        use crate::crypto::FrameRole;
+        use crate::network::{FragmentingSender, MAX_CHUNK_SIZE};
+
+        let key = vec![7u8; 32];
+        let viewer = FrameCipher::new(key.clone(), FrameRole::Viewer).unwrap();
+        let host = FrameCipher::new(key, FrameRole::Host).unwrap();
+        let sealed = viewer.seal(&msg.to_bytes().unwrap()).unwrap();
+        assert!(sealed.len() > MAX_CHUNK_SIZE, "message censé être fragmenté");
+
+        let fragments = FragmentingSender::fragments(&sealed);
+        assert!(fragments.len() > 2);
+        let mut decoder = HostFrameDecoder::new();
+        let mut decoded = None;
+        for fragment in fragments {
+            assert!(decoded.is_none());
+            decoded = decoder.decode(fragment.to_vec(), Some(&host));
+        }
+        ControlMessage::from_bytes(&decoded.expect("message réassemblé")).unwrap()
+    }
+
+    #[test]
+    fn test_host_reassembles_file_chunk() {
+        let data: Vec<u8> = (0..crate::file_transfer::CHUNK_SIZE).map(|i| i as u8).collect();
+        let msg = ControlMessage::FileTransferChunk { id: "ft-1".into(), data: data.clone(), offset: 0, sha256: None };
+        match through_host_path(&msg) {
+            ControlMessage::FileTransferChunk { id, data: received, .. } => {
+                assert_eq!(id, "ft-1");
+                assert_eq!(received, data);
+            }
+            other => panic!("message inattendu: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_host_reassembles_large_clipboard() {
+        use crate::protocol::ClipboardMime;
+
+        let content = "A".repeat(200 * 1024);
+        let msg = ControlMessage::ClipboardSync { content: content.clone(), mime: ClipboardMime::ImagePng, alt_text: None };
+        match through_host_path(&msg) {
+            ControlMessage::ClipboardSync { content: received, mime, .. } => {
+                assert_eq!(received, content);
+                assert_eq!(mime, ClipboardMime::ImagePng);
+            }
+            other => panic!("message inattendu: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_host_reassembles_large_manifest() {
+        use crate::protocol::FolderEntry;
+
+        let entries: Vec<FolderEntry> = (0..2000)
+            .map(|i| FolderEntry {
+                path: format!("logs/app-{:05}.log", i), dir: false, size: 1024, mtime: Some(1_600_000_000), mode: Some(0o644), readonly: false,
+            })
+            .collect();
+        let msg = ControlMessage::FolderTransferStart { id: "fd-1".into(), name: "logs".into(), entries };
+        match through_host_path(&msg) {
+            ControlMessage::FolderTransferStart { entries, .. } => {
+                assert_eq!(entries.len(), 2000);
+                assert_eq!(entries[1999].path, "logs/app-01999.log");
+            }
+            other => panic!("message inattendu: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_host_decoder_rejects_plaintext_once_keyed() {
+        use crate::crypto::FrameRole;
+
+        let host = FrameCipher::new(vec![7u8; 32], FrameRole::Host).unwrap();
+        let plain = ControlMessage::ClipboardSync { content: "x".into(), mime: Default::default(), alt_text: None }
+            .to_bytes()
+            .unwrap();
+        let mut decoder = HostFrameDecoder::new();
+        assert_eq!(decoder.decode(plain.clone(), None), Some(plain.clone()), "handshake : clair accepté");
+        assert_eq!(decoder.decode(plain, Some(&host)), None);
+    }
 }
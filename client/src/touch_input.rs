@@ -0,0 +1,341 @@
+//! Input tactile et stylet (côté hôte)
+//!
+//! Les contacts reçus du viewer (`ControlMessage::Touch` / `Pen`) sont traduits
+//! en événements souris ordinaires, qui passent ensuite par la même politique
+//! d'input et le même audit que la souris :
+//! - un doigt : déplacement + bouton gauche (tap = clic, glisser = drag) ;
+//! - deux doigts : le pan devient un défilement, le pinch devient Ctrl+molette ;
+//! - stylet : pointeur avec bouton gauche tant qu'il touche l'écran (survol = déplacement).
+
+use crate::input_control::{MouseButton, MouseEvent, SCROLL_UNITS_PER_NOTCH};
+use crate::protocol::{ScrollUnit, TouchPhase};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Contacts tactiles suivis simultanément ; au-delà, un `Start` est ignoré
+pub const MAX_TOUCH_CONTACTS: usize = 10;
+
+/// Correspondance contacts → pointeur, configurable par l'hôte
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TouchMapping {
+    /// Accepter les contacts tactiles
+    pub touch_enabled: bool,
+    /// Accepter le stylet
+    pub pen_enabled: bool,
+    /// Pan à deux doigts → défilement
+    pub two_finger_scroll: bool,
+    /// Pinch → Ctrl+molette (zoom)
+    pub pinch_zoom: bool,
+}
+
+impl Default for TouchMapping {
+    fn default() -> Self {
+        Self {
+            touch_enabled: true,
+            pen_enabled: true,
+            two_finger_scroll: true,
+            pinch_zoom: true,
+        }
+    }
+}
+
+/// Origine d'un contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Touch,
+    Pen,
+}
+
+/// Contact transmis par le viewer (position normalisée sur l'écran sélectionné)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchContact {
+    pub id: u32,
+    pub phase: TouchPhase,
+    pub x: f64,
+    pub y: f64,
+    /// Pression 0.0..=1.0 (non injectée : enigo ne simule qu'une souris)
+    pub pressure: f32,
+}
+
+/// Action pointeur produite par le mapping
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointerAction {
+    Mouse(MouseEvent),
+    /// Zoom en crans de molette avec Ctrl maintenu (négatif = zoom avant)
+    Zoom { notches: i32 },
+}
+
+/// Geste à deux doigts en cours (positions en pixels hôte)
+#[derive(Debug, Clone, Copy)]
+struct Gesture {
+    centroid: (f64, f64),
+    distance: f64,
+}
+
+/// Suivi des contacts et reconnaissance des gestes
+#[derive(Debug, Default)]
+pub struct TouchMapper {
+    mapping: TouchMapping,
+    /// Contacts actifs, dans l'ordre d'arrivée (positions normalisées)
+    contacts: Vec<(u32, f64, f64)>,
+    /// Le premier doigt maintient le bouton gauche
+    touch_pressed: bool,
+    pen_pressed: bool,
+    gesture: Option<Gesture>,
+    /// Reliquat de zoom (1/120 de cran) non encore injecté
+    zoom_remainder: i32,
+}
+
+impl TouchMapper {
+    pub fn new(mapping: TouchMapping) -> Self {
+        Self {
+            mapping,
+            ..Self::default()
+        }
+    }
+
+    pub fn mapping(&self) -> &TouchMapping {
+        &self.mapping
+    }
+
+    /// Oublier les contacts en cours (les boutons sont relâchés par l'appelant)
+    pub fn reset(&mut self) {
+        *self = Self::new(self.mapping.clone());
+    }
+
+    /// Contact en cours (doigt posé ou stylet en contact)
+    pub fn is_active(&self, kind: PointerKind, id: u32) -> bool {
+        match kind {
+            PointerKind::Pen => self.pen_pressed,
+            PointerKind::Touch => self.contacts.iter().any(|(c, _, _)| *c == id),
+        }
+    }
+
+    /// Traduire un contact en actions pointeur, pour un écran de `screen` pixels
+    pub fn map(&mut self, kind: PointerKind, contact: TouchContact, screen: (i32, i32)) -> Vec<PointerAction> {
+        match kind {
+            PointerKind::Pen if self.mapping.pen_enabled => self.map_pen(contact),
+            PointerKind::Touch if self.mapping.touch_enabled => self.map_touch(contact, screen),
+            _ => Vec::new(),
+        }
+    }
+
+    fn map_pen(&mut self, c: TouchContact) -> Vec<PointerAction> {
+        let mut actions = vec![move_to(&c)];
+        match c.phase {
+            TouchPhase::Start if !self.pen_pressed => {
+                self.pen_pressed = true;
+                actions.push(button(true));
+            }
+            TouchPhase::End | TouchPhase::Cancel if self.pen_pressed => {
+                self.pen_pressed = false;
+                actions.push(button(false));
+            }
+            // Move sans contact : survol
+            _ => {}
+        }
+        actions
+    }
+
+    fn map_touch(&mut self, c: TouchContact, screen: (i32, i32)) -> Vec<PointerAction> {
+        match c.phase {
+            TouchPhase::Start => {
+                if self.contacts.iter().any(|(id, _, _)| *id == c.id) {
+                    return Vec::new();
+                }
+                if self.contacts.len() >= MAX_TOUCH_CONTACTS {
+                    debug!("Contact {} ignoré : {} contacts déjà actifs", c.id, MAX_TOUCH_CONTACTS);
+                    return Vec::new();
+                }
+                self.contacts.push((c.id, c.x, c.y));
+                match self.contacts.len() {
+                    1 => {
+                        self.touch_pressed = true;
+                        vec![move_to(&c), button(true)]
+                    }
+                    2 => {
+                        // Second doigt : le drag devient un geste
+                        let mut actions = Vec::new();
+                        if std::mem::take(&mut self.touch_pressed) {
+                            actions.push(button(false));
+                        }
+                        self.gesture = self.measure(screen);
+                        actions
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            TouchPhase::Move => {
+                let Some(index) = self.contacts.iter().position(|(id, _, _)| *id == c.id) else {
+                    return Vec::new();
+                };
+                self.contacts[index] = (c.id, c.x, c.y);
+                if self.gesture.is_some() {
+                    if index < 2 {
+                        return self.map_gesture(screen);
+                    }
+                    Vec::new()
+                } else if self.touch_pressed {
+                    vec![move_to(&c)]
+                } else {
+                    Vec::new()
+                }
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                let Some(index) = self.contacts.iter().position(|(id, _, _)| *id == c.id) else {
+                    return Vec::new();
+                };
+                self.contacts.remove(index);
+                if self.gesture.is_some() {
+                    // Le geste s'arrête avec l'un des deux doigts ; les doigts restants
+                    // n'ont plus d'effet jusqu'au relâchement complet
+                    if index < 2 {
+                        self.gesture = None;
+                    }
+                    return Vec::new();
+                }
+                if index == 0 && std::mem::take(&mut self.touch_pressed) {
+                    if c.phase == TouchPhase::Cancel {
+                        return vec![button(false)];
+                    }
+                    return vec![move_to(&c), button(false)];
+                }
+                Vec::new()
+            }
+        }
+    }
+
+    /// Centre et écartement des deux premiers doigts, en pixels hôte
+    fn measure(&self, (width, height): (i32, i32)) -> Option<Gesture> {
+        let [(_, x1, y1), (_, x2, y2)] = self.contacts.get(..2)? else {
+            return None;
+        };
+        let (w, h) = (f64::from(width), f64::from(height));
+        let (ax, ay, bx, by) = (x1 * w, y1 * h, x2 * w, y2 * h);
+        Some(Gesture {
+            centroid: ((ax + bx) / 2.0, (ay + by) / 2.0),
+            distance: ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt(),
+        })
+    }
+
+    fn map_gesture(&mut self, screen: (i32, i32)) -> Vec<PointerAction> {
+        let (Some(previous), Some(current)) = (self.gesture, self.measure(screen)) else {
+            return Vec::new();
+        };
+        self.gesture = Some(current);
+        let mut actions = Vec::new();
+
+        if self.mapping.two_finger_scroll {
+            // Défilement « naturel » : le contenu suit les doigts
+            let dx = (previous.centroid.0 - current.centroid.0).round() as i32;
+            let dy = (previous.centroid.1 - current.centroid.1).round() as i32;
+            if dx != 0 || dy != 0 {
+                actions.push(PointerAction::Mouse(MouseEvent::ScrollHighRes {
+                    delta_x: ScrollUnit::Pixel.to_high_res(dx),
+                    delta_y: ScrollUnit::Pixel.to_high_res(dy),
+                }));
+            }
+        }
+
+        if self.mapping.pinch_zoom {
+            // Écarter les doigts = zoom avant = molette vers le haut
+            let spread = (current.distance - previous.distance).round() as i32;
+            let total = self.zoom_remainder.saturating_add(ScrollUnit::Pixel.to_high_res(-spread));
+            self.zoom_remainder = total % SCROLL_UNITS_PER_NOTCH;
+            let notches = total / SCROLL_UNITS_PER_NOTCH;
+            if notches != 0 {
+                actions.push(PointerAction::Zoom { notches });
+            }
+        }
+        actions
+    }
+}
+
+fn move_to(c: &TouchContact) -> PointerAction {
+    PointerAction::Mouse(MouseEvent::MoveNormalized { x: c.x, y: c.y })
+}
+
+fn button(pressed: bool) -> PointerAction {
+    let button = MouseButton::Left;
+    PointerAction::Mouse(if pressed {
+        MouseEvent::Press { button }
+    } else {
+        MouseEvent::Release { button }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (i32, i32) = (1000, 1000);
+
+    fn contact(id: u32, phase: TouchPhase, x: f64, y: f64) -> TouchContact {
+        TouchContact { id, phase, x, y, pressure: 0.5 }
+    }
+
+    #[test]
+    fn test_single_finger_drags_with_left_button() {
+        let mut mapper = TouchMapper::default();
+        let actions: Vec<_> = [
+            contact(1, TouchPhase::Start, 0.1, 0.1),
+            contact(1, TouchPhase::Move, 0.2, 0.2),
+            contact(1, TouchPhase::End, 0.2, 0.2),
+        ]
+        .into_iter()
+        .flat_map(|c| mapper.map(PointerKind::Touch, c, SCREEN))
+        .collect();
+
+        assert_eq!(actions.len(), 5);
+        assert_eq!(actions[1], button(true));
+        assert_eq!(actions[4], button(false));
+    }
+
+    #[test]
+    fn test_active_contacts_are_capped() {
+        let mut mapper = TouchMapper::default();
+        for id in 0..MAX_TOUCH_CONTACTS as u32 + 50 {
+            mapper.map(PointerKind::Touch, contact(id, TouchPhase::Start, 0.5, 0.5), SCREEN);
+        }
+        assert_eq!(mapper.contacts.len(), MAX_TOUCH_CONTACTS);
+        assert!(!mapper.is_active(PointerKind::Touch, MAX_TOUCH_CONTACTS as u32));
+
+        // Une place libérée accepte un nouveau contact
+        mapper.map(PointerKind::Touch, contact(3, TouchPhase::End, 0.5, 0.5), SCREEN);
+        mapper.map(PointerKind::Touch, contact(100, TouchPhase::Start, 0.5, 0.5), SCREEN);
+        assert!(mapper.is_active(PointerKind::Touch, 100));
+        assert_eq!(mapper.contacts.len(), MAX_TOUCH_CONTACTS);
+    }
+
+    #[test]
+    fn test_two_finger_pan_and_pinch() {
+        let mut mapper = TouchMapper::default();
+        mapper.map(PointerKind::Touch, contact(1, TouchPhase::Start, 0.4, 0.5), SCREEN);
+        // Le second doigt relâche le bouton pressé par le premier
+        let actions = mapper.map(PointerKind::Touch, contact(2, TouchPhase::Start, 0.6, 0.5), SCREEN);
+        assert_eq!(actions, vec![button(false)]);
+
+        // Les deux doigts montent de 100 px : défilement vers le bas
+        mapper.map(PointerKind::Touch, contact(1, TouchPhase::Move, 0.4, 0.4), SCREEN);
+        let actions = mapper.map(PointerKind::Touch, contact(2, TouchPhase::Move, 0.6, 0.4), SCREEN);
+        assert!(matches!(
+            actions.as_slice(),
+            [PointerAction::Mouse(MouseEvent::ScrollHighRes { delta_x: 0, delta_y }), ..] if *delta_y > 0
+        ));
+
+        // Écartement de 200 px : zoom avant (crans négatifs)
+        let actions = mapper.map(PointerKind::Touch, contact(2, TouchPhase::Move, 0.8, 0.4), SCREEN);
+        assert!(actions.iter().any(|a| matches!(a, PointerAction::Zoom { notches } if *notches < 0)));
+    }
+
+    #[test]
+    fn test_disabled_inputs_are_ignored() {
+        let mut mapper = TouchMapper::new(TouchMapping {
+            pen_enabled: false,
+            ..TouchMapping::default()
+        });
+        assert!(mapper.map(PointerKind::Pen, contact(0, TouchPhase::Start, 0.5, 0.5), SCREEN).is_empty());
+        assert!(!mapper.map(PointerKind::Touch, contact(0, TouchPhase::Start, 0.5, 0.5), SCREEN).is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+//! File des transferts de fichiers
+//!
+//! Chaque envoi et chaque réception y a une entrée avec son état (en attente,
+//! actif, en pause, terminé, échoué), sa progression, son débit et une estimation
+//! du temps restant. Les envois attendent une place : au plus `max_active`
+//! s'exécutent en même temps, dans l'ordre d'arrivée. Les réceptions, pilotées
+//! par l'émetteur, n'occupent pas de place ; une annonce en attente de
+//! confirmation est « en attente », une réception suspendue par une déconnexion
+//! est « en pause ».
+//!
+//! Chaque changement est publié aux abonnés (`subscribe`), l'UI Tauri comme un
+//! outil sans interface.
+
+use crate::error::{GhostHandError, Result};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{broadcast, Notify};
+use tokio::time::Duration;
+
+/// Envois simultanés par défaut
+pub const DEFAULT_MAX_ACTIVE: usize = 2;
+/// Intervalle minimal entre deux événements de progression d'un transfert
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Événements gardés pour un abonné lent avant qu'il ne décroche
+const EVENT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferDirection {
+    Send,
+    Receive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    Queued,
+    Active,
+    Paused,
+    Done,
+    Failed,
+}
+
+impl TransferStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, TransferStatus::Done | TransferStatus::Failed)
+    }
+}
+
+/// État publié d'un transfert (un dossier compte pour un seul transfert)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferInfo {
+    pub id: String,
+    pub name: String,
+    pub direction: TransferDirection,
+    pub status: TransferStatus,
+    pub size: u64,
+    /// Octets transférés (acquittés par le destinataire pour un envoi)
+    pub transferred: u64,
+    /// Débit lissé (octets/s)
+    pub bytes_per_sec: f64,
+    /// Temps restant estimé, en secondes (transfert actif au débit connu)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Changement publié aux abonnés
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TransferEvent {
+    Updated(TransferInfo),
+    Removed { id: String },
+}
+
+struct Entry {
+    info: TransferInfo,
+    /// Dernier échantillon du débit (instant, octets transférés)
+    sample: (Instant, u64),
+}
+
+impl Entry {
+    /// Mettre à jour le débit (moyenne mobile exponentielle) et le temps restant
+    fn sample(&mut self) {
+        let (last, last_bytes) = self.sample;
+        let secs = last.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            let instant = self.info.transferred.saturating_sub(last_bytes) as f64 / secs;
+            let rate = self.info.bytes_per_sec;
+            self.info.bytes_per_sec = if rate == 0.0 { instant } else { 0.7 * rate + 0.3 * instant };
+        }
+        self.sample = (Instant::now(), self.info.transferred);
+        self.refresh_eta();
+    }
+
+    fn refresh_eta(&mut self) {
+        let remaining = self.info.size.saturating_sub(self.info.transferred);
+        self.info.eta_secs = (self.info.status == TransferStatus::Active && self.info.bytes_per_sec > 0.0)
+            .then(|| (remaining as f64 / self.info.bytes_per_sec).ceil() as u64);
+    }
+}
+
+struct Inner {
+    /// Entrées dans l'ordre d'arrivée
+    entries: Vec<Entry>,
+    max_active: usize,
+}
+
+/// File des transferts, partagée (clonable) entre le gestionnaire de transferts,
+/// les tâches d'envoi et les abonnés
+#[derive(Clone)]
+pub struct TransferQueue {
+    inner: Arc<Mutex<Inner>>,
+    events: broadcast::Sender<TransferEvent>,
+    /// Réveille les envois en attente d'une place
+    changed: Arc<Notify>,
+}
+
+impl Default for TransferQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ACTIVE)
+    }
+}
+
+impl TransferQueue {
+    pub fn new(max_active: usize) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(Inner { entries: Vec::new(), max_active: max_active.max(1) })),
+            events,
+            changed: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Modifier le nombre d'envois simultanés (les envois actifs continuent)
+    pub fn set_max_active(&self, max_active: usize) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.max_active = max_active.max(1);
+        }
+        self.changed.notify_waiters();
+    }
+
+    /// S'abonner aux changements. Un abonné trop lent perd des événements
+    /// (`RecvError::Lagged`) et doit se resynchroniser avec `snapshot`.
+    pub fn subscribe(&self) -> broadcast::Receiver<TransferEvent> {
+        self.events.subscribe()
+    }
+
+    /// État de tous les transferts, dans l'ordre d'arrivée
+    pub fn snapshot(&self) -> Vec<TransferInfo> {
+        self.inner.lock()
+            .map(|inner| inner.entries.iter().map(|e| e.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<TransferInfo> {
+        let inner = self.inner.lock().ok()?;
+        inner.entries.iter().find(|e| e.info.id == id).map(|e| e.info.clone())
+    }
+
+    /// Ajouter un transfert en attente. Une entrée terminée du même id est
+    /// remplacée ; retourne false si un transfert de cet id est déjà en cours.
+    pub fn enqueue(&self, id: &str, name: &str, direction: TransferDirection, size: u64) -> bool {
+        let Ok(mut inner) = self.inner.lock() else {
+            return false;
+        };
+        if let Some(pos) = inner.entries.iter().position(|e| e.info.id == id) {
+            if !inner.entries[pos].info.status.is_finished() {
+                return false;
+            }
+            inner.entries.remove(pos);
+        }
+        let info = TransferInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            direction,
+            status: TransferStatus::Queued,
+            size,
+            transferred: 0,
+            bytes_per_sec: 0.0,
+            eta_secs: None,
+            error: None,
+        };
+        let _ = self.events.send(TransferEvent::Updated(info.clone()));
+        inner.entries.push(Entry { info, sample: (Instant::now(), 0) });
+        true
+    }
+
+    /// Démarrer un transfert sans attendre de place (réception)
+    pub fn start(&self, id: &str) -> bool {
+        self.modify(id, |entry| {
+            if entry.info.status.is_finished() {
+                return false;
+            }
+            entry.info.status = TransferStatus::Active;
+            entry.sample = (Instant::now(), entry.info.transferred);
+            true
+        })
+    }
+
+    /// Attendre qu'un envoi puisse s'exécuter : son tour dans la file et une place
+    /// libre. Appelé aussi avant chaque chunk : un envoi mis en pause libère sa
+    /// place et attend ici sa reprise. Échoue si le transfert est terminé ou retiré.
+    pub async fn acquire(&self, id: &str) -> Result<()> {
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.try_activate(id)? {
+                return Ok(());
+            }
+            notified.await;
+        }
+    }
+
+    fn try_activate(&self, id: &str) -> Result<bool> {
+        let mut inner = self.inner.lock().map_err(|_| GhostHandError::Internal("File des transferts inaccessible".into()))?;
+        let Some(pos) = inner.entries.iter().position(|e| e.info.id == id) else {
+            return Err(GhostHandError::TransferCancelled { id: id.to_string(), reason: "retiré de la file".into() });
+        };
+        let info = &inner.entries[pos].info;
+        if info.status.is_finished() {
+            let reason = info.error.clone().unwrap_or_else(|| "transfert terminé".into());
+            return Err(GhostHandError::TransferCancelled { id: id.to_string(), reason });
+        }
+        match info.status {
+            TransferStatus::Active => return Ok(true),
+            TransferStatus::Paused => return Ok(false),
+            _ => {}
+        }
+        let sends = || inner.entries.iter().filter(|e| e.info.direction == TransferDirection::Send);
+        let active = sends().filter(|e| e.info.status == TransferStatus::Active).count();
+        let first = sends().find(|e| e.info.status == TransferStatus::Queued).is_some_and(|e| e.info.id == id);
+        if active >= inner.max_active || !first {
+            return Ok(false);
+        }
+        let entry = &mut inner.entries[pos];
+        entry.info.status = TransferStatus::Active;
+        entry.sample = (Instant::now(), entry.info.transferred);
+        let _ = self.events.send(TransferEvent::Updated(entry.info.clone()));
+        Ok(true)
+    }
+
+    /// Progression absolue d'un transfert
+    pub fn update(&self, id: &str, transferred: u64) {
+        self.progress(id, |info| info.transferred = transferred);
+    }
+
+    /// Progression relative (octets nouvellement reçus)
+    pub fn advance(&self, id: &str, bytes: u64) {
+        self.progress(id, |info| info.transferred += bytes);
+    }
+
+    fn progress(&self, id: &str, f: impl FnOnce(&mut TransferInfo)) {
+        let Ok(mut inner) = self.inner.lock() else { return };
+        let Some(entry) = inner.entries.iter_mut().find(|e| e.info.id == id) else { return };
+        if entry.info.status.is_finished() {
+            return;
+        }
+        f(&mut entry.info);
+        entry.info.transferred = entry.info.transferred.min(entry.info.size);
+        if entry.sample.0.elapsed() >= PROGRESS_INTERVAL {
+            entry.sample();
+            let _ = self.events.send(TransferEvent::Updated(entry.info.clone()));
+        }
+    }
+
+    /// Mettre un envoi en pause (en attente ou actif) : il libère sa place avant
+    /// son prochain chunk. Une réception ne peut pas être mise en pause.
+    pub fn pause(&self, id: &str) -> bool {
+        self.modify(id, |entry| {
+            let pausable = entry.info.direction == TransferDirection::Send
+                && matches!(entry.info.status, TransferStatus::Queued | TransferStatus::Active);
+            if pausable {
+                entry.info.status = TransferStatus::Paused;
+            }
+            pausable
+        })
+    }
+
+    /// Remettre dans la file un envoi en pause ; il reprend à son tour
+    pub fn resume(&self, id: &str) -> bool {
+        self.modify(id, |entry| {
+            let paused = entry.info.direction == TransferDirection::Send && entry.info.status == TransferStatus::Paused;
+            if paused {
+                entry.info.status = TransferStatus::Queued;
+            }
+            paused
+        })
+    }
+
+    /// Suspendre une réception interrompue par une déconnexion (reprise possible)
+    pub fn suspend(&self, id: &str) -> bool {
+        self.modify(id, |entry| {
+            let active = entry.info.status == TransferStatus::Active;
+            if active {
+                entry.info.status = TransferStatus::Paused;
+            }
+            active
+        })
+    }
+
+    /// Terminer un transfert : réussi (`error` = None) ou échoué. Sans effet
+    /// sur un transfert déjà terminé.
+    pub fn finish(&self, id: &str, error: Option<String>) -> bool {
+        self.modify(id, |entry| {
+            if entry.info.status.is_finished() {
+                return false;
+            }
+            match error {
+                None => {
+                    entry.info.status = TransferStatus::Done;
+                    entry.info.transferred = entry.info.size;
+                }
+                Some(error) => {
+                    entry.info.status = TransferStatus::Failed;
+                    entry.info.error = Some(error);
+                }
+            }
+            true
+        })
+    }
+
+    /// Retirer les transferts terminés ; retourne leur nombre
+    pub fn clear_finished(&self) -> usize {
+        let Ok(mut inner) = self.inner.lock() else {
+            return 0;
+        };
+        let before = inner.entries.len();
+        inner.entries.retain(|entry| {
+            let finished = entry.info.status.is_finished();
+            if finished {
+                let _ = self.events.send(TransferEvent::Removed { id: entry.info.id.clone() });
+            }
+            !finished
+        });
+        before - inner.entries.len()
+    }
+
+    /// Appliquer un changement d'état ; publié (et les envois en attente
+    /// réveillés) si `f` retourne true
+    fn modify(&self, id: &str, f: impl FnOnce(&mut Entry) -> bool) -> bool {
+        let changed = {
+            let Ok(mut inner) = self.inner.lock() else {
+                return false;
+            };
+            let Some(entry) = inner.entries.iter_mut().find(|e| e.info.id == id) else {
+                return false;
+            };
+            let changed = f(entry);
+            if changed {
+                if entry.info.status != TransferStatus::Active {
+                    entry.info.bytes_per_sec = 0.0;
+                }
+                entry.refresh_eta();
+                let _ = self.events.send(TransferEvent::Updated(entry.info.clone()));
+            }
+            changed
+        };
+        if changed {
+            self.changed.notify_waiters();
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(queue: &TransferQueue, id: &str) -> Option<TransferStatus> {
+        queue.get(id).map(|info| info.status)
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_and_pause() {
+        let queue = TransferQueue::new(1);
+        let mut events = queue.subscribe();
+        for id in ["ft-1", "ft-2"] {
+            assert!(queue.enqueue(id, id, TransferDirection::Send, 100));
+        }
+        assert!(!queue.enqueue("ft-1", "ft-1", TransferDirection::Send, 100));
+        // Une réception n'occupe pas de place
+        assert!(queue.enqueue("ft-r", "r", TransferDirection::Receive, 10));
+        assert!(queue.start("ft-r"));
+
+        queue.acquire("ft-1").await.unwrap();
+        let second = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("ft-2").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(status(&queue, "ft-2"), Some(TransferStatus::Queued));
+
+        // La pause libère la place ; à sa reprise, ft-1 attend son tour
+        assert!(queue.pause("ft-1"));
+        second.await.unwrap().unwrap();
+        assert_eq!(status(&queue, "ft-2"), Some(TransferStatus::Active));
+        assert!(queue.resume("ft-1"));
+        let first = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("ft-1").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(status(&queue, "ft-1"), Some(TransferStatus::Queued));
+        assert!(queue.finish("ft-2", None));
+        first.await.unwrap().unwrap();
+
+        // Un envoi terminé pendant qu'il attend échoue
+        assert!(queue.pause("ft-1"));
+        let paused = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire("ft-1").await }
+        });
+        assert!(queue.finish("ft-1", Some("annulé".into())));
+        assert!(matches!(paused.await.unwrap(), Err(GhostHandError::TransferCancelled { .. })));
+        assert!(!queue.finish("ft-1", None));
+        assert!(!queue.pause("ft-r"));
+
+        assert_eq!(queue.clear_finished(), 2);
+        assert_eq!(queue.snapshot().len(), 1);
+        let mut removed = 0;
+        while let Ok(event) = events.try_recv() {
+            if let TransferEvent::Removed { .. } = event {
+                removed += 1;
+            }
+        }
+        assert_eq!(removed, 2);
+    }
+
+    #[test]
+    fn test_progress_speed_and_eta() {
+        let queue = TransferQueue::default();
+        let mut events = queue.subscribe();
+        queue.enqueue("ft-1", "data.bin", TransferDirection::Receive, 1000);
+        queue.start("ft-1");
+        std::thread::sleep(PROGRESS_INTERVAL);
+        queue.advance("ft-1", 400);
+
+        let info = queue.get("ft-1").unwrap();
+        assert_eq!(info.transferred, 400);
+        assert!(info.bytes_per_sec > 0.0);
+        assert!(info.eta_secs.is_some_and(|eta| eta >= 1));
+        let last = std::iter::from_fn(|| events.try_recv().ok()).last();
+        assert_eq!(last, Some(TransferEvent::Updated(info)));
+
+        queue.finish("ft-1", None);
+        let info = queue.get("ft-1").unwrap();
+        assert_eq!((info.status, info.transferred, info.eta_secs), (TransferStatus::Done, 1000, None));
+    }
+}
@@ -6,6 +6,7 @@ use ghost_hand_client::validation::{
     validate_password, ClientRateLimiter, sanitize_for_logging
 };
 use ghost_hand_client::input_control::{InputController, KeyModifiers};
+use ghost_hand_client::protocol::{ClipboardMime, ControlMessage, KeyInputMode, ScrollUnit, TouchPhase};
 use std::time::Duration;
 
 #[test]
@@ -156,6 +157,48 @@ fn test_combined_attack_scenarios() {
     assert!(InputController::is_key_blocked("r", &modifiers)); // Win+R bloqué
 }
 
+#[test]
+fn test_view_only_blocks_all_remote_input() {
+    // Tout message agissant sur l'hôte doit être identifié par le filtre lecture seule
+    let remote_inputs = vec![
+        ControlMessage::MouseMove { x: 10, y: 10 },
+        ControlMessage::MouseMoveNormalized { x: 0.5, y: 0.5 },
+        ControlMessage::MouseClick { button: "left".to_string(), pressed: true },
+        ControlMessage::MouseDoubleClick { button: "back".to_string() },
+        ControlMessage::MouseScroll { delta: 3, delta_x: -1, unit: ScrollUnit::HighRes },
+        ControlMessage::Touch { id: 1, phase: TouchPhase::Start, x: 0.5, y: 0.5, pressure: 1.0 },
+        ControlMessage::Pen { id: 0, phase: TouchPhase::Move, x: 0.5, y: 0.5, pressure: 0.0 },
+        ControlMessage::SendKeySequence { name: "ctrl-alt-del".to_string() },
+        ControlMessage::KeyPress {
+            key: "a".to_string(),
+            pressed: true,
+            modifiers: None,
+            mode: KeyInputMode::Logical,
+            hid_usage: None,
+            layout: None,
+        },
+        ControlMessage::ClipboardSync {
+            content: "rm -rf /".to_string(),
+            mime: ClipboardMime::TextPlain,
+            alt_text: None,
+        },
+    ];
+    for msg in &remote_inputs {
+        assert!(msg.remote_input_kind().is_some(), "non filtré: {:?}", msg);
+    }
+
+    // Les messages de pilotage de session restent autorisés
+    assert!(ControlMessage::SelectDisplay { display_id: 1 }.remote_input_kind().is_none());
+    assert!(ControlMessage::Ping.remote_input_kind().is_none());
+
+    // La notification de permission fait l'aller-retour sur le fil
+    let bytes = ControlMessage::PermissionChanged { view_only: true }.to_bytes().unwrap();
+    match ControlMessage::from_bytes(&bytes).unwrap() {
+        ControlMessage::PermissionChanged { view_only } => assert!(view_only),
+        other => panic!("message inattendu: {:?}", other),
+    }
+}
+
 #[cfg(test)]
 mod stress_tests {
     use super::*;